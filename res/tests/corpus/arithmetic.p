@@ -0,0 +1,6 @@
+let a: int = 60 * 60 * 24;
+let b: float = 1.5 * 4 - 2;
+let c: int = a % 7 - -3;
+let d: bool = !(a > 100) || b <= 4.0 && c != 3;
+let e: string = "con" + "cat";
+let f: bool = e == "concat";
@@ -0,0 +1,14 @@
+let grid: array = [[1, 2], [3, 4], [5]];
+let row: array = grid[1];
+row[0] = 30;
+grid[2] = [50, 60];
+let shared: int = grid[1][0];
+let copy: int = grid[0][1];
+copy = 20;
+let first: int = grid[0][1];
+let i: int = 0;
+let sum: int = 0;
+while (i < 2) {
+    sum = sum + grid[2][i];
+    i = i + 1;
+}
@@ -0,0 +1,10 @@
+function sum(n: int): int {
+    let s: int = 0;
+    let i: int = 0;
+    while (i < n) {
+        s = s + i % 7;
+        i = i + 1;
+    }
+    s
+}
+let summed: int = sum(30000);
@@ -0,0 +1,17 @@
+function popcount(n: int): int {
+    let count: int = 0;
+    while (n != 0) {
+        count = count + (n & 1);
+        n = n >> 1;
+    }
+    count
+}
+let flags: int = 1 << 3 | 1 << 1;
+let toggled: int = flags ^ 2;
+let inverted: int = ~flags & 255;
+let bits: int = popcount(255);
+let tower: int = 2 ** 3 ** 2;
+let negated: int = -2 ** 2;
+let big: bigint = 3n ** 90 >> 10;
+let cube: decimal = 0.5d ** 3;
+let overflow: int = 10 ** 40;
@@ -0,0 +1,5 @@
+let i: int = 0;
+while (i < 3) {
+    i = i + 1;
+    if (i == 2) { if (i) { 1 } }
+}
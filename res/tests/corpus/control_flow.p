@@ -0,0 +1,21 @@
+let values: array = [4, 8, 15, 16];
+function find(wanted: int): int {
+    let i: int = 0;
+    while (true) {
+        if (values[i] == wanted) { ret i; }
+        i = i + 1;
+    }
+    ret 0;
+}
+function classify(n: int): string {
+    if (n < 0) { "negative" } else { if (n == 0) { "zero" } else { "positive" } }
+}
+function early(n: int): int {
+    if (n > 0) { ret n * 2; }
+    n
+}
+let position: int = find(15);
+let negative: string = classify(-3);
+let zero: string = classify(0);
+let doubled: int = early(21);
+let block: int = if (true) { let inner: int = 2; inner * inner } else { 0 };
@@ -0,0 +1,7 @@
+function check(n: int): int {
+    if (n < 1) { ret err("n must be positive"); }
+    n
+}
+let message: int = err("failed");
+let ok: int = check(3);
+let failed: int = check(0);
@@ -0,0 +1,17 @@
+function fibo(n: int): int {
+    if (n < 2) { ret n; }
+    fibo(n - 1) + fibo(n - 2)
+}
+function iterative(n: int): int {
+    let a: int = 0;
+    let b: int = 1;
+    while (n > 0) {
+        let tmp: int = a + b;
+        a = b;
+        b = tmp;
+        n = n - 1;
+    }
+    a
+}
+let recursive: int = fibo(15);
+let looped: int = iterative(50);
@@ -0,0 +1,12 @@
+let item: string = "apple";
+let count: int = 3;
+let price: float = 0.5;
+let total: decimal = 1.5d;
+let line: string = f"{item:<8}|{count:>3}|{price:.2}|{total:.3}";
+let header: string = f"{"name":*^10}";
+let values: array = [1, [2, 3]];
+let pair: (int, bool) = (count, true);
+let summary: string = f"values = {values}, pair = {pair}, {{escaped}}";
+let formatted: string = format(count * 2, "^7");
+let converted: string = to_string(123n) + to_string(2.0);
+let broken: string = format(count, ".1");
@@ -0,0 +1,7 @@
+let keep: array = [[1, 2], [3]];
+let i: int = 0;
+while (i < 600) {
+    let garbage: array = [i, [i, i]];
+    i = i + 1;
+}
+let kept: int = keep[0][1];
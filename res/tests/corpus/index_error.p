@@ -0,0 +1,10 @@
+function inner(n: int): int {
+    let a: array = [1, 2];
+    if (n > 0) { a[n] } else { 0 }
+}
+function outer(n: int): int {
+    inner(n + 1)
+}
+let before: int = outer(0);
+outer(2);
+let after: int = 1;
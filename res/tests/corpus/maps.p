@@ -0,0 +1,23 @@
+let stock: map<string, int> = { "apple": 3, "pear": 0 };
+stock["plum"] = 7;
+stock["apple"] = stock["apple"] - 1;
+let shared: map<string, int> = stock;
+shared["kiwi"] = 2;
+function total(items: map<string, int>): int {
+    let pairs: array = entries(items);
+    let sum: int = 0;
+    let i: int = 0;
+    while (i < len(pairs)) {
+        sum = sum + pairs[i][1];
+        i = i + 1;
+    }
+    sum
+}
+let sum: int = total(stock);
+let sold: int = remove(stock, "pear");
+let names: array = keys(stock);
+let counts: array = values(stock);
+let known: bool = contains(stock, "plum") && !contains(stock, "pear");
+let grid: map<int, map<int, bool>> = { 0: { 0: true } };
+grid[0][1] = false;
+let missing: int = stock["banana"];
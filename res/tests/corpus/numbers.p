@@ -0,0 +1,19 @@
+function factorial(n: bigint): bigint {
+    let result: bigint = 1n;
+    while (n > 1) {
+        result = result * n;
+        n = n - 1;
+    }
+    result
+}
+let big: bigint = factorial(40n);
+let digits: bigint = big / 1000000000000000000000000000000n;
+let price: decimal = 19.99d;
+let total: decimal = price * 3 + 0.1d + 0.2d;
+let exact: bool = 0.1d + 0.2d == 0.3d;
+let share: decimal = 10d / 4;
+let back: int = to_int(total);
+let approximate: float = to_float(price);
+let parsed: decimal = to_decimal("1.005") * 2;
+let widened: bigint = to_bigint(170141183460469231731687303715884105727) + 1;
+let mixed: decimal = 1.5 + 1d;
@@ -0,0 +1,10 @@
+function churn(n: int): int {
+    let i: int = 0;
+    while (i < 600) {
+        let garbage: array = [i, [i, i]];
+        i = i + 1;
+    }
+    n
+}
+let picked: int = [[1, 2], [3, 4]][churn(1)][0];
+let summed: int = [5, 6][churn(1)] + [[7]][0][churn(0)];
@@ -0,0 +1,8 @@
+let max: int = 170141183460469231731687303715884105727;
+let wrapped: int = wrapping_add(max, 1);
+let saturated: int = saturating_mul(max, 2);
+let lowest: int = saturating_sub(-max, 10);
+let product: int = wrapping_mul(3, 4);
+let quotient: int = -7 / 2;
+let remainder: int = -7 % 2;
+let overflow: int = max + 1;
@@ -0,0 +1,9 @@
+let a: int = 1;
+let r: int = &a;
+r = 5;
+let b: int = a;
+b = 7;
+let x: int = 3;
+let y: int = x + (x = 10);
+let values: array = [a, &b];
+b = 8;
@@ -0,0 +1,6 @@
+function name(n: int): string {
+    if (n > 0) { ret "positive"; }
+    n
+}
+let a: string = name(1);
+let b: string = name(0);
@@ -0,0 +1,14 @@
+let values: array = [4, 2, 0, 7];
+let calls: int = 0;
+function touch(result: bool): bool {
+    calls = calls + 1;
+    result
+}
+let i: int = 0;
+while (i < len(values) && values[i] > 0) {
+    i = i + 1;
+}
+let first: bool = false && touch(true);
+let second: bool = true || touch(false);
+let third: bool = true && touch(false) || touch(true);
+let beyond: bool = i >= len(values) || values[i] == 0;
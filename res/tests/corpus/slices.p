@@ -0,0 +1,17 @@
+let values: array = [3, 1, 4, 1, 5, 9, 2, 6];
+let front: array = values[..3];
+let back: array = values[5..];
+let inner: array = values[2..-2];
+let whole: array = values[..];
+whole[0] = 30;
+let last: int = values[-1];
+values[-2] = 20;
+let greeting: string = "hello world";
+let first_word: string = greeting[..5];
+let last_char: string = greeting[-1];
+function window(from: int, to: int): int {
+    let numbers: array = [0, 1, 2, 3, 4, 5];
+    len(numbers[from..to])
+}
+let size: int = window(1, 4);
+let broken: array = values[4..2];
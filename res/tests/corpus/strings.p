@@ -0,0 +1,17 @@
+let sentence: string = "  The quick brown fox  ";
+let words: array = split(trim(sentence), " ");
+let count: int = len(words);
+let initials: string = "";
+let i: int = 0;
+while (i < count) {
+    initials = initials + to_upper(words[i][0]);
+    i = i + 1;
+}
+let shout: string = to_upper(join(words, "_"));
+let quiet: string = to_lower(shout);
+let fox: bool = contains(sentence, "fox") && starts_with(trim(sentence), "The");
+let swapped: string = replace(quiet, "_", " ");
+let letters: array = chars("abc");
+let piece: string = substring(swapped, 4, 9);
+let sorted: bool = "abc" < "abd" && "b" > "a" && "a" <= "a";
+let broken: string = sentence[100];
@@ -0,0 +1,17 @@
+let (minutes, seconds): (int, int) = divmod(135, 60);
+function stats(a: int, b: int, c: int): (int, int) {
+    let low: int = a;
+    let high: int = a;
+    if (b < low) { low = b; }
+    if (c < low) { low = c; }
+    if (b > high) { high = b; }
+    if (c > high) { high = c; }
+    ret (low, high);
+}
+let (low, high): (int, int) = stats(4, 9, 1);
+let point: (int, (string, bool)) = (3, ("x", true));
+let (x, (label, shown)): (int, (string, bool)) = point;
+let [first, ..others]: array = [1, 2, 3];
+let [start, ..inner, end]: array = [5, 6, 7, 8];
+others[0] = 10;
+let (a, b, c): (int, int, int) = divmod(1, 2);
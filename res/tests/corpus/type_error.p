@@ -0,0 +1,5 @@
+function name(n: int): string {
+    n
+}
+let a: int = 1;
+a = "text";
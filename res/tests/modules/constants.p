@@ -0,0 +1 @@
+pub let day: int = 60 * 60 * 24;
@@ -0,0 +1,2 @@
+import "cycle_b";
+pub let a: int = 1;
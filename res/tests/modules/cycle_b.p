@@ -0,0 +1,2 @@
+import { a } from "cycle_a.p";
+pub let b: int = a;
@@ -0,0 +1,6 @@
+import { square } from "../math";
+pub function area(width: int, height: int): int {
+    if (width == height) { ret square(width); }
+    ret width * height;
+}
+pub let unit: int = area(1, 1);
@@ -0,0 +1,12 @@
+pub let runs: int = 0;
+runs = runs + 1;
+let factor: int = 2;
+function twice(x: int): int {
+    ret x * factor;
+}
+pub function square(x: int): int {
+    ret x * x;
+}
+pub function quadruple(x: int): int {
+    ret twice(twice(x));
+}
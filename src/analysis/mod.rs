@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::vec::IntoIter;
+use crate::error::TypeError;
+use crate::error::TypeError::{InvalidAmountOfArguments, MismatchedType};
+use crate::evaluator::builtins::builtin;
+use crate::lexer::token::{Span, Token};
+use crate::parser::ast::expression::{Expression, FormatPart};
+use crate::parser::ast::pattern::Pattern;
+use crate::parser::ast::statement::Statement;
+use crate::parser::layout::{Declaration, Layout, Lines, StatementLayout};
+
+/// Kind of declaration a symbol comes from
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum SymbolKind {
+    Variable,
+    Parameter,
+    Function,
+    Import,
+}
+
+/// Name which is declared in a program
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    // Declared type or the return type of a function, imported names have none
+    pub typee: Option<Token>,
+    pub parameters: Vec<(String, Token)>,
+    // Position of the name inside the declaration
+    pub span: Span,
+    // Lines of the statement which declares the symbol
+    pub lines: Lines,
+    // Last line the symbol can be used on
+    pub end: usize,
+    // Function the symbol is declared in
+    pub parent: Option<usize>,
+    // Symbol with the same name which is hidden by this one
+    pub shadows: Option<usize>,
+    // Whether the declaration is marked with `pub`
+    pub exported: bool,
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let typee = self.typee.as_ref().map(Token::type_name).unwrap_or_default();
+        match self.kind {
+            SymbolKind::Variable => write!(f, "let {}: {}", self.name, typee),
+            SymbolKind::Parameter => write!(f, "{}: {}", self.name, typee),
+            SymbolKind::Function => {
+                let parameters = self.parameters.iter()
+                    .map(|(name, typee)| format!("{}: {}", name, typee.type_name()))
+                    .collect::<Vec<String>>();
+                write!(f, "function {}({}): {}", self.name, parameters.join(", "), typee)
+            }
+            SymbolKind::Import => write!(f, "import {}", self.name),
+        }
+    }
+}
+
+/// Usage of a name, names of builtins and namespaces have no symbol
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub name: String,
+    pub span: Span,
+    pub symbol: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub error: TypeError,
+}
+
+/// Symbols, references and type errors of a program, which tools can look up by position
+#[derive(Debug, Default)]
+pub struct Analysis {
+    pub symbols: Vec<Symbol>,
+    pub references: Vec<Reference>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Analysis {
+    /// Symbol which is declared or used at the position
+    pub fn symbol_at(&self, position: Span) -> Option<&Symbol> {
+        if let Some(reference) = self.references.iter().find(|reference| covers(reference.span, &reference.name, position)) {
+            return reference.symbol.map(|symbol| &self.symbols[symbol])
+        }
+        self.symbols.iter().find(|symbol| covers(symbol.span, &symbol.name, position))
+    }
+
+    /// Symbols which can be used at the position, inner declarations hide outer ones with the same name
+    pub fn visible(&self, position: Span) -> Vec<&Symbol> {
+        let mut visible: Vec<&Symbol> = vec![];
+        for symbol in &self.symbols {
+            // Global functions can be called before they are declared
+            let hoisted = symbol.kind == SymbolKind::Function && symbol.parent.is_none();
+            let declared = (symbol.span.line, symbol.span.column) < (position.line, position.column);
+            if !(hoisted || declared) || position.line > symbol.end { continue }
+
+            visible.retain(|other| other.name != symbol.name);
+            visible.push(symbol);
+        }
+        visible
+    }
+
+    /// Symbols which are declared directly inside the function, or at the top level without one
+    pub fn children(&self, parent: Option<usize>) -> impl Iterator<Item = (usize, &Symbol)> {
+        self.symbols.iter().enumerate().filter(move |(_, symbol)| symbol.parent == parent)
+    }
+}
+
+/// Whether the name written at the span contains the position
+fn covers(span: Span, name: &str, position: Span) -> bool {
+    span.line == position.line && (span.column..span.column + name.chars().count()).contains(&position.column)
+}
+
+/// Binds the names of a parsed program to their declarations and checks the types which are known before it runs.
+/// The layout has to be the one of the parser which parsed the statements, it provides the positions of the declarations.
+pub fn analyze(statements: &[Statement], layout: Layout) -> Analysis {
+    let mut analyzer = Analyzer {
+        analysis: Analysis::default(),
+        layout: Queues {
+            statements: layout.statements.into_iter(),
+            blocks: layout.blocks.into_iter(),
+            declarations: layout.declarations.into_iter(),
+        },
+        globals: HashMap::new(),
+        scopes: vec![],
+        function: None,
+        returns: None,
+        statement: StatementLayout::default(),
+        exported: false,
+        pending: vec![],
+        calls: vec![],
+    };
+
+    for statement in statements {
+        analyzer.statement(statement);
+    }
+    analyzer.finish();
+    analyzer.analysis
+}
+
+#[derive(Default)]
+struct Queues {
+    statements: IntoIter<StatementLayout>,
+    blocks: IntoIter<Lines>,
+    declarations: IntoIter<Declaration>,
+}
+
+struct Scope {
+    names: HashMap<String, usize>,
+    end: usize,
+}
+
+/// Call whose arguments get checked once every function of the program is known
+struct Call {
+    reference: usize,
+    arguments: Vec<Option<Token>>,
+}
+
+struct Analyzer {
+    analysis: Analysis,
+    // Positions of the parser, consumed in the order the statements are visited in
+    layout: Queues,
+    globals: HashMap<String, usize>,
+    scopes: Vec<Scope>,
+    // Function which is being analyzed and its return type
+    function: Option<usize>,
+    returns: Option<Token>,
+    // Layout of the statement which is being analyzed and whether it is marked with `pub`
+    statement: StatementLayout,
+    exported: bool,
+    // References inside functions which may point to globals that are declared later
+    pending: Vec<usize>,
+    calls: Vec<Call>,
+}
+
+impl Analyzer {
+    /// Visits the statement and returns the type of its value, if it is known without running the program
+    fn statement(&mut self, statement: &Statement) -> Option<Token> {
+        let layout = self.layout.statements.next().unwrap_or_default();
+        let outer = std::mem::replace(&mut self.statement, layout);
+
+        let typee = match statement {
+            Statement::Let { name, typee, value } => {
+                let span = self.declaration();
+                let found = self.expression(value);
+                self.check(typee, found, span);
+                self.declare(name, SymbolKind::Variable, Some(typee.clone()), vec![], span);
+                Some(typee.clone())
+            }
+            Statement::Destructure { pattern, typee, value } => {
+                let spans = pattern.names().iter().map(|_| self.declaration()).collect::<Vec<Span>>();
+                self.expression(value);
+                for ((name, typee), span) in bindings(pattern, Some(typee)).into_iter().zip(spans) {
+                    self.declare(&name, SymbolKind::Variable, typee, vec![], span);
+                }
+                None
+            }
+            Statement::Return { value } => {
+                let found = self.expression(value);
+                let span = self.statement.span;
+                // Functions returning null drop the value of their return
+                if let Some(returns) = self.returns.clone().filter(|returns| !returns.equal_variant(&Token::NullType)) {
+                    self.check(&returns, found, span);
+                }
+                None
+            }
+            Statement::Expression { value } => self.expression(value),
+            Statement::Function { name, parameter, typee, body } => {
+                let span = self.declaration();
+                let function = self.declare(name, SymbolKind::Function, Some(typee.clone()), parameter.clone(), span);
+
+                let outer_function = self.function.replace(function);
+                let outer_returns = self.returns.replace(typee.clone());
+                self.scopes.push(Scope { names: HashMap::new(), end: self.statement.lines.end });
+                for (name, typee) in parameter {
+                    let span = self.declaration();
+                    self.declare(name, SymbolKind::Parameter, Some(typee.clone()), vec![], span);
+                }
+                // The value of the last statement is returned if the body does not return before
+                let found = self.expression(body);
+                if !typee.equal_variant(&Token::NullType) {
+                    self.check(typee, found, span);
+                }
+                self.scopes.pop();
+                self.function = outer_function;
+                self.returns = outer_returns;
+                None
+            }
+            Statement::Import { names, .. } => {
+                for (_, binding) in names.iter().flatten() {
+                    let span = self.declaration();
+                    self.declare(binding, SymbolKind::Import, None, vec![], span);
+                }
+                None
+            }
+            Statement::Export { statement } => {
+                self.exported = true;
+                self.statement(statement);
+                self.exported = false;
+                None
+            }
+        };
+
+        self.statement = outer;
+        typee
+    }
+
+    /// Visits the expression and returns its type, if it is known without running the program
+    fn expression(&mut self, expression: &Expression) -> Option<Token> {
+        match expression {
+            Expression::Identifier { name, span, .. } => {
+                let symbol = self.reference(name, *span)?;
+                let symbol = &self.analysis.symbols[symbol];
+                match symbol.kind {
+                    SymbolKind::Variable | SymbolKind::Parameter => symbol.typee.clone(),
+                    SymbolKind::Function | SymbolKind::Import => None,
+                }
+            }
+            Expression::Integer { .. } => Some(Token::IntegerType),
+            Expression::Float { .. } => Some(Token::FloatType),
+            Expression::String { .. } => Some(Token::StringType),
+            Expression::Boolean { .. } => Some(Token::BooleanType),
+            Expression::BigInteger { .. } => Some(Token::BigIntegerType),
+            Expression::Decimal { .. } => Some(Token::DecimalType),
+            Expression::Prefix { prefix, value } => {
+                let typee = self.expression(value);
+                match prefix {
+                    Token::Invert => Some(Token::BooleanType),
+                    Token::Subtract | Token::BitNot => typee,
+                    _ => None,
+                }
+            }
+            Expression::Infix { left, operation, right } => {
+                let left = self.expression(left);
+                let right = self.expression(right);
+                match operation {
+                    Token::Equal | Token::NotEqual | Token::LessThan | Token::GreaterThan |
+                    Token::LessThanEqual | Token::GreaterThanEqual | Token::And | Token::Or => Some(Token::BooleanType),
+                    _ if left == right => left,
+                    _ => None,
+                }
+            }
+            Expression::If { condition, consequence, alternative } => {
+                self.expression(condition);
+                let consequence = self.expression(consequence);
+                let alternative = alternative.as_ref().and_then(|alternative| self.expression(alternative));
+                // Without an alternative the value is null whenever the condition does not hold
+                if alternative == consequence { consequence } else { None }
+            }
+            Expression::While { condition, consequence } => {
+                self.expression(condition);
+                self.expression(consequence);
+                None
+            }
+            Expression::Call { name, arguments, span, .. } => {
+                let arguments = arguments.iter().map(|argument| self.expression(argument)).collect::<Vec<Option<Token>>>();
+                let symbol = self.reference(name, *span);
+                self.calls.push(Call { reference: self.analysis.references.len() - 1, arguments });
+
+                let symbol = &self.analysis.symbols[symbol?];
+                match symbol.kind {
+                    SymbolKind::Function => symbol.typee.clone().filter(|typee| !typee.equal_variant(&Token::NullType)),
+                    _ => None,
+                }
+            }
+            Expression::Error { value } => {
+                self.expression(value);
+                None
+            }
+            Expression::Assign { assign_to, value } => {
+                let expected = self.expression(assign_to);
+                let found = self.expression(value);
+                if let (Expression::Identifier { span, .. }, Some(expected)) = (assign_to.as_ref(), expected) {
+                    self.check(&expected, found, *span);
+                }
+                None
+            }
+            Expression::Array { values } => {
+                for value in values {
+                    self.expression(value);
+                }
+                Some(Token::ArrayType)
+            }
+            Expression::Tuple { values } => {
+                let types = values.iter().map(|value| self.expression(value)).collect::<Vec<Option<Token>>>();
+                types.into_iter().collect::<Option<Vec<Token>>>().map(Token::TupleType)
+            }
+            Expression::Map { entries } => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+                None
+            }
+            Expression::Block { statements } => {
+                let lines = self.layout.blocks.next().unwrap_or_default();
+                self.scopes.push(Scope { names: HashMap::new(), end: lines.end });
+                let mut typee = None;
+                for statement in statements {
+                    typee = self.statement(statement);
+                }
+                self.scopes.pop();
+                typee
+            }
+            Expression::Access { source, index } => {
+                self.expression(source);
+                self.expression(index);
+                None
+            }
+            Expression::Slice { source, start, end } => {
+                self.expression(source);
+                for bound in [start, end].into_iter().flatten() {
+                    self.expression(bound);
+                }
+                None
+            }
+            Expression::Format { parts } => {
+                // Interpolated values are parsed on their own, so they take no positions out of the layout
+                let layout = std::mem::take(&mut self.layout);
+                for value in format_values(parts) {
+                    self.expression(value);
+                }
+                self.layout = layout;
+                Some(Token::StringType)
+            }
+        }
+    }
+
+    /// Position of the next declared name, in the order the parser found them
+    fn declaration(&mut self) -> Span {
+        self.layout.declarations.next().map(|declaration| declaration.span).unwrap_or_default()
+    }
+
+    fn declare(&mut self, name: &str, kind: SymbolKind, typee: Option<Token>, parameters: Vec<(String, Token)>, span: Span) -> usize {
+        let index = self.analysis.symbols.len();
+        let shadows = self.lookup(name);
+        let (names, end) = match self.scopes.last_mut() {
+            Some(scope) => (&mut scope.names, scope.end),
+            None => (&mut self.globals, usize::MAX),
+        };
+        names.insert(name.to_string(), index);
+
+        self.analysis.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            typee,
+            parameters,
+            span,
+            lines: self.statement.lines,
+            end,
+            parent: self.function,
+            shadows,
+            // Only the global which is declared by the statement gets exported
+            exported: self.exported && self.function.is_none() && self.scopes.is_empty(),
+        });
+        index
+    }
+
+    /// Records the usage of a name and returns the symbol it refers to
+    fn reference(&mut self, name: &str, span: Span) -> Option<usize> {
+        let symbol = self.lookup(name);
+        if symbol.is_none() && self.function.is_some() {
+            self.pending.push(self.analysis.references.len());
+        }
+        self.analysis.references.push(Reference { name: name.to_string(), span, symbol });
+        symbol
+    }
+
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev()
+            .find_map(|scope| scope.names.get(name))
+            .or_else(|| self.globals.get(name))
+            .copied()
+    }
+
+    fn check(&mut self, expected: &Token, found: Option<Token>, span: Span) {
+        match found {
+            Some(found) if &found != expected => {
+                self.analysis.diagnostics.push(Diagnostic { span, error: MismatchedType(expected.type_name(), found.type_name()) });
+            }
+            _ => {}
+        }
+    }
+
+    /// Binds the usages of globals inside functions which come before their declaration and checks the calls.
+    /// The diagnostics end up in the order of the source.
+    fn finish(&mut self) {
+        for reference in std::mem::take(&mut self.pending) {
+            let reference = &mut self.analysis.references[reference];
+            reference.symbol = self.globals.get(&reference.name).copied();
+        }
+
+        for call in std::mem::take(&mut self.calls) {
+            let reference = &self.analysis.references[call.reference];
+            let span = reference.span;
+            let parameters = match reference.symbol.map(|symbol| &self.analysis.symbols[symbol]) {
+                Some(symbol) if symbol.kind == SymbolKind::Function => symbol.parameters.iter().map(|(_, typee)| Some(typee.clone())).collect(),
+                Some(_) => continue,
+                None => match builtin(&reference.name) {
+                    // Arguments of builtins may have different types
+                    Some(builtin) => vec![None; builtin.arguments],
+                    None => continue,
+                }
+            };
+
+            if parameters.len() != call.arguments.len() {
+                let error = InvalidAmountOfArguments(reference.name.clone(), parameters.len(), call.arguments.len());
+                self.analysis.diagnostics.push(Diagnostic { span, error });
+                continue
+            }
+            for (parameter, argument) in parameters.into_iter().zip(call.arguments) {
+                if let Some(parameter) = parameter {
+                    self.check(&parameter, argument, span);
+                }
+            }
+        }
+        self.analysis.diagnostics.sort_by_key(|diagnostic| (diagnostic.span.line, diagnostic.span.column));
+    }
+}
+
+/// Names of a pattern with their types, elements of arrays have no declared type
+fn bindings(pattern: &Pattern, typee: Option<&Token>) -> Vec<(String, Option<Token>)> {
+    match pattern {
+        Pattern::Identifier(name) => vec![(name.clone(), typee.cloned())],
+        Pattern::Rest(name) => vec![(name.clone(), Some(Token::ArrayType))],
+        Pattern::Tuple(patterns) => patterns.iter().enumerate()
+            .flat_map(|(index, pattern)| {
+                let typee = match typee {
+                    Some(Token::TupleType(types)) => types.get(index),
+                    _ => None,
+                };
+                bindings(pattern, typee)
+            })
+            .collect(),
+        Pattern::Array(patterns) => patterns.iter().flat_map(|pattern| bindings(pattern, None)).collect(),
+    }
+}
+
+fn format_values(parts: &[FormatPart]) -> impl Iterator<Item = &Expression> {
+    parts.iter().filter_map(|part| match part {
+        FormatPart::Value { value, .. } => Some(value.as_ref()),
+        FormatPart::Literal(_) => None,
+    })
+}
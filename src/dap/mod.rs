@@ -0,0 +1,345 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{stdin, stdout, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use anyhow::{bail, Context};
+use serde_json::{json, Value};
+use crate::debugger::{self, Debugger, Frontend, Pause, Reason, Step};
+use crate::error::DebugError::{MissingProgram, Terminated, UnknownReference, UnknownRequest};
+use crate::evaluator::environment::Environment;
+use crate::evaluator::object::OwnerShip;
+use crate::evaluator::printer::print;
+use crate::parser::ast::statement::Statement;
+use crate::parser::layout::Layout;
+
+// Programs run on a single thread, the protocol still asks for its id
+const THREAD: i64 = 1;
+
+/// Reads and writes the messages of the debug adapter protocol, every one is preceded by a `Content-Length` header
+struct Transport {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    // Sequence number of the last sent message
+    seq: i64,
+    // Whether the editor disconnected or closed the input
+    closed: bool,
+}
+
+impl Transport {
+    /// Next message of the editor, none once the input is closed
+    fn receive(&mut self) -> anyhow::Result<Option<Value>> {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if self.input.read_line(&mut header)? == 0 {
+                self.closed = true;
+                return Ok(None)
+            }
+            let header = header.trim();
+            if header.is_empty() && length.is_some() { break }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = Some(value.trim().parse::<usize>().context("Cannot read the length of a message")?);
+            }
+        }
+
+        let mut body = vec![0; length.unwrap_or_default()];
+        self.input.read_exact(&mut body)?;
+        Ok(Some(serde_json::from_slice(&body)?))
+    }
+
+    fn send(&mut self, mut message: Value) -> anyhow::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let body = message.to_string();
+        write!(self.output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    /// Answers the request with the body of the result, or with the message of its error
+    fn respond(&mut self, request: &Value, result: anyhow::Result<Value>) -> anyhow::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Value::Null) => {}
+            Ok(body) => response["body"] = body,
+            Err(err) => response["message"] = json!(err.root_cause().to_string()),
+        }
+        self.send(response)
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> anyhow::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+}
+
+/// Resolved program which got launched, it runs once the editor sent its configuration
+struct Program {
+    path: PathBuf,
+    statements: Vec<Statement>,
+    layout: Layout,
+    // Lines the program can pause at
+    lines: HashSet<usize>,
+    stop_on_entry: bool,
+}
+
+/// Answers the requests of an editor before and after the program runs
+struct Server {
+    transport: Rc<RefCell<Transport>>,
+    program: Option<Program>,
+    breakpoints: HashSet<usize>,
+}
+
+/// Runs the debug adapter over the standard input and output until the editor disconnects
+pub fn run() -> anyhow::Result<()> {
+    serve(stdin().lock(), stdout())
+}
+
+/// Handles the requests of the input until the editor disconnects or closes the input.
+/// Lines and columns are sent starting at one, which is the default of the protocol.
+pub fn serve(input: impl BufRead + 'static, output: impl Write + 'static) -> anyhow::Result<()> {
+    let transport = Rc::new(RefCell::new(Transport { input: Box::new(input), output: Box::new(output), seq: 0, closed: false }));
+    let mut server = Server { transport: transport.clone(), program: None, breakpoints: HashSet::new() };
+
+    loop {
+        let Some(request) = transport.borrow_mut().receive()? else { return Ok(()) };
+        server.request(&request)?;
+        if transport.borrow().closed { return Ok(()) }
+    }
+}
+
+impl Server {
+    fn request(&mut self, request: &Value) -> anyhow::Result<()> {
+        let arguments = &request["arguments"];
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => self.respond(request, Ok(json!({ "supportsConfigurationDoneRequest": true }))),
+            "launch" => {
+                let result = self.launch(arguments);
+                let launched = result.is_ok();
+                self.respond(request, result.map(|_| Value::Null))?;
+                // Breakpoints are only accepted once the program is known
+                if launched {
+                    self.transport.borrow_mut().event("initialized", json!({}))?;
+                }
+                Ok(())
+            }
+            "setBreakpoints" => {
+                let result = match &self.program {
+                    Some(program) => Ok(set_breakpoints(arguments, &program.path, &program.lines, &mut self.breakpoints)),
+                    None => Err(MissingProgram.into()),
+                };
+                self.respond(request, result)
+            }
+            "configurationDone" => match self.program.take() {
+                Some(program) => {
+                    self.respond(request, Ok(Value::Null))?;
+                    self.debug(program)
+                }
+                None => self.respond(request, Err(MissingProgram.into())),
+            }
+            "threads" => self.respond(request, Ok(threads())),
+            "disconnect" => {
+                self.respond(request, Ok(Value::Null))?;
+                self.transport.borrow_mut().closed = true;
+                Ok(())
+            }
+            command => self.respond(request, Err(UnknownRequest(command.to_string()).into())),
+        }
+    }
+
+    fn respond(&self, request: &Value, result: anyhow::Result<Value>) -> anyhow::Result<()> {
+        self.transport.borrow_mut().respond(request, result)
+    }
+
+    fn launch(&mut self, arguments: &Value) -> anyhow::Result<()> {
+        let path = arguments["program"].as_str().ok_or(MissingProgram)?;
+        let path = Path::new(path).canonicalize().with_context(|| format!("Cannot read {}", path))?;
+        let (_, statements, layout) = debugger::load(&path)?;
+
+        let lines = debugger::lines(&statements, layout.clone());
+        let stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or_default();
+        self.program = Some(Program { path, statements, layout, lines, stop_on_entry });
+        Ok(())
+    }
+
+    /// Runs the program until it ends, and tells the editor how it ended unless the editor disconnected
+    fn debug(&mut self, program: Program) -> anyhow::Result<()> {
+        let adapter = Adapter { transport: self.transport.clone(), path: program.path, lines: program.lines, handles: vec![] };
+        let mut debugger = Debugger::new(adapter).with_breakpoints(self.breakpoints.iter().copied());
+        if !program.stop_on_entry {
+            debugger = debugger.without_entry();
+        }
+        let result = debugger.run(&program.statements, program.layout, &mut Environment::default());
+
+        let mut transport = self.transport.borrow_mut();
+        if transport.closed { return Ok(()) }
+        let code = match result {
+            Ok(()) => 0,
+            Err(err) => {
+                transport.event("output", json!({ "category": "stderr", "output": format!("{}\n", err.root_cause()) }))?;
+                1
+            }
+        };
+        transport.event("exited", json!({ "exitCode": code }))?;
+        transport.event("terminated", json!({}))
+    }
+}
+
+/// Value which the editor can expand, its reference is its index plus one
+enum Handle {
+    // Variables of the call with the index on the stack
+    Scope(usize),
+    Value(OwnerShip),
+}
+
+/// Frontend which tells the editor where the program paused and answers its requests until it continues the program
+struct Adapter {
+    transport: Rc<RefCell<Transport>>,
+    path: PathBuf,
+    lines: HashSet<usize>,
+    // Only valid while the program is paused, since values may be freed once it continues
+    handles: Vec<Handle>,
+}
+
+impl Frontend for Adapter {
+    fn paused(&mut self, pause: &mut Pause) -> anyhow::Result<Step> {
+        self.handles.clear();
+        let reason = match pause.reason {
+            Reason::Entry => "entry",
+            Reason::Breakpoint => "breakpoint",
+            Reason::Step => "step",
+        };
+        self.transport.borrow_mut().event("stopped", json!({ "reason": reason, "threadId": THREAD, "allThreadsStopped": true }))?;
+
+        loop {
+            let Some(request) = self.transport.borrow_mut().receive()? else { bail!(Terminated) };
+            let arguments = &request["arguments"];
+            let (result, step) = match request["command"].as_str().unwrap_or_default() {
+                "continue" => (Ok(json!({ "allThreadsContinued": true })), Some(Step::Continue)),
+                "next" => (Ok(Value::Null), Some(Step::Over)),
+                "stepIn" => (Ok(Value::Null), Some(Step::Into)),
+                "stepOut" => (Ok(Value::Null), Some(Step::Out)),
+                "threads" => (Ok(threads()), None),
+                "setBreakpoints" => (Ok(set_breakpoints(arguments, &self.path, &self.lines, pause.breakpoints)), None),
+                "stackTrace" => (Ok(self.stack_trace(pause)), None),
+                "scopes" => (Ok(self.scopes(arguments)), None),
+                "variables" => (self.variables(arguments, pause), None),
+                "evaluate" => (self.evaluate(arguments, pause), None),
+                "disconnect" => {
+                    let mut transport = self.transport.borrow_mut();
+                    transport.respond(&request, Ok(Value::Null))?;
+                    transport.closed = true;
+                    bail!(Terminated)
+                }
+                command => (Err(UnknownRequest(command.to_string()).into()), None),
+            };
+
+            self.transport.borrow_mut().respond(&request, result)?;
+            if let Some(step) = step {
+                return Ok(step)
+            }
+        }
+    }
+}
+
+impl Adapter {
+    fn stack_trace(&self, pause: &Pause) -> Value {
+        let name = self.path.file_name().map(|name| name.to_string_lossy().to_string());
+        let frames = pause.frames().into_iter()
+            .map(|frame| json!({
+                "id": frame.call + 1,
+                "name": frame.name,
+                "line": frame.span.line,
+                "column": frame.span.column,
+                "source": { "name": name, "path": self.path },
+            }))
+            .collect::<Vec<Value>>();
+        json!({ "stackFrames": frames, "totalFrames": frames.len() })
+    }
+
+    /// Locals of the frame, function calls can see the globals of the program as well
+    fn scopes(&mut self, arguments: &Value) -> Value {
+        let call = arguments["frameId"].as_u64().unwrap_or(1).saturating_sub(1) as usize;
+        let mut scopes = vec![json!({ "name": "Locals", "variablesReference": self.handle(Handle::Scope(call)), "expensive": false })];
+        if call > 0 {
+            scopes.push(json!({ "name": "Globals", "variablesReference": self.handle(Handle::Scope(0)), "expensive": false }));
+        }
+        json!({ "scopes": scopes })
+    }
+
+    fn variables(&mut self, arguments: &Value, pause: &Pause) -> anyhow::Result<Value> {
+        let reference = arguments["variablesReference"].as_i64().unwrap_or_default();
+        let handle = usize::try_from(reference - 1).ok()
+            .and_then(|index| self.handles.get(index))
+            .ok_or(UnknownReference(reference))?;
+        let variables = match handle {
+            Handle::Scope(call) => pause.locals(*call),
+            Handle::Value(value) => pause.children(value)?,
+        };
+
+        let variables = variables.into_iter()
+            .map(|(name, value)| {
+                let mut variable = self.describe(value, pause);
+                variable["name"] = json!(name);
+                variable
+            })
+            .collect::<Vec<Value>>();
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn evaluate(&mut self, arguments: &Value, pause: &mut Pause) -> anyhow::Result<Value> {
+        let value = pause.value(arguments["expression"].as_str().unwrap_or_default())?;
+        let mut result = self.describe(value, pause);
+        if let Some(map) = result.as_object_mut() {
+            let value = map.remove("value").unwrap_or_default();
+            map.insert("result".to_string(), value);
+        }
+        Ok(result)
+    }
+
+    /// Readable value and type of the value, with a reference to its elements if it has any
+    fn describe(&mut self, value: OwnerShip, pause: &Pause) -> Value {
+        let text = print(&value, pause.environment).unwrap_or_else(|err| err.to_string());
+        let kind = value.value(pause.environment).map(|object| object.type_name()).unwrap_or_default();
+        let reference = match pause.children(&value) {
+            Ok(children) if !children.is_empty() => self.handle(Handle::Value(value)),
+            _ => 0,
+        };
+        json!({ "value": text, "type": kind, "variablesReference": reference })
+    }
+
+    fn handle(&mut self, handle: Handle) -> usize {
+        self.handles.push(handle);
+        self.handles.len()
+    }
+}
+
+/// Replaces the breakpoints of the program, only lines the program can pause at are verified.
+/// Breakpoints in other sources are never reached.
+fn set_breakpoints(arguments: &Value, path: &Path, lines: &HashSet<usize>, breakpoints: &mut HashSet<usize>) -> Value {
+    let source = arguments["source"]["path"].as_str().and_then(|source| Path::new(source).canonicalize().ok());
+    let program = source.as_deref() == Some(path);
+    let requested = arguments["breakpoints"].as_array().into_iter()
+        .flatten()
+        .filter_map(|breakpoint| breakpoint["line"].as_u64())
+        .map(|line| line as usize)
+        .collect::<Vec<usize>>();
+
+    if program {
+        breakpoints.clear();
+        breakpoints.extend(requested.iter().copied());
+    }
+    let verified = requested.into_iter()
+        .map(|line| json!({ "verified": program && lines.contains(&line), "line": line }))
+        .collect::<Vec<Value>>();
+    json!({ "breakpoints": verified })
+}
+
+fn threads() -> Value {
+    json!({ "threads": [{ "id": THREAD, "name": "main" }] })
+}
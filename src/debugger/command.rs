@@ -0,0 +1,74 @@
+use anyhow::bail;
+use crate::error::CommandError::MissingArgument;
+use crate::error::DebugError::{InvalidLine, UnknownCommand};
+
+/// Names, short names, arguments and descriptions of the commands, listed by `help`
+pub const COMMANDS: [(&str, &str, &str, &str); 13] = [
+    ("break", "b", "<line>", "Pauses the program whenever it reaches the line"),
+    ("delete", "d", "<line>", "Removes the breakpoint at the line"),
+    ("continue", "c", "", "Runs until a breakpoint is reached"),
+    ("next", "n", "", "Runs to the next line and steps over called functions"),
+    ("step", "s", "", "Runs to the next line and steps into called functions"),
+    ("out", "o", "", "Runs until the current function returned"),
+    ("locals", "l", "", "Lists the variables of the current function"),
+    ("stack", "bt", "", "Lists the function calls which are being evaluated"),
+    ("print", "p", "<expression>", "Evaluates an expression and prints its value"),
+    ("watch", "w", "<expression>", "Prints the value of an expression whenever the program pauses"),
+    ("unwatch", "u", "<expression>", "Stops watching an expression"),
+    ("help", "h", "", "Lists the commands"),
+    ("quit", "q", "", "Stops the program"),
+];
+
+/// Input of the debugger while the program is paused
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub enum Command {
+    Break(usize),
+    Delete(usize),
+    Continue,
+    Next,
+    Step,
+    Out,
+    Locals,
+    Stack,
+    Print(String),
+    Watch(String),
+    Unwatch(String),
+    Help,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(input: &str) -> anyhow::Result<Command> {
+        let input = input.trim();
+        let (name, argument) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let argument = argument.trim().to_string();
+        let Some((name, ..)) = COMMANDS.iter().find(|(long, short, ..)| *long == name || *short == name) else {
+            bail!(UnknownCommand(name.to_string()))
+        };
+        let required = |argument: String, expected: &str| {
+            if argument.is_empty() { bail!(MissingArgument(name.to_string(), expected.to_string())) }
+            Ok(argument)
+        };
+        let line = |argument: String| {
+            let argument = required(argument, "a line")?;
+            argument.parse::<usize>().map_err(|_| anyhow::Error::from(InvalidLine(argument)))
+        };
+
+        Ok(match *name {
+            "break" => Command::Break(line(argument)?),
+            "delete" => Command::Delete(line(argument)?),
+            "continue" => Command::Continue,
+            "next" => Command::Next,
+            "step" => Command::Step,
+            "out" => Command::Out,
+            "locals" => Command::Locals,
+            "stack" => Command::Stack,
+            "print" => Command::Print(required(argument, "an expression")?),
+            "watch" => Command::Watch(required(argument, "an expression")?),
+            "unwatch" => Command::Unwatch(required(argument, "an expression")?),
+            "help" => Command::Help,
+            _ => Command::Quit,
+        })
+    }
+}
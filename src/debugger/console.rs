@@ -0,0 +1,127 @@
+use std::io::{BufRead, Write};
+use anyhow::bail;
+use colored::Colorize;
+use crate::debugger::command::{Command, COMMANDS};
+use crate::debugger::{Frontend, Pause, Reason, Step};
+use crate::error::DebugError::Terminated;
+use crate::evaluator::printer::print;
+
+/// Frontend which reads commands line by line and writes what the program looks like, used by the `debug` subcommand
+pub struct Console<R, W> {
+    input: R,
+    output: W,
+    // Lines of the debugged program, shown where it pauses
+    source: Vec<String>,
+    // Command which an empty input repeats
+    last: Option<Command>,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    pub fn new(input: R, output: W, source: &str) -> Self {
+        Self { input, output, source: source.lines().map(str::to_string).collect(), last: None }
+    }
+
+    /// Runs a command and returns how the program continues, unless the program stays paused
+    fn run(&mut self, command: Command, pause: &mut Pause) -> anyhow::Result<Option<Step>> {
+        match command {
+            Command::Continue => return Ok(Some(Step::Continue)),
+            Command::Next => return Ok(Some(Step::Over)),
+            Command::Step => return Ok(Some(Step::Into)),
+            Command::Out => return Ok(Some(Step::Out)),
+            Command::Quit => bail!(Terminated),
+            Command::Break(line) => {
+                pause.breakpoints.insert(line);
+                writeln!(self.output, "{}", format!("Added a breakpoint at line {}!", line).bright_green())?;
+            }
+            Command::Delete(line) => {
+                if pause.breakpoints.remove(&line) {
+                    writeln!(self.output, "{}", format!("Removed the breakpoint at line {}!", line).bright_green())?;
+                } else {
+                    writeln!(self.output, "{}", format!("Found no breakpoint at line {}!", line).bright_red())?;
+                }
+            }
+            Command::Locals => {
+                let call = pause.environment.call_stack().frames().len();
+                for (name, value) in pause.locals(call) {
+                    let value = print(&value, pause.environment).unwrap_or_else(|err| err.to_string());
+                    writeln!(self.output, "{} = {}", name.bright_cyan(), value)?;
+                }
+            }
+            Command::Stack => {
+                for (index, frame) in pause.frames().iter().enumerate() {
+                    writeln!(self.output, "#{} {} at {}", index, frame.name.bright_cyan(), frame.span)?;
+                }
+            }
+            Command::Print(expression) => match pause.evaluate(&expression) {
+                Ok(value) => writeln!(self.output, "{}", value)?,
+                Err(err) => writeln!(self.output, "{}", format!("{}", err.root_cause()).bright_red())?,
+            }
+            Command::Watch(expression) => {
+                if !pause.watches.contains(&expression) {
+                    pause.watches.push(expression.clone());
+                }
+                self.watch(expression.clone(), pause.evaluate(&expression))?;
+            }
+            Command::Unwatch(expression) => pause.watches.retain(|watch| *watch != expression),
+            Command::Help => {
+                for (name, short, argument, description) in COMMANDS {
+                    writeln!(self.output, "{:<26}{}", format!("{}, {} {}", name, short, argument).bright_cyan(), description)?;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn watch(&mut self, expression: String, value: anyhow::Result<String>) -> anyhow::Result<()> {
+        match value {
+            Ok(value) => writeln!(self.output, "{} = {}", expression.bright_cyan(), value)?,
+            Err(err) => writeln!(self.output, "{} = {}", expression.bright_cyan(), format!("{}", err.root_cause()).bright_red())?,
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for Console<R, W> {
+    fn paused(&mut self, pause: &mut Pause) -> anyhow::Result<Step> {
+        let reason = match pause.reason {
+            Reason::Entry => "Paused before",
+            Reason::Breakpoint => "Reached the breakpoint at",
+            Reason::Step => "Stepped to",
+        };
+        writeln!(self.output, "{}", format!("{} {}", reason, pause.span).bright_green())?;
+        if let Some(line) = self.source.get(pause.span.line.wrapping_sub(1)) {
+            writeln!(self.output, "{:>5} | {}", pause.span.line, line)?;
+        }
+        for (expression, value) in pause.watched() {
+            self.watch(expression, value)?;
+        }
+
+        loop {
+            write!(self.output, "(debug) ")?;
+            self.output.flush()?;
+            let mut input = String::new();
+            // The program ends with the input, like it does with `quit`
+            if self.input.read_line(&mut input)? == 0 { bail!(Terminated) }
+
+            let command = if input.trim().is_empty() {
+                match self.last.clone() {
+                    Some(command) => command,
+                    None => continue,
+                }
+            } else {
+                match Command::parse(&input) {
+                    Ok(command) => command,
+                    Err(err) => {
+                        writeln!(self.output, "{}", format!("{}", err).bright_red())?;
+                        continue
+                    }
+                }
+            };
+
+            self.last = Some(command.clone());
+            if let Some(step) = self.run(command, pause)? {
+                return Ok(step)
+            }
+        }
+    }
+}
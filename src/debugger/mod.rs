@@ -0,0 +1,363 @@
+pub mod command;
+pub mod console;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{stdin, stdout};
+use std::path::{Path, PathBuf};
+use std::vec::IntoIter;
+use anyhow::{bail, Context};
+use colored::Colorize;
+use crate::debugger::console::Console;
+use crate::error::DebugError::{self, NotAnExpression, Terminated};
+use crate::error::StackTrace;
+use crate::evaluator::environment::Environment;
+use crate::evaluator::hooks::Hooks;
+use crate::evaluator::object::{Object, OwnerShip};
+use crate::evaluator::printer::print;
+use crate::lexer::token::Span;
+use crate::lexer::Lexer;
+use crate::parser::ast::expression::Expression;
+use crate::parser::ast::statement::Statement;
+use crate::parser::layout::{Layout, StatementLayout};
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+
+// Name of the frame of the top level of the program
+pub const TOP_LEVEL: &str = "<program>";
+
+/// How the program continues after it paused
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum Step {
+    // Runs until a breakpoint is reached
+    Continue,
+    // Pauses at the next line, inside of called functions as well
+    Into,
+    // Pauses at the next line of the current function, or of the function it returns to
+    Over,
+    // Pauses at the next line after the current function returned
+    Out,
+}
+
+/// Why the program paused
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum Reason {
+    Entry,
+    Breakpoint,
+    Step,
+}
+
+/// Function call which is being evaluated
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub struct StackFrame {
+    pub name: String,
+    // Statement which runs next in the innermost call, the call of the next inner frame in the others
+    pub span: Span,
+    // Index of the call on the stack, the top level of the program is the first call
+    pub call: usize,
+}
+
+/// Shows a paused program to the person who debugs it and decides how it continues
+pub trait Frontend {
+    fn paused(&mut self, pause: &mut Pause) -> anyhow::Result<Step>;
+}
+
+/// Program which is paused before a statement, its breakpoints and watched expressions may be changed
+pub struct Pause<'a> {
+    pub reason: Reason,
+    // Position of the statement which runs next
+    pub span: Span,
+    pub breakpoints: &'a mut HashSet<usize>,
+    pub watches: &'a mut Vec<String>,
+    pub environment: &'a mut Environment,
+}
+
+impl Pause<'_> {
+    /// Calls which are being evaluated, starting with the innermost one and ending with the top level
+    pub fn frames(&self) -> Vec<StackFrame> {
+        let mut frames = vec![];
+        let mut span = self.span;
+        for (index, frame) in self.environment.call_stack().frames().iter().enumerate().rev() {
+            frames.push(StackFrame { name: frame.name.clone(), span, call: index + 1 });
+            span = frame.span;
+        }
+        frames.push(StackFrame { name: TOP_LEVEL.to_string(), span, call: 0 });
+        frames
+    }
+
+    /// Variables of the call in the order they got declared, functions are left out.
+    /// A variable which is hidden by a later one with the same name is left out as well.
+    pub fn locals(&self, call: usize) -> Vec<(String, OwnerShip)> {
+        let stack = self.environment.stack();
+        if call >= stack.frames() { return vec![] }
+
+        let mut names = HashSet::new();
+        let mut locals = stack.frame(call).iter()
+            .rev()
+            .filter(|(name, _)| names.insert(name.as_str()))
+            .filter(|(_, value)| !matches!(value.value(self.environment), Ok(Object::Function(_) | Object::Builtin(_))))
+            .cloned()
+            .collect::<Vec<(String, OwnerShip)>>();
+        locals.reverse();
+        locals
+    }
+
+    /// Evaluates expressions in the paused program and returns the readable value of the last one
+    pub fn evaluate(&mut self, source: &str) -> anyhow::Result<String> {
+        let value = self.value(source)?;
+        print(&value, self.environment)
+    }
+
+    /// Evaluates expressions in the paused program and returns the value of the last one.
+    /// Names refer to the innermost variable with the name, and nothing pauses while they are evaluated.
+    pub fn value(&mut self, source: &str) -> anyhow::Result<OwnerShip> {
+        let tokens = Lexer::from_string(source.to_string()).lex_spanned();
+        let statements = Parser::from_spanned_tokens(tokens).parse()?;
+
+        let mut expressions = vec![];
+        for statement in &statements {
+            let Statement::Expression { value } = statement else { bail!(NotAnExpression) };
+            expressions.push(value);
+        }
+
+        let mut value = OwnerShip::Instance(Object::Null);
+        for expression in expressions {
+            value = expression.evaluate(self.environment)?.0;
+        }
+        Ok(value)
+    }
+
+    /// Elements of an array or a tuple and entries of a map, other values have none
+    pub fn children(&self, value: &OwnerShip) -> anyhow::Result<Vec<(String, OwnerShip)>> {
+        Ok(match value.value(self.environment)? {
+            Object::Array(values) | Object::Tuple(values) => values.into_iter()
+                .enumerate()
+                .map(|(index, value)| (format!("[{}]", index), value))
+                .collect(),
+            Object::Map(map) => self.environment.entries(map)?.iter()
+                .map(|(key, value)| (format!("[{}]", key), value.clone()))
+                .collect(),
+            _ => vec![],
+        })
+    }
+
+    /// Values of the watched expressions, or why they cannot be evaluated
+    pub fn watched(&mut self) -> Vec<(String, anyhow::Result<String>)> {
+        self.watches.clone().into_iter()
+            .map(|watch| {
+                let value = self.evaluate(&watch);
+                (watch, value)
+            })
+            .collect()
+    }
+}
+
+/// Pauses a program at its breakpoints and after every step, the frontend decides how it continues
+pub struct Debugger<F> {
+    frontend: F,
+    // Positions of the statements which start a line, the program only pauses before them
+    positions: HashMap<*const Statement, Span>,
+    breakpoints: HashSet<usize>,
+    watches: Vec<String>,
+    step: Step,
+    // Amount of calls when the program paused the last time
+    depth: usize,
+    paused: bool,
+}
+
+impl<F: Frontend + 'static> Debugger<F> {
+    /// Debugger which pauses before the first line of the program
+    pub fn new(frontend: F) -> Self {
+        Self {
+            frontend,
+            positions: HashMap::new(),
+            breakpoints: HashSet::new(),
+            watches: vec![],
+            step: Step::Into,
+            depth: 0,
+            paused: false,
+        }
+    }
+
+    pub fn with_breakpoints(mut self, lines: impl IntoIterator<Item = usize>) -> Self {
+        self.breakpoints.extend(lines);
+        self
+    }
+
+    /// Runs until the first breakpoint instead of pausing before the first line
+    pub fn without_entry(mut self) -> Self {
+        self.step = Step::Continue;
+        self
+    }
+
+    /// Runs resolved statements, the layout has to be the one the parser recorded for them.
+    /// A program which got terminated by the frontend ends without an error.
+    pub fn run(mut self, statements: &[Statement], layout: Layout, environment: &mut Environment) -> anyhow::Result<()> {
+        self.positions = positions(statements, layout);
+
+        environment.set_hooks(Some(Box::new(self)));
+        let result = statements.iter().try_for_each(|statement| {
+            environment.release_all();
+            environment.collect_garbage_if_needed();
+            statement.evaluate(environment).map(|_| ())
+        });
+        environment.set_hooks(None);
+
+        match result {
+            Err(err) if matches!(err.downcast_ref::<DebugError>(), Some(Terminated)) => Ok(()),
+            result => result,
+        }
+    }
+}
+
+impl<F: Frontend> Hooks for Debugger<F> {
+    fn before_statement(&mut self, statement: &Statement, environment: &mut Environment) -> anyhow::Result<()> {
+        let Some(span) = self.positions.get(&(statement as *const Statement)).copied() else { return Ok(()) };
+        let depth = environment.call_stack().frames().len();
+
+        let reason = if self.breakpoints.contains(&span.line) {
+            Reason::Breakpoint
+        } else {
+            let done = match self.step {
+                Step::Continue => false,
+                Step::Into => true,
+                Step::Over => depth <= self.depth,
+                Step::Out => depth < self.depth,
+            };
+            if !done { return Ok(()) }
+            if self.paused { Reason::Step } else { Reason::Entry }
+        };
+
+        self.paused = true;
+        self.depth = depth;
+        let mut pause = Pause { reason, span, breakpoints: &mut self.breakpoints, watches: &mut self.watches, environment };
+        self.step = self.frontend.paused(&mut pause)?;
+        Ok(())
+    }
+}
+
+/// Reads, parses and resolves a program for debugging, it is not optimized since that changes its statements.
+/// Returns the source with the statements and the layout the parser recorded for them.
+pub fn load(path: &Path) -> anyhow::Result<(String, Vec<Statement>, Layout)> {
+    let source = fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let tokens = Lexer::from_string(source.clone()).lex_spanned();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let mut statements = parser.parse()?;
+    Resolver::default().in_directory(path.parent().unwrap_or(Path::new("."))).resolve(&mut statements)?;
+    Ok((source, statements, parser.layout().clone()))
+}
+
+/// Lines the program can pause at, breakpoints at other lines are never reached
+pub fn lines(statements: &[Statement], layout: Layout) -> HashSet<usize> {
+    positions(statements, layout).into_values().map(|span| span.line).collect()
+}
+
+fn positions(statements: &[Statement], layout: Layout) -> HashMap<*const Statement, Span> {
+    let mut positions = Positions { layout: layout.statements.into_iter(), line: 0, spans: HashMap::new() };
+    for statement in statements {
+        positions.statement(statement);
+    }
+    positions.spans
+}
+
+/// Finds the positions of the statements a line starts with, by walking the statements in the order they got parsed
+struct Positions {
+    layout: IntoIter<StatementLayout>,
+    // Line of the last visited statement
+    line: usize,
+    spans: HashMap<*const Statement, Span>,
+}
+
+impl Positions {
+    fn statement(&mut self, statement: &Statement) {
+        if let Some(layout) = self.layout.next() {
+            // Statements which share the line with the statement before them are stepped over together with it
+            if layout.span.line != self.line {
+                self.spans.insert(statement as *const Statement, layout.span);
+            }
+            self.line = layout.span.line;
+        }
+
+        match statement {
+            Statement::Let { value, .. } |
+            Statement::Return { value } |
+            Statement::Expression { value } |
+            Statement::Destructure { value, .. } => self.expression(value),
+            Statement::Function { body, .. } => self.expression(body),
+            Statement::Export { statement } => self.statement(statement),
+            Statement::Import { .. } => {}
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Block { statements } => {
+                for statement in statements {
+                    self.statement(statement);
+                }
+            }
+            Expression::Format { .. } => {
+                // Interpolated values are parsed on their own, so their statements have no positions
+                let layout = std::mem::take(&mut self.layout);
+                for child in expression.children() {
+                    self.expression(child);
+                }
+                self.layout = layout;
+            }
+            expression => {
+                for child in expression.children() {
+                    self.expression(child);
+                }
+            }
+        }
+    }
+}
+
+/// Settings of the `debug` subcommand
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct DebugArguments {
+    /// Lines the program pauses at
+    #[arg(short, long = "break")]
+    pub breakpoints: Vec<usize>,
+    /// Runs until the first breakpoint instead of pausing before the first line
+    #[arg(long)]
+    pub run: bool,
+    /// Program which gets debugged
+    pub path: PathBuf,
+}
+
+/// Debugs the program of the arguments with commands from the standard input and returns whether it ran without an error
+pub fn run(arguments: &DebugArguments) -> bool {
+    let (source, statements, layout) = match load(&arguments.path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}", format!("{:#}", err).bright_red());
+            return false
+        }
+    };
+
+    let mut debugger = Debugger::new(Console::new(stdin().lock(), stdout(), &source))
+        .with_breakpoints(arguments.breakpoints.iter().copied());
+    if arguments.run {
+        debugger = debugger.without_entry();
+    }
+
+    match debugger.run(&statements, layout, &mut Environment::default()) {
+        Ok(()) => {
+            println!("{}", "Debugging finished!".bright_green());
+            true
+        }
+        Err(err) => {
+            eprintln!("{}", format!("{}", err.root_cause()).bright_red());
+            if let Some(trace) = err.downcast_ref::<StackTrace>() {
+                eprintln!("{}", trace.calls().red());
+            }
+            false
+        }
+    }
+}
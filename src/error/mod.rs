@@ -1,7 +1,9 @@
+use std::fmt::{Display, Formatter};
 use thiserror::Error;
+use crate::evaluator::environment::{Address, Frame};
 use crate::evaluator::object::Object;
-use crate::lexer::token::Token;
-use crate::parser::ast::expression::Expression;
+use crate::lexer::token::{Span, Token};
+use crate::parser::ast::expression::{Expression, MAX_BIG_BITS};
 
 #[derive(Error, Debug)]
 pub enum ParseError {
@@ -15,6 +17,72 @@ pub enum ParseError {
     ExpectedTokenButFound(Token, Token),
     #[error("Found unexpected token: {0:?}!")]
     UnexpectedTokenFound(Token),
+    #[error("Invalid format specifier {0:?}!")]
+    InvalidFormatSpec(String),
+    #[error("Found an unterminated or unbalanced format string at {0}!")]
+    InvalidFormatString(Span),
+}
+
+#[derive(Error, Debug)]
+pub enum ResolveError {
+    #[error("Found unknown identifier {0} at {1}!")]
+    UnknownIdentifier(String, Span),
+    #[error("Cannot find the module {0} imported at {1}!")]
+    ModuleNotFound(String, Span),
+    #[error("Found an import cycle: {0}!")]
+    ImportCycle(String),
+    #[error("Module {1} does not export {0}, imported at {2}!")]
+    NotExported(String, String, Span),
+    #[error("Modules can only be imported at the top level, found an import at {0}!")]
+    NestedImport(Span),
+}
+
+impl ResolveError {
+    /// Position the error was found at, import cycles run through several files and have none
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ResolveError::UnknownIdentifier(_, span) |
+            ResolveError::ModuleNotFound(_, span) |
+            ResolveError::NotExported(_, _, span) |
+            ResolveError::NestedImport(span) => Some(*span),
+            ResolveError::ImportCycle(_) => None,
+        }
+    }
+}
+
+/// Type mismatch which is found before the program runs, the evaluator would fail with it at runtime
+#[derive(Error, Debug)]
+pub enum TypeError {
+    #[error("Expected a value of type {0} but found {1}!")]
+    MismatchedType(String, String),
+    #[error("Function {0} expects {1} arguments but found {2}!")]
+    InvalidAmountOfArguments(String, usize, usize),
+}
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Found unknown command {0}, enter :help to list the commands!")]
+    UnknownCommand(String),
+    #[error("Command {0} expects {1}!")]
+    MissingArgument(String, String),
+}
+
+#[derive(Error, Debug)]
+pub enum DebugError {
+    #[error("Found unknown command {0}, enter help to list the commands!")]
+    UnknownCommand(String),
+    #[error("Expected a line number but found {0}!")]
+    InvalidLine(String),
+    #[error("Only expressions can be evaluated while debugging!")]
+    NotAnExpression,
+    #[error("Program got terminated by the debugger!")]
+    Terminated,
+    #[error("Found unknown request {0}!")]
+    UnknownRequest(String),
+    #[error("Found no variables with the reference {0}!")]
+    UnknownReference(i64),
+    #[error("Expected a program to debug, it has to be launched first!")]
+    MissingProgram,
 }
 
 #[derive(Error, Debug)]
@@ -27,18 +95,18 @@ pub enum EvalError {
     UnknownIdentifier(String),
     #[error("Could not convert {0} into {1}!")]
     CannotConvertInto(String, String),
-    #[error("Cannot apply {0:?} on {1:#?}!")]
-    IllegalPrefixOperation(Token, Box<Expression>),
+    #[error("Cannot apply {0:?} on {1:?}!")]
+    IllegalPrefixOperation(Token, Object),
     #[error("Cannot perform operation {0:?} on {1:?}!")]
-    CannotApplyOn(Token, Box<Expression>),
+    CannotApplyOn(Token, Object),
     #[error("Cannot apply oepration {2:?} between {0:?} and {1:?}!")]
     IncompatibleTypes(Token, Object, Token),
     #[error("Cannot assignt object with type {0:?} to variable {2} with type {1:?}!")]
     CannotAssignDifferentType(Object, Object, String),
     #[error("Cannot access: {0:?}")]
-    CannotArrayAccess(Box<Expression>),
+    CannotArrayAccess(Object),
     #[error("Cannot access array with: {0:?}")]
-    CannotAccessArrayWith(Box<Expression>),
+    CannotAccessArrayWith(Object),
     #[error("Index {0} is out of range!")]
     IndexOutOfRange(i128),
     #[error("You cannot call {0:?}!")]
@@ -48,5 +116,65 @@ pub enum EvalError {
     #[error("Expected type {0:?} but found {1:?}!")]
     ExpectedTypeButFound(Token, Object),
     #[error("You cannot assign to {0:?}!")]
-    CannotAssignTo(Box<Expression>)
-}
\ No newline at end of file
+    CannotAssignTo(Object),
+    #[error("Heap address {0} does not point to a value!")]
+    InvalidAddress(Address),
+    #[error("Integer overflow while applying {0:?} on {1} and {2}!")]
+    IntegerOverflow(Token, i128, i128),
+    #[error("Integer overflow while negating {0}!")]
+    NegationOverflow(i128),
+    #[error("Cannot divide {0} by zero!")]
+    DivisionByZero(String),
+    #[error("Cannot calculate {0} modulo zero!")]
+    ModuloByZero(String),
+    #[error("Cannot shift by {0}!")]
+    ShiftOutOfRange(String),
+    #[error("Result of applying {0:?} with {1} would have more than {max} bits!", max = MAX_BIG_BITS)]
+    ResultTooLarge(Token, String),
+    #[error("Cannot raise to the negative power {0}!")]
+    NegativeExponent(String),
+    #[error("Cannot use {0:?} as a map key!")]
+    InvalidMapKey(Object),
+    #[error("Map does not contain the key {0}!")]
+    KeyNotFound(String),
+    #[error("Cannot destructure {1:?} into {0}!")]
+    CannotDestructure(String, Object),
+    #[error("Cannot slice {0}..{1} out of a sequence with length {2}!")]
+    SliceOutOfRange(i128, i128, usize),
+    #[error("Cannot slice {0:?}!")]
+    CannotSlice(Object),
+    #[error("Invalid format specifier {0:?}!")]
+    InvalidFormatSpec(String),
+    #[error("Cannot format {0:?} with a precision!")]
+    PrecisionNotSupported(Object),
+}
+
+/// Calls that were active when a runtime error occurred, starting with the innermost one.
+/// It is attached to the error as context, so it can be retrieved with `downcast_ref::<StackTrace>()`,
+/// and the error it is attached to can still be downcast to its own type.
+/// It is displayed as the message of the error followed by the calls.
+#[derive(Debug, Clone)]
+pub struct StackTrace {
+    pub message: String,
+    pub frames: Vec<Frame>,
+}
+
+impl StackTrace {
+    /// Calls without the message, one per line
+    pub fn calls(&self) -> String {
+        self.frames.iter()
+            .map(|frame| format!("at {} ({})", frame.name, frame.span))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Display for StackTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.frames.is_empty() {
+            write!(f, "\n{}", self.calls())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use anyhow::bail;
+use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive};
+use num_bigint::BigInt;
+use crate::error::EvalError::{CannotConvertInto, DivisionByZero, ExpectedTypeButFound, IntegerOverflow, InvalidFormatSpec, KeyNotFound, NegativeExponent};
+use crate::evaluator::environment::{Address, Environment};
+use crate::evaluator::format::FormatSpec;
+use crate::evaluator::object::{Key, Object, OwnerShip};
+use crate::lexer::token::Token;
+use crate::parser::ast::expression::{allocate, slice_bounds};
+
+/// Function which is implemented by the interpreter itself
+#[derive(Debug)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub arguments: usize,
+    pub function: fn(Vec<Object>, &mut Environment) -> anyhow::Result<Object>,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin { name: "wrapping_add", arguments: 2, function: |arguments, _| integers(arguments, i128::wrapping_add) },
+    Builtin { name: "wrapping_sub", arguments: 2, function: |arguments, _| integers(arguments, i128::wrapping_sub) },
+    Builtin { name: "wrapping_mul", arguments: 2, function: |arguments, _| integers(arguments, i128::wrapping_mul) },
+    Builtin { name: "wrapping_div", arguments: 2, function: |arguments, _| divide(arguments, i128::wrapping_div) },
+    Builtin { name: "wrapping_pow", arguments: 2, function: |arguments, _| power(arguments, i128::wrapping_pow) },
+    Builtin { name: "wrapping_neg", arguments: 1, function: |arguments, _| Ok(Object::Integer(integer(first(arguments))?.wrapping_neg())) },
+    Builtin { name: "saturating_add", arguments: 2, function: |arguments, _| integers(arguments, i128::saturating_add) },
+    Builtin { name: "saturating_sub", arguments: 2, function: |arguments, _| integers(arguments, i128::saturating_sub) },
+    Builtin { name: "saturating_mul", arguments: 2, function: |arguments, _| integers(arguments, i128::saturating_mul) },
+    Builtin { name: "saturating_div", arguments: 2, function: |arguments, _| divide(arguments, i128::saturating_div) },
+    Builtin { name: "saturating_pow", arguments: 2, function: |arguments, _| power(arguments, i128::saturating_pow) },
+    Builtin { name: "saturating_neg", arguments: 1, function: |arguments, _| Ok(Object::Integer(integer(first(arguments))?.saturating_neg())) },
+    Builtin { name: "to_int", arguments: 1, function: |arguments, _| to_int(first(arguments)) },
+    Builtin { name: "to_float", arguments: 1, function: |arguments, _| to_float(first(arguments)) },
+    Builtin { name: "to_bigint", arguments: 1, function: |arguments, _| to_bigint(first(arguments)) },
+    Builtin { name: "to_decimal", arguments: 1, function: |arguments, _| to_decimal(first(arguments)) },
+    Builtin { name: "to_string", arguments: 1, function: |arguments, environment| Ok(Object::String(first(arguments).resolve(environment)?.to_string())) },
+    Builtin { name: "format", arguments: 2, function: |arguments, environment| format(arguments, environment) },
+    Builtin { name: "len", arguments: 1, function: |arguments, environment| len(first(arguments), environment) },
+    Builtin { name: "keys", arguments: 1, function: keys },
+    Builtin { name: "values", arguments: 1, function: values },
+    Builtin { name: "entries", arguments: 1, function: entries },
+    Builtin { name: "contains", arguments: 2, function: |arguments, environment| contains(arguments, environment) },
+    Builtin { name: "remove", arguments: 2, function: remove },
+    Builtin { name: "divmod", arguments: 2, function: |arguments, _| divmod(arguments) },
+    Builtin { name: "substring", arguments: 3, function: |arguments, _| substring(arguments) },
+    Builtin { name: "split", arguments: 2, function: split },
+    Builtin { name: "join", arguments: 2, function: |arguments, environment| join(arguments, environment) },
+    Builtin { name: "trim", arguments: 1, function: |arguments, _| Ok(Object::String(string(first(arguments))?.trim().to_string())) },
+    Builtin { name: "starts_with", arguments: 2, function: |arguments, _| starts_with(arguments) },
+    Builtin { name: "replace", arguments: 3, function: |arguments, _| replace(arguments) },
+    Builtin { name: "to_upper", arguments: 1, function: |arguments, _| Ok(Object::String(string(first(arguments))?.to_uppercase())) },
+    Builtin { name: "to_lower", arguments: 1, function: |arguments, _| Ok(Object::String(string(first(arguments))?.to_lowercase())) },
+    Builtin { name: "chars", arguments: 1, function: chars },
+];
+
+pub fn builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+pub fn builtins() -> impl Iterator<Item = &'static Builtin> {
+    BUILTINS.iter()
+}
+
+fn integer(object: Object) -> anyhow::Result<i128> {
+    match object {
+        Object::Integer(value) => Ok(value),
+        obj => bail!(ExpectedTypeButFound(Token::IntegerType, obj)),
+    }
+}
+
+fn integers(arguments: Vec<Object>, operation: fn(i128, i128) -> i128) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let left = integer(arguments.next().unwrap())?;
+    let right = integer(arguments.next().unwrap())?;
+    Ok(Object::Integer(operation(left, right)))
+}
+
+/// Fails on a zero divisor like the `/` operator, only the overflow of `MIN / -1` is handled by the operation
+fn divide(arguments: Vec<Object>, operation: fn(i128, i128) -> i128) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let left = integer(arguments.next().unwrap())?;
+    let right = integer(arguments.next().unwrap())?;
+    if right == 0 { bail!(DivisionByZero(left.to_string())) }
+    Ok(Object::Integer(operation(left, right)))
+}
+
+/// Fails on a negative exponent like the `**` operator, only the overflow is handled by the operation
+fn power(arguments: Vec<Object>, operation: fn(i128, u32) -> i128) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let base = integer(arguments.next().unwrap())?;
+    let exponent = integer(arguments.next().unwrap())?;
+    if exponent < 0 { bail!(NegativeExponent(exponent.to_string())) }
+    let exponent = u32::try_from(exponent).map_err(|_| CannotConvertInto(exponent.to_string(), "exponent".to_string()))?;
+    Ok(Object::Integer(operation(base, exponent)))
+}
+
+/// Truncates like the `/` and `%` operators, so the quotient and remainder match them
+fn divmod(arguments: Vec<Object>) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let left = integer(arguments.next().unwrap())?;
+    let right = integer(arguments.next().unwrap())?;
+    if right == 0 { bail!(DivisionByZero(left.to_string())) }
+
+    let quotient = left.checked_div(right).ok_or(IntegerOverflow(Token::Divide, left, right))?;
+    let remainder = left % right;
+    Ok(Object::Tuple(vec![OwnerShip::Instance(Object::Integer(quotient)), OwnerShip::Instance(Object::Integer(remainder))]))
+}
+
+fn first(arguments: Vec<Object>) -> Object {
+    arguments.into_iter().next().unwrap()
+}
+
+fn cannot_convert(object: Object, into: &str) -> anyhow::Error {
+    CannotConvertInto(format!("{:?}", object), into.to_string()).into()
+}
+
+/// Numbers are truncated towards zero
+fn to_int(object: Object) -> anyhow::Result<Object> {
+    let value = match &object {
+        Object::Integer(value) => Some(*value),
+        Object::Float(value) if value.is_finite() && value.trunc() >= i128::MIN as f64 && value.trunc() < i128::MAX as f64 => Some(*value as i128),
+        Object::BigInteger(value) => value.to_i128(),
+        Object::Decimal(value) => value.with_scale_round(0, RoundingMode::Down).to_i128(),
+        Object::String(value) => value.parse().ok(),
+        _ => None,
+    };
+    Ok(Object::Integer(value.ok_or_else(|| cannot_convert(object, "Integer"))?))
+}
+
+fn to_float(object: Object) -> anyhow::Result<Object> {
+    let value = match &object {
+        Object::Integer(value) => Some(*value as f64),
+        Object::Float(value) => Some(*value),
+        Object::BigInteger(value) => value.to_f64(),
+        Object::Decimal(value) => value.to_f64(),
+        Object::String(value) => value.parse().ok(),
+        _ => None,
+    };
+    Ok(Object::Float(value.ok_or_else(|| cannot_convert(object, "Float"))?))
+}
+
+/// Decimals are truncated towards zero
+fn to_bigint(object: Object) -> anyhow::Result<Object> {
+    let value = match &object {
+        Object::Integer(value) => Some(BigInt::from(*value)),
+        Object::BigInteger(value) => Some(value.clone()),
+        Object::Decimal(value) => Some(value.with_scale_round(0, RoundingMode::Down).into_bigint_and_exponent().0),
+        Object::String(value) => value.parse().ok(),
+        _ => None,
+    };
+    Ok(Object::BigInteger(value.ok_or_else(|| cannot_convert(object, "BigInteger"))?))
+}
+
+/// Floats are converted through their shortest representation, so `0.1` becomes exactly `0.1`
+fn to_decimal(object: Object) -> anyhow::Result<Object> {
+    let value = match &object {
+        Object::Integer(value) => Some(BigDecimal::from(*value)),
+        Object::Float(value) if value.is_finite() => value.to_string().parse().ok(),
+        Object::BigInteger(value) => Some(BigDecimal::from(value.clone())),
+        Object::Decimal(value) => Some(value.clone()),
+        Object::String(value) => value.parse().ok(),
+        _ => None,
+    };
+    Ok(Object::Decimal(value.ok_or_else(|| cannot_convert(object, "Decimal"))?))
+}
+
+fn len(object: Object, environment: &mut Environment) -> anyhow::Result<Object> {
+    match object {
+        Object::Array(values) => Ok(Object::Integer(values.len() as i128)),
+        Object::Map(map) => Ok(Object::Integer(environment.entries(map)?.len() as i128)),
+        Object::String(value) => Ok(Object::Integer(value.chars().count() as i128)),
+        obj => bail!(ExpectedTypeButFound(Token::ArrayType, obj)),
+    }
+}
+
+fn map(object: Object) -> anyhow::Result<Address> {
+    match object {
+        Object::Map(map) => Ok(map),
+        obj => bail!(ExpectedTypeButFound(Token::Map, obj)),
+    }
+}
+
+fn map_and_key(arguments: Vec<Object>) -> anyhow::Result<(Address, Key)> {
+    let mut arguments = arguments.into_iter();
+    let map = map(arguments.next().unwrap())?;
+    let key = Key::try_from(arguments.next().unwrap())?;
+    Ok((map, key))
+}
+
+/// Allocates the elements of an array which gets created by a builtin
+fn array(values: Vec<OwnerShip>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let values = values.into_iter()
+        .map(|value| Ok(OwnerShip::Heap(allocate(false, value, environment)?)))
+        .collect::<anyhow::Result<Vec<OwnerShip>>>()?;
+    Ok(Object::Array(values))
+}
+
+/// Keys of a map in ascending order
+fn keys(arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let keys = environment.entries(map(first(arguments))?)?.keys()
+        .map(|key| OwnerShip::Instance(key.clone().into()))
+        .collect();
+    array(keys, environment)
+}
+
+/// Values of a map, ordered by their keys
+fn values(arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let values = environment.entries(map(first(arguments))?)?.values().cloned().collect();
+    array(values, environment)
+}
+
+/// Pairs of keys and values, used to iterate over a map
+fn entries(arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let entries = environment.entries(map(first(arguments))?)?.iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<Vec<(Key, OwnerShip)>>();
+
+    let pairs = entries.into_iter()
+        .map(|(key, value)| Ok(OwnerShip::Instance(array(vec![OwnerShip::Instance(key.into()), value], environment)?)))
+        .collect::<anyhow::Result<Vec<OwnerShip>>>()?;
+    array(pairs, environment)
+}
+
+/// Checks a map for a key or a string for a substring
+fn contains(arguments: Vec<Object>, environment: &Environment) -> anyhow::Result<Object> {
+    if let Object::String(value) = &arguments[0] {
+        let part = string(arguments[1].clone())?;
+        return Ok(Object::Boolean(value.contains(&part)))
+    }
+    let (map, key) = map_and_key(arguments)?;
+    Ok(Object::Boolean(environment.entries(map)?.contains_key(&key)))
+}
+
+/// Removes an entry from a map and returns its value
+fn remove(arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let (map, key) = map_and_key(arguments)?;
+    let value = environment.entries_mut(map)?.remove(&key).ok_or(KeyNotFound(key.to_string()))?;
+    value.value(environment)
+}
+
+fn string(object: Object) -> anyhow::Result<String> {
+    match object {
+        Object::String(value) => Ok(value),
+        obj => bail!(ExpectedTypeButFound(Token::StringType, obj)),
+    }
+}
+
+fn strings(arguments: Vec<Object>) -> anyhow::Result<Vec<String>> {
+    arguments.into_iter().map(string).collect()
+}
+
+/// Characters from the start index up to, but not including, the end index
+fn substring(arguments: Vec<Object>) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let value = string(arguments.next().unwrap())?;
+    let start = integer(arguments.next().unwrap())?;
+    let end = integer(arguments.next().unwrap())?;
+
+    let (from, to) = slice_bounds(Some(start), Some(end), value.chars().count())?;
+    Ok(Object::String(value.chars().skip(from).take(to - from).collect()))
+}
+
+/// An empty separator splits between every character
+fn split(arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let arguments = strings(arguments)?;
+    let parts = if arguments[1].is_empty() {
+        arguments[0].chars().map(|char| char.to_string()).collect::<Vec<String>>()
+    } else {
+        arguments[0].split(&arguments[1]).map(str::to_string).collect()
+    };
+    array(parts.into_iter().map(|part| OwnerShip::Instance(Object::String(part))).collect(), environment)
+}
+
+/// Concatenates an array of strings with a separator between them
+fn join(arguments: Vec<Object>, environment: &Environment) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let values = match arguments.next().unwrap() {
+        Object::Array(values) => values,
+        obj => bail!(ExpectedTypeButFound(Token::ArrayType, obj)),
+    };
+    let separator = string(arguments.next().unwrap())?;
+
+    let parts = values.iter()
+        .map(|value| string(value.value(environment)?))
+        .collect::<anyhow::Result<Vec<String>>>()?;
+    Ok(Object::String(parts.join(&separator)))
+}
+
+fn starts_with(arguments: Vec<Object>) -> anyhow::Result<Object> {
+    let arguments = strings(arguments)?;
+    Ok(Object::Boolean(arguments[0].starts_with(&arguments[1])))
+}
+
+/// Replaces every occurrence of a pattern
+fn replace(arguments: Vec<Object>) -> anyhow::Result<Object> {
+    let arguments = strings(arguments)?;
+    Ok(Object::String(arguments[0].replace(&arguments[1], &arguments[2])))
+}
+
+/// Characters of a string as an array of strings
+fn chars(arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let chars = string(first(arguments))?.chars()
+        .map(|char| OwnerShip::Instance(Object::String(char.to_string())))
+        .collect();
+    array(chars, environment)
+}
+
+/// Formats a value with a specifier like the ones of interpolated strings
+fn format(arguments: Vec<Object>, environment: &Environment) -> anyhow::Result<Object> {
+    let mut arguments = arguments.into_iter();
+    let value = arguments.next().unwrap().resolve(environment)?;
+    let spec = string(arguments.next().unwrap())?;
+
+    let spec = FormatSpec::parse(&spec).ok_or(InvalidFormatSpec(spec))?;
+    Ok(Object::String(spec.apply(&value)?))
+}
@@ -1,26 +1,97 @@
-use std::collections::HashMap;
-use nanoid::nanoid;
-use crate::evaluator::object::{OwnerShip};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::bail;
+use crate::error::EvalError::{InvalidAddress, KeyNotFound, UnknownIdentifier};
+use crate::error::StackTrace;
+use crate::evaluator::hooks::Hooks;
+use crate::evaluator::object::{Key, Object, OwnerShip};
+use crate::lexer::token::Span;
+use crate::parser::ast::expression::Expression;
+use crate::parser::ast::statement::Statement;
+use crate::resolver::module::Module;
+use crate::resolver::Slot;
 
 #[derive(Default)]
 pub struct Environment {
     stack: Stack,
-    heap: Heap
+    heap: Heap,
+    call_stack: CallStack,
+
+    // Values which are only held by the evaluator while it evaluates other expressions
+    temporaries: Vec<OwnerShip>,
+
+    // Modules which already ran, by their canonical path
+    modules: HashMap<PathBuf, Arc<Module>>,
+
+    // Called before every statement and expression, while a debugger is attached
+    hooks: Option<Box<dyn Hooks>>,
+}
+
+/// Copy of the variables, heap and loaded modules, attached hooks are left out
+impl Clone for Environment {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            heap: self.heap.clone(),
+            call_stack: self.call_stack.clone(),
+            temporaries: self.temporaries.clone(),
+            modules: self.modules.clone(),
+            hooks: None,
+        }
+    }
 }
 
 impl Environment {
     pub fn get(&self, identifier: &str) -> Option<&OwnerShip> {
-        let stack_obj = self.stack.get(identifier);
-        if stack_obj.is_some() { return stack_obj }
-
-        self.heap.get(identifier)
+        self.stack.get(identifier)
     }
 
     pub fn get_mut(&mut self, identifier: &str) -> Option<&mut OwnerShip> {
-        let stack_obj = self.stack.get_mut(identifier);
-        if stack_obj.is_some() { return stack_obj }
+        self.stack.get_mut(identifier)
+    }
 
-        self.heap.get_mut(identifier)
+    pub fn lookup(&self, location: &Location) -> anyhow::Result<&OwnerShip> {
+        match location {
+            Location::Variable(variable) => Ok(self.stack.get_variable(variable).ok_or(UnknownIdentifier(variable.name.clone()))?),
+            Location::Heap(address) => Ok(self.heap.get(*address).ok_or(InvalidAddress(*address))?),
+            Location::Entry { map, key } => self.entries(*map)?.get(key).ok_or(KeyNotFound(key.to_string()).into()),
+        }
+    }
+
+    pub fn lookup_mut(&mut self, location: &Location) -> anyhow::Result<&mut OwnerShip> {
+        match location {
+            Location::Variable(variable) => Ok(self.stack.get_variable_mut(variable).ok_or(UnknownIdentifier(variable.name.clone()))?),
+            Location::Heap(address) => Ok(self.heap.get_mut(*address).ok_or(InvalidAddress(*address))?),
+            Location::Entry { map, key } => self.entries_mut(*map)?.get_mut(key).ok_or(KeyNotFound(key.to_string()).into()),
+        }
+    }
+
+    /// Entries of the map with the given handle
+    pub fn entries(&self, map: Address) -> anyhow::Result<&BTreeMap<Key, OwnerShip>> {
+        match self.heap.get(map).ok_or(InvalidAddress(map))? {
+            OwnerShip::Instance(Object::MapEntries(entries)) => Ok(entries),
+            _ => bail!(InvalidAddress(map)),
+        }
+    }
+
+    pub fn entries_mut(&mut self, map: Address) -> anyhow::Result<&mut BTreeMap<Key, OwnerShip>> {
+        match self.heap.get_mut(map).ok_or(InvalidAddress(map))? {
+            OwnerShip::Instance(Object::MapEntries(entries)) => Ok(entries),
+            _ => bail!(InvalidAddress(map)),
+        }
+    }
+
+    /// Follows the references to variables until the location that stores the actual value is found
+    pub fn resolve(&self, ownership: &OwnerShip) -> anyhow::Result<Option<Location>> {
+        let Some(mut location) = ownership.location() else { return Ok(None) };
+
+        while let OwnerShip::Reference(variable) = self.lookup(&location)? {
+            location = Location::Variable(variable.clone());
+        }
+
+        Ok(Some(location))
     }
 
 
@@ -37,11 +108,100 @@ impl Environment {
     pub fn heap_mut(&mut self) -> &mut Heap {
         &mut self.heap
     }
+
+    pub fn module(&self, path: &Path) -> Option<&Arc<Module>> {
+        self.modules.get(path)
+    }
+    pub fn add_module(&mut self, module: Arc<Module>) {
+        self.modules.insert(module.path.clone(), module);
+    }
+    pub fn modules(&self) -> impl Iterator<Item = &Arc<Module>> {
+        self.modules.values()
+    }
+
+    pub fn call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+    pub fn call_stack_mut(&mut self) -> &mut CallStack {
+        &mut self.call_stack
+    }
+
+    /// Attaches hooks which the tree walking evaluator calls, or removes them
+    pub fn set_hooks(&mut self, hooks: Option<Box<dyn Hooks>>) {
+        self.hooks = hooks;
+    }
+
+    pub fn before_statement(&mut self, statement: &Statement) -> anyhow::Result<()> {
+        let Some(mut hooks) = self.hooks.take() else { return Ok(()) };
+        let result = hooks.before_statement(statement, self);
+        self.hooks = Some(hooks);
+        result
+    }
+
+    pub fn before_expression(&mut self, expression: &Expression) -> anyhow::Result<()> {
+        let Some(mut hooks) = self.hooks.take() else { return Ok(()) };
+        let result = hooks.before_expression(expression, self);
+        self.hooks = Some(hooks);
+        result
+    }
+
+    /// Protects a value from being collected until it gets released again
+    pub fn retain(&mut self, value: OwnerShip) {
+        self.temporaries.push(value);
+    }
+
+    /// Releases the last retained values
+    pub fn release(&mut self, amount: usize) {
+        self.temporaries.truncate(self.temporaries.len().saturating_sub(amount));
+    }
+
+    /// Releases every retained value, only safe when no expression is being evaluated
+    pub fn release_all(&mut self) {
+        self.temporaries.clear();
+    }
+
+    /// Frees every heap value that cannot be reached from the stack, the call stack or a retained value
+    pub fn collect_garbage(&mut self) -> usize {
+        self.collect_garbage_with(&[])
+    }
+
+    /// Collects the garbage while keeping the operands of the virtual machine alive as well
+    pub fn collect_garbage_with(&mut self, operands: &[OwnerShip]) -> usize {
+        let handles = self.call_stack.frames.iter()
+            .flat_map(|frame| frame.arguments.iter().filter_map(Object::handle))
+            .map(OwnerShip::Heap)
+            .collect::<Vec<OwnerShip>>();
+        let roots = self.stack.values.iter()
+            .map(|(_, value)| value)
+            .chain(self.call_stack.frames.iter().flat_map(|frame| frame.arguments.iter().flat_map(Object::references)))
+            .chain(handles.iter())
+            .chain(self.temporaries.iter())
+            .chain(operands.iter());
+
+        self.heap.collect(roots)
+    }
+
+    /// Collects the garbage if enough values got allocated since the last collection
+    pub fn collect_garbage_if_needed(&mut self) {
+        self.collect_garbage_if_needed_with(&[])
+    }
+
+    /// Collects the garbage if needed while the virtual machine still works on the given operands
+    pub fn collect_garbage_if_needed_with(&mut self, operands: &[OwnerShip]) {
+        if self.heap.should_collect() {
+            self.collect_garbage_with(operands);
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Stack {
     values: Vec<(String, OwnerShip)>,
     scope_pointers: Vec<usize>,
+    // Index of the first scope of every function call, the top level counts as the first call
+    frame_pointers: Vec<usize>,
+    // Scope every function call was declared in, the variables of enclosing functions are reached through it
+    links: Vec<Option<usize>>,
 }
 
 impl Default for Stack {
@@ -49,16 +209,60 @@ impl Default for Stack {
         Self {
             values: vec![],
             scope_pointers: vec![0],
+            frame_pointers: vec![0],
+            links: vec![None],
         }
     }
 }
 
+/// Position of a variable on the stack
+#[derive(Debug, Clone)]
+#[derive(Eq, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub index: usize,
+}
+
 impl Stack {
     pub fn create_scope(&mut self) {
         self.scope_pointers.push(self.values.len());
     }
     pub fn drop_scope(&mut self) {
-        unsafe { self.values.set_len(self.scope_pointers.pop().unwrap()); }
+        self.values.truncate(self.scope_pointers.pop().unwrap());
+    }
+
+    /// Creates the scope of a function call, only the global scope and the scopes around the
+    /// declaration of the function, starting at the linked scope, are visible from it
+    pub fn create_frame(&mut self, link: Option<usize>) {
+        self.frame_pointers.push(self.scope_pointers.len());
+        self.links.push(link);
+        self.create_scope();
+    }
+    pub fn drop_frame(&mut self) {
+        self.drop_scope();
+        self.frame_pointers.pop();
+        self.links.pop();
+    }
+
+    /// Scope the value at the index was added to
+    pub fn scope_of(&self, index: usize) -> usize {
+        self.scope_pointers.partition_point(|start| *start <= index) - 1
+    }
+
+    /// Function call the scope belongs to
+    fn frame_of(&self, scope: usize) -> usize {
+        self.frame_pointers.partition_point(|start| *start <= scope) - 1
+    }
+
+    /// Amount of scopes which currently exist, including the scopes of every function call
+    pub fn scopes(&self) -> usize {
+        self.scope_pointers.len()
+    }
+    /// Drops scopes until only the given amount is left
+    pub fn unwind(&mut self, scopes: usize) {
+        while self.scope_pointers.len() > scopes {
+            self.drop_scope();
+        }
     }
 
     pub fn add(&mut self, identifier: String, value: OwnerShip) {
@@ -78,31 +282,283 @@ impl Stack {
             .find(|x| x.0 == identifier)
             .map(|x| &mut x.1)
     }
+
+    /// Finds the index of a variable, using the slot if the identifier got resolved
+    pub fn locate(&self, identifier: &str, slot: Option<Slot>) -> Option<usize> {
+        let index = match slot {
+            Some(slot) => self.slot_index(slot)?,
+            None => self.values.iter().rposition(|x| x.0 == identifier)?,
+        };
+
+        (self.values[index].0 == identifier).then_some(index)
+    }
+
+    fn slot_index(&self, slot: Slot) -> Option<usize> {
+        let frame = *self.frame_pointers.last().unwrap();
+        let visible_scopes = self.scope_pointers.len() - frame;
+
+        let scope = if slot.hops > 0 {
+            // Every hop leaves a function for the scope it was declared in
+            let mut call = self.frame_pointers.len() - 1;
+            let mut link = 0;
+            for _ in 0..slot.hops {
+                link = self.links[call]?;
+                call = self.frame_of(link);
+            }
+            link.checked_sub(slot.depth).filter(|scope| *scope >= self.frame_pointers[call])?
+        } else if slot.depth < visible_scopes {
+            self.scope_pointers.len() - 1 - slot.depth
+        } else if slot.depth == visible_scopes && frame != 0 {
+            0
+        } else {
+            return None;
+        };
+
+        let index = self.scope_pointers[scope] + slot.index;
+        let end = self.scope_pointers.get(scope + 1).copied().unwrap_or(self.values.len());
+
+        (index < end).then_some(index)
+    }
+
+    pub fn value(&self, index: usize) -> Option<&OwnerShip> {
+        self.values.get(index).map(|x| &x.1)
+    }
+
+    pub fn get_variable(&self, variable: &Variable) -> Option<&OwnerShip> {
+        self.values.get(variable.index)
+            .filter(|x| x.0 == variable.name)
+            .map(|x| &x.1)
+    }
+
+    pub fn get_variable_mut(&mut self, variable: &Variable) -> Option<&mut OwnerShip> {
+        self.values.get_mut(variable.index)
+            .filter(|x| x.0 == variable.name)
+            .map(|x| &mut x.1)
+    }
+
+    /// Amount of function calls which currently exist, the top level of the program counts as the first call
+    pub fn frames(&self) -> usize {
+        self.frame_pointers.len()
+    }
+
+    /// Variables of a function call in the order they were added, the globals belong to the top level
+    pub fn frame(&self, call: usize) -> &[(String, OwnerShip)] {
+        let start = self.scope_pointers[self.frame_pointers[call]];
+        let end = self.frame_pointers.get(call + 1).map_or(self.values.len(), |scope| self.scope_pointers[*scope]);
+        &self.values[start..end]
+    }
+
+    /// Identifiers of the global scope in the order they were added
+    pub fn globals(&self) -> impl Iterator<Item = &str> {
+        let end = self.scope_pointers.get(1).copied().unwrap_or(self.values.len());
+        self.values[..end].iter().map(|x| x.0.as_str())
+    }
 }
 
-#[derive(Default)]
+// Amount of live values at which the first collection happens
+const INITIAL_COLLECTION_THRESHOLD: usize = 1024;
+
+/// Handle to a value on the heap, the generation detects handles to slots that got reused
+#[derive(Debug, Clone, Copy)]
+#[derive(Eq, PartialEq, Hash)]
+pub struct Address {
+    index: u32,
+    generation: u32,
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}@{}", self.index, self.generation)
+    }
+}
+
+/// Place inside the environment which stores an ownership
+#[derive(Debug, Clone)]
+#[derive(Eq, PartialEq)]
+pub enum Location {
+    Variable(Variable),
+    Heap(Address),
+    // Entry of a map, which gets inserted by assigning to it
+    Entry { map: Address, key: Key },
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Variable(variable) => write!(f, "{}", variable.name),
+            Location::Heap(address) => write!(f, "{}", address),
+            Location::Entry { map, key } => write!(f, "{}[{}]", map, key),
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+struct HeapSlot {
+    generation: u32,
+    value: Option<OwnerShip>,
+}
+
+#[derive(Clone)]
 pub struct Heap {
-    memory: HashMap<String, OwnerShip>,
+    slots: Vec<HeapSlot>,
+    // Indices of the slots which currently store no value
+    free: Vec<u32>,
+
+    // Amount of live values at which the next collection happens
+    threshold: usize,
+    statistics: HeapStatistics,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+            threshold: INITIAL_COLLECTION_THRESHOLD,
+            statistics: HeapStatistics::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[derive(Eq, PartialEq)]
+pub struct HeapStatistics {
+    // Values which are currently stored on the heap
+    pub live: usize,
+    // Values which got stored on the heap since its creation
+    pub allocated: usize,
+    // Values which got freed by the garbage collector
+    pub freed: usize,
+    pub collections: usize,
 }
 
 impl Heap {
-    pub fn get(&self, address: &str) -> Option<&OwnerShip> {
-        self.memory.get(address)
+    pub fn get(&self, address: Address) -> Option<&OwnerShip> {
+        self.slots.get(address.index as usize)
+            .filter(|slot| slot.generation == address.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, address: Address) -> Option<&mut OwnerShip> {
+        self.slots.get_mut(address.index as usize)
+            .filter(|slot| slot.generation == address.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    pub fn set(&mut self, value: OwnerShip) -> Address {
+        self.statistics.allocated += 1;
+        self.statistics.live += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return Address { index, generation: slot.generation };
+        }
+
+        self.slots.push(HeapSlot { generation: 0, value: Some(value) });
+        Address { index: (self.slots.len() - 1) as u32, generation: 0 }
+    }
+
+    pub fn deallocate(&mut self, address: Address) {
+        if self.get(address).is_some() {
+            self.free_slot(address.index);
+            self.statistics.freed += 1;
+        }
+    }
+
+    fn free_slot(&mut self, index: u32) {
+        let slot = &mut self.slots[index as usize];
+        slot.value = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.statistics.live -= 1;
+    }
+
+    pub fn should_collect(&self) -> bool {
+        self.statistics.live >= self.threshold
+    }
+
+    pub fn statistics(&self) -> HeapStatistics {
+        self.statistics
+    }
+
+    /// Marks every value reachable from the roots and frees the rest, returns the amount of freed values
+    pub fn collect<'a>(&mut self, roots: impl IntoIterator<Item = &'a OwnerShip>) -> usize {
+        let mut marked = vec![false; self.slots.len()];
+        let mut pending: Vec<&OwnerShip> = roots.into_iter().collect();
+
+        while let Some(ownership) = pending.pop() {
+            let address = match ownership {
+                // References to variables are ignored because every variable is a root itself
+                OwnerShip::Reference(_) => continue,
+                OwnerShip::Heap(address) => *address,
+                OwnerShip::Instance(object) => {
+                    pending.extend(object.references());
+                    match object.handle() {
+                        Some(address) => address,
+                        None => continue,
+                    }
+                }
+            };
+
+            if let Some(value) = self.get(address) {
+                if !marked[address.index as usize] {
+                    marked[address.index as usize] = true;
+                    pending.push(value);
+                }
+            }
+        }
+
+        let mut freed = 0;
+        for (index, marked) in marked.into_iter().enumerate() {
+            if !marked && self.slots[index].value.is_some() {
+                self.free_slot(index as u32);
+                freed += 1;
+            }
+        }
+
+        self.statistics.freed += freed;
+        self.statistics.collections += 1;
+        self.threshold = INITIAL_COLLECTION_THRESHOLD.max(self.statistics.live * 2);
+
+        freed
     }
+}
 
-    pub fn get_mut(&mut self, address: &str) -> Option<&mut OwnerShip> {
-        self.memory.get_mut(address)
+/// Keeps track of the function calls which are currently being evaluated
+#[derive(Default, Clone)]
+pub struct CallStack {
+    frames: Vec<Frame>,
+}
+
+impl CallStack {
+    pub fn push(&mut self, frame: Frame) {
+        self.frames.push(frame);
     }
 
-    pub fn set(&mut self, value: OwnerShip) -> String {
-        let address = nanoid!(20);
-        self.memory.insert(address.clone(), value);
-        address
+    pub fn pop(&mut self) -> Option<Frame> {
+        self.frames.pop()
     }
 
-    pub fn deallocate(&mut self, address: &str) {
-        self.memory.remove(address);
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
     }
+
+    /// Snapshot of the current calls for the error, starting with the innermost one
+    pub fn trace(&self, error: &anyhow::Error) -> StackTrace {
+        StackTrace {
+            message: error.to_string(),
+            frames: self.frames.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name: String,
+    // Position of the call expression which created this frame
+    pub span: Span,
+    pub arguments: Vec<Object>,
 }
 
 // pub struct Stack {
@@ -0,0 +1,99 @@
+use anyhow::bail;
+use bigdecimal::RoundingMode;
+use crate::error::EvalError::PrecisionNotSupported;
+use crate::evaluator::object::Object;
+
+/// Side of the width a formatted value gets placed on
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+/// Specifier of a formatted value, written as `[[fill]align][width][.precision]` like `>8.2` or `*^10`
+#[derive(Eq, PartialEq)]
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct FormatSpec {
+    pub fill: char,
+    // Numbers are aligned to the right and every other value to the left if it is missing
+    pub alignment: Option<Alignment>,
+    pub width: usize,
+    // Digits after the decimal point of floats and decimals, maximum length of strings
+    pub precision: Option<usize>,
+}
+
+impl Default for FormatSpec {
+    fn default() -> Self {
+        Self { fill: ' ', alignment: None, width: 0, precision: None }
+    }
+}
+
+fn alignment(char: char) -> Option<Alignment> {
+    match char {
+        '<' => Some(Alignment::Left),
+        '>' => Some(Alignment::Right),
+        '^' => Some(Alignment::Center),
+        _ => None,
+    }
+}
+
+impl FormatSpec {
+    pub fn parse(spec: &str) -> Option<FormatSpec> {
+        let chars = spec.chars().collect::<Vec<char>>();
+        let mut format = FormatSpec::default();
+
+        let mut rest = &chars[..];
+        if let Some(align) = chars.get(1).copied().and_then(alignment) {
+            format.fill = chars[0];
+            format.alignment = Some(align);
+            rest = &chars[2..];
+        } else if let Some(align) = chars.first().copied().and_then(alignment) {
+            format.alignment = Some(align);
+            rest = &chars[1..];
+        }
+
+        let digits = rest.iter().take_while(|char| char.is_ascii_digit()).count();
+        if digits > 0 {
+            format.width = rest[..digits].iter().collect::<String>().parse().ok()?;
+        }
+        rest = &rest[digits..];
+
+        if let Some(('.', precision)) = rest.split_first() {
+            if precision.is_empty() || !precision.iter().all(|char| char.is_ascii_digit()) { return None }
+            format.precision = Some(precision.iter().collect::<String>().parse().ok()?);
+        } else if !rest.is_empty() {
+            return None
+        }
+        Some(format)
+    }
+
+    /// Formats an object, its arrays and maps have to be resolved already
+    pub fn apply(&self, object: &Object) -> anyhow::Result<String> {
+        let text = match (object, self.precision) {
+            (object, None) => object.to_string(),
+            (Object::Float(value), Some(precision)) => format!("{:.*}", precision, value),
+            (Object::Decimal(value), Some(precision)) => value.with_scale_round(precision as i64, RoundingMode::HalfEven).to_string(),
+            (Object::String(value), Some(precision)) => value.chars().take(precision).collect(),
+            (object, Some(_)) => bail!(PrecisionNotSupported(object.clone())),
+        };
+
+        let length = text.chars().count();
+        if length >= self.width { return Ok(text) }
+
+        let numeric = matches!(object, Object::Integer(_) | Object::Float(_) | Object::BigInteger(_) | Object::Decimal(_));
+        let alignment = self.alignment.unwrap_or(if numeric { Alignment::Right } else { Alignment::Left });
+        let padding = self.width - length;
+        let (before, after) = match alignment {
+            Alignment::Left => (0, padding),
+            Alignment::Right => (padding, 0),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+        };
+
+        let fill = |amount: usize| std::iter::repeat_n(self.fill, amount).collect::<String>();
+        Ok(format!("{}{}{}", fill(before), text, fill(after)))
+    }
+}
@@ -0,0 +1,15 @@
+use crate::evaluator::environment::Environment;
+use crate::parser::ast::expression::Expression;
+use crate::parser::ast::statement::Statement;
+
+/// Gets called by the tree walking evaluator before it evaluates a statement or an expression, errors stop the program.
+/// The hooks are taken out of the environment while they run, so code they evaluate does not call them again.
+pub trait Hooks {
+    fn before_statement(&mut self, _statement: &Statement, _environment: &mut Environment) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn before_expression(&mut self, _expression: &Expression, _environment: &mut Environment) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -1,11 +1,45 @@
 pub mod object;
 pub mod environment;
+pub mod builtins;
+pub mod format;
+pub mod printer;
+pub mod hooks;
 
 use std::vec::IntoIter;
-use clap::builder::EnumValueParser;
+use clap::ValueEnum;
 use crate::evaluator::environment::Environment;
 use crate::evaluator::object::{Object, OwnerShip};
 use crate::parser::ast::statement::Statement;
+use crate::vm::compiler::Compiler;
+use crate::vm::VirtualMachine;
+
+/// Backend which runs a program, both give the same results
+#[derive(Debug, Clone, Copy)]
+#[derive(Eq, PartialEq)]
+#[derive(Default, ValueEnum)]
+pub enum Backend {
+    // Walks the syntax tree
+    #[default]
+    Tree,
+    // Compiles to bytecode which runs on the virtual machine
+    Bytecode,
+}
+
+/// Receives the value of every top level expression statement right after it got evaluated
+pub type Output<'a> = &'a mut dyn FnMut(&OwnerShip, &Environment);
+
+/// Runs already resolved statements with the given backend
+pub fn execute(statements: Vec<Statement>, backend: Backend, environment: &mut Environment) -> anyhow::Result<()> {
+    execute_with(statements, backend, environment, &mut |_, _| {})
+}
+
+/// Runs already resolved statements with the given backend and passes the values of expression statements to the output
+pub fn execute_with(statements: Vec<Statement>, backend: Backend, environment: &mut Environment, output: Output) -> anyhow::Result<()> {
+    match backend {
+        Backend::Tree => Evaluator::new(statements.into_iter()).evaluate_with(environment, output),
+        Backend::Bytecode => VirtualMachine::new(Compiler::compile(&statements).into_iter()).evaluate_with(environment, output),
+    }
+}
 
 pub struct Evaluator {
     statements: IntoIter<Statement>,
@@ -19,8 +53,17 @@ impl Evaluator {
 
 impl Evaluator {
     pub fn evaluate(&mut self, environment: &mut Environment) -> anyhow::Result<()> {
-        while let Some(statement) = self.statements.next() {
-            println!("{:?}", statement.evaluate(environment)?);
+        self.evaluate_with(environment, &mut |_, _| {})
+    }
+
+    pub fn evaluate_with(&mut self, environment: &mut Environment, output: Output) -> anyhow::Result<()> {
+        for statement in self.statements.by_ref() {
+            environment.release_all();
+            environment.collect_garbage_if_needed();
+            let (ownership, _) = statement.evaluate(environment)?;
+            if let Statement::Expression { .. } = statement {
+                output(&ownership, environment);
+            }
         }
         Ok(())
     }
@@ -29,6 +72,7 @@ impl Evaluator {
 pub fn evaluate_block(statements: &Vec<Box<Statement>>, remove_ret: bool, environment: &mut Environment) -> anyhow::Result<(OwnerShip, bool)> {
     let mut result = (OwnerShip::Instance(Object::Null), false);
     for statement in statements {
+        environment.collect_garbage_if_needed();
         result = statement.evaluate(environment)?;
         if result.1 { break }
     }
@@ -1,32 +1,59 @@
-use crate::error::EvalError::UnknownIdentifier;
-use crate::evaluator::environment::Environment;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use std::sync::{Arc, OnceLock};
+use crate::error::EvalError::{InvalidAddress, InvalidMapKey, UnknownIdentifier};
+use crate::evaluator::builtins::Builtin;
+use crate::evaluator::environment::{Address, Environment, Location, Variable};
 use crate::lexer::token::Token;
 use crate::parser::ast::expression::Expression;
+use crate::vm::chunk::Chunk;
 
 #[derive(Debug, Clone)]
 pub enum OwnerShip {
-    Reference(String),
+    // Refers to a variable on the stack
+    Reference(Variable),
+    // Refers to a value stored on the heap
+    Heap(Address),
     Instance(Object),
 }
 
 impl OwnerShip {
     pub fn value(&self, environment: &Environment) -> anyhow::Result<Object> {
-        match self {
-            OwnerShip::Reference(identifier) => {
-                let mut value = environment.get(identifier).ok_or(UnknownIdentifier(identifier.clone()))?.clone();
+        let mut ownership = self;
+
+        loop {
+            ownership = match ownership {
+                OwnerShip::Reference(variable) => environment.stack().get_variable(variable).ok_or(UnknownIdentifier(variable.name.clone()))?,
+                OwnerShip::Heap(address) => environment.heap().get(*address).ok_or(InvalidAddress(*address))?,
+                OwnerShip::Instance(val) => return Ok(val.clone()),
+            }
+        }
+    }
+
+    /// Ownership which gets stored when the value is bound to a variable or an array element.
+    /// Arrays are shared through their heap address, maps through their handle and every other value gets copied.
+    pub fn bind(&self, environment: &Environment) -> anyhow::Result<OwnerShip> {
+        let mut ownership = self;
 
-                loop {
-                    match value {
-                        OwnerShip::Reference(identifier) => {
-                            value = environment.get(&identifier).ok_or(UnknownIdentifier(identifier))?.clone();
-                        }
-                        OwnerShip::Instance(val) => {
-                            return Ok(val);
-                        }
-                    }
-                }
-            },
-            OwnerShip::Instance(val) => Ok(val.clone()),
+        loop {
+            ownership = match ownership {
+                OwnerShip::Reference(variable) => environment.stack().get_variable(variable).ok_or(UnknownIdentifier(variable.name.clone()))?,
+                OwnerShip::Heap(address) => match environment.heap().get(*address).ok_or(InvalidAddress(*address))? {
+                    OwnerShip::Instance(Object::Array(_)) => return Ok(ownership.clone()),
+                    stored => stored,
+                },
+                OwnerShip::Instance(_) => return Ok(ownership.clone()),
+            }
+        }
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            OwnerShip::Reference(variable) => Some(Location::Variable(variable.clone())),
+            OwnerShip::Heap(address) => Some(Location::Heap(*address)),
+            OwnerShip::Instance(_) => None,
         }
     }
 }
@@ -37,13 +64,89 @@ pub enum Object {
     Float(f64),
     String(String),
     Boolean(bool),
+    BigInteger(BigInt),
+    Decimal(BigDecimal),
     Null,
     Array(Vec<OwnerShip>),
+    // Handle to the entries of a map, copies of it share the same entries
+    Map(Address),
+    // Entries of a map, they are only ever stored on the heap
+    MapEntries(BTreeMap<Key, OwnerShip>),
+    Tuple(Vec<OwnerShip>),
     Error(Box<Object>),
-    Function {
-        parameters: Vec<(String, Token)>,
-        typee: Token,
-        body: Box<Expression>,
+    Function(Arc<Function>),
+    Builtin(&'static Builtin),
+}
+
+/// Value which can be used as the key of a map
+#[derive(Debug, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd)]
+pub enum Key {
+    Integer(i128),
+    BigInteger(BigInt),
+    String(String),
+    Boolean(bool),
+}
+
+impl TryFrom<Object> for Key {
+    type Error = anyhow::Error;
+
+    fn try_from(object: Object) -> anyhow::Result<Self> {
+        match object {
+            Object::Integer(value) => Ok(Key::Integer(value)),
+            Object::BigInteger(value) => Ok(Key::BigInteger(value)),
+            Object::String(value) => Ok(Key::String(value)),
+            Object::Boolean(value) => Ok(Key::Boolean(value)),
+            obj => Err(InvalidMapKey(obj).into()),
+        }
+    }
+}
+
+impl From<Key> for Object {
+    fn from(key: Key) -> Self {
+        match key {
+            Key::Integer(value) => Object::Integer(value),
+            Key::BigInteger(value) => Object::BigInteger(value),
+            Key::String(value) => Object::String(value),
+            Key::Boolean(value) => Object::Boolean(value),
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Integer(value) => write!(f, "{}", value),
+            Key::BigInteger(value) => write!(f, "{}n", value),
+            Key::String(value) => write!(f, "{:?}", value),
+            Key::Boolean(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+pub struct Function {
+    pub name: String,
+    pub parameters: Vec<(String, Token)>,
+    pub typee: Token,
+    pub body: Arc<Expression>,
+    // Bytecode of the body, compiled by the virtual machine on the first call
+    pub code: OnceLock<Chunk>,
+}
+
+impl Function {
+    pub fn new(name: String, parameters: Vec<(String, Token)>, typee: Token, body: Arc<Expression>) -> Self {
+        Self { name, parameters, typee, body, code: OnceLock::new() }
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("parameters", &self.parameters)
+            .field("typee", &self.typee)
+            .field("body", &self.body)
+            .finish()
     }
 }
 
@@ -51,4 +154,143 @@ impl Object {
     pub fn equal_variant(&self, other: &Object) -> bool {
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
-}
\ No newline at end of file
+
+    /// Ownerships which are stored inside this object
+    pub fn references(&self) -> Vec<&OwnerShip> {
+        match self {
+            Object::Array(values) |
+            Object::Tuple(values) => values.iter().collect(),
+            Object::MapEntries(entries) => entries.values().collect(),
+            Object::Error(value) => value.references(),
+            _ => vec![],
+        }
+    }
+
+    /// Heap address a handle inside this object points to
+    pub fn handle(&self) -> Option<Address> {
+        match self {
+            Object::Map(address) => Some(*address),
+            Object::Error(value) => value.handle(),
+            _ => None,
+        }
+    }
+
+    /// Copy whose arrays, tuples and maps only contain instances, so it can be displayed without the environment.
+    /// Values which are already getting resolved are kept as they are, which breaks cycles.
+    pub fn resolve(&self, environment: &Environment) -> anyhow::Result<Object> {
+        self.resolve_along(environment, &mut vec![])
+    }
+
+    fn resolve_along(&self, environment: &Environment, path: &mut Vec<Address>) -> anyhow::Result<Object> {
+        let resolve_all = |values: &[OwnerShip], path: &mut Vec<Address>| values.iter()
+            .map(|value| value.resolve_along(environment, path))
+            .collect::<anyhow::Result<Vec<OwnerShip>>>();
+
+        Ok(match self {
+            Object::Array(values) => Object::Array(resolve_all(values, path)?),
+            Object::Tuple(values) => Object::Tuple(resolve_all(values, path)?),
+            Object::Map(address) if !path.contains(address) => {
+                path.push(*address);
+                let entries = environment.entries(*address)?.iter()
+                    .map(|(key, value)| Ok((key.clone(), value.resolve_along(environment, path)?)))
+                    .collect::<anyhow::Result<BTreeMap<Key, OwnerShip>>>()?;
+                path.pop();
+                Object::MapEntries(entries)
+            }
+            Object::Error(value) => Object::Error(Box::new(value.resolve_along(environment, path)?)),
+            object => object.clone(),
+        })
+    }
+
+    /// Name of the type of a resolved object like it is written in the source, functions show their signature
+    pub fn type_name(&self) -> String {
+        match self {
+            Object::Integer(_) => Token::IntegerType.type_name(),
+            Object::Float(_) => Token::FloatType.type_name(),
+            Object::String(_) => Token::StringType.type_name(),
+            Object::Boolean(_) => Token::BooleanType.type_name(),
+            Object::BigInteger(_) => Token::BigIntegerType.type_name(),
+            Object::Decimal(_) => Token::DecimalType.type_name(),
+            Object::Null => Token::NullType.type_name(),
+            Object::Array(_) => Token::ArrayType.type_name(),
+            Object::Map(_) | Object::MapEntries(_) => Token::Map.type_name(),
+            Object::Tuple(values) => {
+                let types = values.iter().map(|value| match value {
+                    OwnerShip::Instance(object) => object.type_name(),
+                    _ => "...".to_string(),
+                }).collect::<Vec<String>>();
+                format!("({})", types.join(", "))
+            }
+            Object::Error(value) => format!("err({})", value.type_name()),
+            Object::Function(_) | Object::Builtin(_) => self.to_string(),
+        }
+    }
+}
+
+impl OwnerShip {
+    /// Resolves the value like `Object::resolve`, the heap address it is stored at counts as visited,
+    /// so an array which contains itself repeats at its first element that refers back to it
+    pub fn resolve(&self, environment: &Environment) -> anyhow::Result<Object> {
+        let mut ownership = self;
+        while let OwnerShip::Reference(variable) = ownership {
+            ownership = environment.stack().get_variable(variable).ok_or(UnknownIdentifier(variable.name.clone()))?;
+        }
+        ownership.resolve_along(environment, &mut vec![])?.value(environment)
+    }
+
+    fn resolve_along(&self, environment: &Environment, path: &mut Vec<Address>) -> anyhow::Result<OwnerShip> {
+        let OwnerShip::Heap(address) = self else {
+            return Ok(OwnerShip::Instance(self.value(environment)?.resolve_along(environment, path)?))
+        };
+        if path.contains(address) { return Ok(self.clone()) }
+
+        path.push(*address);
+        let value = self.value(environment)?.resolve_along(environment, path)?;
+        path.pop();
+        Ok(OwnerShip::Instance(value))
+    }
+}
+
+/// Strings inside of collections are quoted, values which are not resolved are left out
+fn element(ownership: &OwnerShip) -> String {
+    match ownership {
+        OwnerShip::Instance(Object::String(value)) => format!("{:?}", value),
+        OwnerShip::Instance(object) => object.to_string(),
+        _ => "...".to_string(),
+    }
+}
+
+fn elements<'a>(values: impl Iterator<Item = &'a OwnerShip>) -> String {
+    values.map(element).collect::<Vec<String>>().join(", ")
+}
+
+impl Display for Object {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::Float(value) => write!(f, "{:?}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::BigInteger(value) => write!(f, "{}", value),
+            Object::Decimal(value) => write!(f, "{}", value),
+            Object::Null => write!(f, "null"),
+            Object::Array(values) => write!(f, "[{}]", elements(values.iter())),
+            Object::Map(_) => write!(f, "{{...}}"),
+            Object::MapEntries(entries) => {
+                let entries = entries.iter()
+                    .map(|(key, value)| format!("{}: {}", key, element(value)))
+                    .collect::<Vec<String>>();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Object::Tuple(values) => write!(f, "({})", elements(values.iter())),
+            Object::Error(value) => write!(f, "err({})", element(&OwnerShip::Instance(value.as_ref().clone()))),
+            Object::Function(function) => {
+                let parameters = function.parameters.iter()
+                    .map(|(name, typee)| format!("{}: {}", name, typee.type_name()))
+                    .collect::<Vec<String>>();
+                write!(f, "function {}({}): {}", function.name, parameters.join(", "), function.typee.type_name())
+            }
+            Object::Builtin(builtin) => write!(f, "builtin {}", builtin.name),
+        }
+    }
+}
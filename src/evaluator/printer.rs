@@ -0,0 +1,11 @@
+use crate::evaluator::environment::Environment;
+use crate::evaluator::object::{Object, OwnerShip};
+
+/// Readable form of a value like the REPL shows it. References are followed and arrays and maps are read from the heap,
+/// strings are quoted and a collection which contains itself shows `...` where it repeats.
+pub fn print(ownership: &OwnerShip, environment: &Environment) -> anyhow::Result<String> {
+    Ok(match ownership.resolve(environment)? {
+        Object::String(value) => format!("{:?}", value),
+        object => object.to_string(),
+    })
+}
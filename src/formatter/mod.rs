@@ -0,0 +1,449 @@
+use std::fs;
+use std::io::{stdin, Read};
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::vec::IntoIter;
+use colored::Colorize;
+use crate::evaluator::format::{Alignment, FormatSpec};
+use crate::lexer::token::{Comment, Token};
+use crate::lexer::Lexer;
+use crate::parser::ast::expression::{Expression, FormatPart};
+use crate::parser::ast::statement::Statement;
+use crate::parser::layout::{Layout, Lines, StatementLayout};
+use crate::parser::precedences::Precedences;
+use crate::parser::Parser;
+
+const INDENT: &str = "    ";
+
+/// Settings of the `fmt` subcommand
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct FormatArguments {
+    /// Reports files which are not formatted instead of rewriting them
+    #[arg(long)]
+    pub check: bool,
+    /// Files which get formatted, the standard input is formatted to the standard output without them
+    pub paths: Vec<PathBuf>,
+}
+
+/// Formats the files of the arguments and returns whether all of them could be parsed and, when checking, were formatted
+pub fn run(arguments: &FormatArguments) -> bool {
+    if arguments.paths.is_empty() {
+        let mut source = String::new();
+        if let Err(err) = stdin().read_to_string(&mut source) {
+            eprintln!("{}", format!("Cannot read the standard input: {}", err).bright_red());
+            return false
+        }
+        return match format(&source) {
+            Ok(formatted) if arguments.check => formatted == source,
+            Ok(formatted) => {
+                print!("{}", formatted);
+                true
+            }
+            Err(err) => {
+                eprintln!("{}", format!("{}", err).bright_red());
+                false
+            }
+        }
+    }
+
+    let mut success = true;
+    for path in &arguments.paths {
+        let result = fs::read_to_string(path).map_err(anyhow::Error::from).and_then(|source| {
+            let formatted = format(&source)?;
+            if formatted == source { return Ok(true) }
+            if arguments.check { return Ok(false) }
+            fs::write(path, formatted)?;
+            Ok(true)
+        });
+
+        match result {
+            Ok(true) => {}
+            Ok(false) => {
+                println!("{}", format!("{} is not formatted", path.display()).bright_yellow());
+                success = false;
+            }
+            Err(err) => {
+                eprintln!("{}", format!("Cannot format {}: {}", path.display(), err).bright_red());
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Prints the canonical source of a program. Comments are kept at the statements they are written next to,
+/// consecutive blank lines are merged and everything else about the layout is replaced.
+pub fn format(source: &str) -> anyhow::Result<String> {
+    let (tokens, comments) = Lexer::from_string(source.to_string()).lex_with_comments();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let statements = parser.parse()?;
+
+    let mut formatter = Formatter::new(parser.layout().clone(), comments);
+    formatter.program(&statements);
+    Ok(formatter.output)
+}
+
+/// Prints a syntax tree as source. It walks the tree in the order the parser built it,
+/// so the layout of every statement and block is the next one of the parsed layout.
+pub struct Formatter {
+    output: String,
+    indent: usize,
+    comments: Peekable<IntoIter<Comment>>,
+    statements: Peekable<IntoIter<StatementLayout>>,
+    blocks: IntoIter<Lines>,
+    // Last line of the source which got printed inside the current block, a blank line after it is kept
+    previous: Option<usize>,
+    // Line which closes the current block, comments behind it belong to the statement the block is part of
+    closing: Option<usize>,
+    // Line which closed the last printed block
+    closed: Option<usize>,
+}
+
+impl Formatter {
+    pub fn new(layout: Layout, comments: Vec<Comment>) -> Self {
+        Self {
+            output: String::new(),
+            indent: 0,
+            comments: comments.into_iter().peekable(),
+            statements: layout.statements.into_iter().peekable(),
+            blocks: layout.blocks.into_iter(),
+            previous: None,
+            closing: None,
+            closed: None,
+        }
+    }
+
+    pub fn program(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.line(statement);
+        }
+        self.comments_before(usize::MAX);
+    }
+
+    /// Prints a statement on its own line, together with the comments in front of and behind it
+    fn line(&mut self, statement: &Statement) {
+        let layout = self.statements.next();
+        if let Some(layout) = layout {
+            self.comments_before(layout.lines.start);
+            self.separate(layout.lines.start);
+        }
+
+        let start = self.output.len();
+        self.push_indent();
+        self.statement(statement, layout);
+
+        if let Some(layout) = layout {
+            let end = layout.lines.end;
+            self.previous = Some(end);
+            // Comments inside of a statement which is printed on fewer lines, like a multi-line array, move in front of it
+            let mut moved = String::new();
+            while let Some(comment) = self.comments.next_if(|comment| comment.span.line < end) {
+                moved.push_str(&format!("{}//{}\n", INDENT.repeat(self.indent), comment.text));
+            }
+            self.output.insert_str(start, &moved);
+
+            // A comment behind a statement which shares its line with the next one is printed behind the last of them
+            let shared = self.statements.peek().is_some_and(|next| next.lines.start == end) || self.closing == Some(end);
+            if !shared {
+                if let Some(comment) = self.comments.next_if(|comment| comment.trailing && comment.span.line == end) {
+                    self.output.push_str(&format!(" //{}", comment.text));
+                }
+            }
+        }
+        self.output.push('\n');
+    }
+
+    /// Prints the comments which start before the given line, each on its own line
+    fn comments_before(&mut self, line: usize) {
+        while let Some(comment) = self.comments.next_if(|comment| comment.span.line < line) {
+            self.separate(comment.span.line);
+            self.push_indent();
+            self.output.push_str(&format!("//{}\n", comment.text));
+            self.previous = Some(comment.span.line);
+        }
+    }
+
+    /// Keeps a single blank line if the source has blank lines in front of the given line
+    fn separate(&mut self, line: usize) {
+        if self.previous.is_some_and(|previous| line > previous + 1) {
+            self.output.push('\n');
+        }
+    }
+
+    fn push_indent(&mut self) {
+        self.output.push_str(&INDENT.repeat(self.indent));
+    }
+
+    fn push(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn statement(&mut self, statement: &Statement, layout: Option<StatementLayout>) {
+        match statement {
+            Statement::Let { name, typee, value } => {
+                self.push(&format!("let {}: {} = ", name, typee.type_name()));
+                self.expression(value);
+                self.push(";");
+            }
+            Statement::Destructure { pattern, typee, value } => {
+                self.push(&format!("let {}: {} = ", pattern, typee.type_name()));
+                self.expression(value);
+                self.push(";");
+            }
+            Statement::Return { value } => {
+                self.push("ret ");
+                self.expression(value);
+                self.push(";");
+            }
+            Statement::Expression { value } => {
+                self.expression(value);
+                if layout.is_none_or(|layout| layout.semicolon) {
+                    self.push(";");
+                }
+            }
+            Statement::Function { name, parameter, typee, body } => {
+                let parameters = parameter.iter()
+                    .map(|(name, typee)| format!("{}: {}", name, typee.type_name()))
+                    .collect::<Vec<String>>();
+                self.push(&format!("function {}({}): {} ", name, parameters.join(", "), typee.type_name()));
+                self.expression(body);
+            }
+            Statement::Import { path, names, .. } => match names {
+                Some(names) => {
+                    let names = names.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>();
+                    self.push(&format!("import {{ {} }} from \"{}\";", names.join(", "), path));
+                }
+                None => self.push(&format!("import \"{}\";", path)),
+            }
+            Statement::Export { statement } => {
+                self.push("pub ");
+                let layout = self.statements.next();
+                self.statement(statement, layout);
+            }
+        }
+    }
+
+    fn block(&mut self, statements: &[Box<Statement>]) {
+        let lines = self.blocks.next();
+        let commented = lines.is_some_and(|lines| self.comments.peek().is_some_and(|comment| comment.span.line < lines.end));
+        if statements.is_empty() && !commented {
+            return self.push("{}")
+        }
+
+        self.push("{\n");
+        self.indent += 1;
+        // Blank lines at the start of a block are dropped
+        self.previous = None;
+        let closing = std::mem::replace(&mut self.closing, lines.map(|lines| lines.end));
+        for statement in statements {
+            self.line(statement);
+        }
+        if let Some(lines) = lines {
+            self.comments_before(lines.end);
+        }
+        self.closing = closing;
+        self.closed = lines.map(|lines| lines.end);
+        self.indent -= 1;
+        self.push_indent();
+        self.push("}");
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Identifier { name, .. } => self.push(name),
+            Expression::Integer { value } |
+            Expression::Float { value } |
+            Expression::Boolean { value } => self.push(value),
+            Expression::String { value } => self.push(&format!("\"{}\"", value)),
+            Expression::BigInteger { value } => self.push(&format!("{}n", value)),
+            Expression::Decimal { value } => self.push(&format!("{}d", value)),
+            Expression::Prefix { prefix, value } => {
+                self.push(operator(prefix));
+                let grouped = match value.as_ref() {
+                    Expression::Infix { operation, .. } => operation.precedence() <= Precedences::Prefix,
+                    Expression::Prefix { .. } | Expression::Assign { .. } => true,
+                    _ => false,
+                };
+                self.operand(value, grouped);
+            }
+            Expression::Infix { left, operation, right } => {
+                let precedence = operation.precedence();
+                let grouped = match left.as_ref() {
+                    Expression::Infix { operation: inner, .. } => {
+                        inner.precedence() < precedence || (*operation == Token::Power && *inner == Token::Power)
+                    }
+                    Expression::Prefix { .. } => Precedences::Prefix < precedence,
+                    Expression::Assign { .. } => true,
+                    _ => false,
+                };
+                self.operand(left, grouped);
+
+                self.push(&format!(" {} ", operator(operation)));
+
+                // The right side of a power may contain further powers, see `Parser::parse_infix_expression`
+                let bound = if *operation == Token::Power { Precedences::Prefix } else { precedence };
+                let grouped = match right.as_ref() {
+                    Expression::Infix { operation, .. } => operation.precedence() <= bound,
+                    Expression::Assign { .. } => true,
+                    _ => false,
+                };
+                self.operand(right, grouped);
+            }
+            Expression::If { condition, consequence, alternative } => {
+                self.push("if (");
+                self.expression(condition);
+                self.push(") ");
+                self.expression(consequence);
+                if let Some(alternative) = alternative {
+                    // A comment behind the closing brace stays there when the else starts on a later line
+                    let closed = self.closed;
+                    let separated = self.blocks.as_slice().first().is_some_and(|lines| closed.is_some_and(|closed| lines.start > closed));
+                    match self.comments.next_if(|comment| separated && comment.trailing && Some(comment.span.line) == closed) {
+                        Some(comment) => {
+                            self.push(&format!(" //{}\n", comment.text));
+                            self.push_indent();
+                            self.push("else ");
+                        }
+                        None => self.push(" else "),
+                    }
+                    self.expression(alternative);
+                }
+            }
+            Expression::While { condition, consequence } => {
+                self.push("while (");
+                self.expression(condition);
+                self.push(") ");
+                self.expression(consequence);
+            }
+            Expression::Call { name, arguments, .. } => {
+                self.push(name);
+                self.push("(");
+                self.list(arguments);
+                self.push(")");
+            }
+            Expression::Error { value } => {
+                self.push("err(");
+                self.expression(value);
+                self.push(")");
+            }
+            Expression::Assign { assign_to, value } => {
+                self.expression(assign_to);
+                self.push(" = ");
+                self.expression(value);
+            }
+            Expression::Array { values } => {
+                self.push("[");
+                self.list(values);
+                self.push("]");
+            }
+            Expression::Map { entries } => {
+                self.push("{");
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 { self.push(", ") }
+                    self.expression(key);
+                    self.push(": ");
+                    self.expression(value);
+                }
+                self.push("}");
+            }
+            Expression::Tuple { values } => {
+                self.push("(");
+                self.list(values);
+                self.push(")");
+            }
+            Expression::Block { statements } => self.block(statements),
+            Expression::Access { source, index } => {
+                self.source(source);
+                self.push("[");
+                self.expression(index);
+                self.push("]");
+            }
+            Expression::Slice { source, start, end } => {
+                self.source(source);
+                self.push("[");
+                if let Some(start) = start { self.expression(start) }
+                self.push("..");
+                if let Some(end) = end { self.expression(end) }
+                self.push("]");
+            }
+            Expression::Format { parts } => {
+                self.push("f\"");
+                for part in parts {
+                    match part {
+                        FormatPart::Literal(literal) => self.push(&literal.replace('{', "{{").replace('}', "}}")),
+                        FormatPart::Value { value, spec } => {
+                            // Values are parsed on their own, so they have no layout
+                            let mut nested = Formatter::new(Layout::default(), vec![]);
+                            nested.expression(value);
+                            self.push(&format!("{{{}{}}}", nested.output, specifier(spec)));
+                        }
+                    }
+                }
+                self.push("\"");
+            }
+        }
+    }
+
+    fn operand(&mut self, expression: &Expression, grouped: bool) {
+        if grouped { self.push("(") }
+        self.expression(expression);
+        if grouped { self.push(")") }
+    }
+
+    /// Prints the collection of an access or slice, operations have to be grouped to be accessed
+    fn source(&mut self, expression: &Expression) {
+        let grouped = matches!(expression, Expression::Infix { .. } | Expression::Prefix { .. } | Expression::Assign { .. });
+        self.operand(expression, grouped);
+    }
+
+    fn list(&mut self, values: &[Box<Expression>]) {
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 { self.push(", ") }
+            self.expression(value);
+        }
+    }
+}
+
+fn operator(token: &Token) -> &'static str {
+    match token {
+        Token::Add => "+",
+        Token::Subtract => "-",
+        Token::Multiply => "*",
+        Token::Divide => "/",
+        Token::Modular => "%",
+        Token::Power => "**",
+        Token::SingleAnd => "&",
+        Token::BitOr => "|",
+        Token::BitXor => "^",
+        Token::BitNot => "~",
+        Token::ShiftLeft => "<<",
+        Token::ShiftRight => ">>",
+        Token::Invert => "!",
+        Token::And => "&&",
+        Token::Or => "||",
+        Token::Equal => "==",
+        Token::NotEqual => "!=",
+        Token::GreaterThan => ">",
+        Token::LessThan => "<",
+        Token::GreaterThanEqual => ">=",
+        Token::LessThanEqual => "<=",
+        _ => unreachable!("The parser only creates operations with operators!"),
+    }
+}
+
+/// Source of a format specifier, written after the colon of an interpolated value
+fn specifier(spec: &FormatSpec) -> String {
+    let mut text = String::new();
+    if let Some(alignment) = spec.alignment {
+        if spec.fill != ' ' { text.push(spec.fill) }
+        text.push(match alignment {
+            Alignment::Left => '<',
+            Alignment::Right => '>',
+            Alignment::Center => '^',
+        });
+    }
+    if spec.width > 0 { text.push_str(&spec.width.to_string()) }
+    if let Some(precision) = spec.precision { text.push_str(&format!(".{}", precision)) }
+
+    if text.is_empty() { text } else { format!(":{}", text) }
+}
@@ -2,10 +2,8 @@ pub mod token;
 
 use std::collections::HashMap;
 use std::fs;
-use std::iter::Peekable;
 use std::path::Path;
-use std::vec::IntoIter;
-use crate::lexer::token::Token;
+use crate::lexer::token::{Comment, FormatSegment, Span, Token};
 
 const SKIPPABLES: [char; 4] = ['\n', '\r', '\t', ' '];
 
@@ -26,50 +24,128 @@ pub fn keyword_map() -> HashMap<String, Token> {
         ("err".to_string(), Token::Error),
         ("null".to_string(), Token::NullType),
         ("array".to_string(), Token::ArrayType),
+        ("bigint".to_string(), Token::BigIntegerType),
+        ("decimal".to_string(), Token::DecimalType),
+        ("map".to_string(), Token::Map),
+        ("import".to_string(), Token::Import),
+        ("pub".to_string(), Token::Public),
     ])
 }
 
+/// Characters of a program which the lexer can look ahead at without consuming them
+pub struct Source {
+    chars: Vec<char>,
+    // Index of the next character
+    position: usize,
+}
+
+impl Source {
+    pub fn peek(&self) -> Option<&char> {
+        self.chars.get(self.position)
+    }
+
+    /// Character after the next one
+    pub fn peek_second(&self) -> Option<&char> {
+        self.chars.get(self.position + 1)
+    }
+}
+
+impl From<Vec<char>> for Source {
+    fn from(chars: Vec<char>) -> Self {
+        Self { chars, position: 0 }
+    }
+}
+
+impl Iterator for Source {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.chars.get(self.position).copied()?;
+        self.position += 1;
+        Some(char)
+    }
+}
+
 /// Parses a Program into the individual Token
 pub struct Lexer {
     // String representation of the program
-    pub program: Peekable<IntoIter<char>>,
+    pub program: Source,
 
     // Stores all the keywords
     pub keywords: HashMap<String, Token>,
+
+    // Position of the next character inside the program
+    line: usize,
+    column: usize,
+
+    // Comments are skipped like whitespace, but kept for tools which print the source again
+    comments: Vec<Comment>,
 }
 
 impl Lexer {
     pub fn from_string(program: String) -> Lexer {
         Self {
-            program: program.chars().collect::<Vec<char>>().into_iter().peekable(),
+            program: program.chars().collect::<Vec<char>>().into(),
             keywords: keyword_map(),
+            line: 1,
+            column: 1,
+            comments: vec![],
         }
     }
 
-    #[allow(unused)]
+    /// Starts counting lines and columns at the given position, used for sources nested inside other tokens
+    pub fn at(mut self, span: Span) -> Lexer {
+        self.line = span.line;
+        self.column = span.column;
+        self
+    }
+
     pub fn from_path<P: AsRef<Path>>(path: P) -> anyhow::Result<Lexer> {
         Ok(Self {
-            program: fs::read_to_string(path)?.chars().collect::<Vec<char>>().into_iter().peekable(),
+            program: fs::read_to_string(path)?.chars().collect::<Vec<char>>().into(),
             keywords: keyword_map(),
+            line: 1,
+            column: 1,
+            comments: vec![],
         })
     }
 
     pub fn lex(&mut self) -> Vec<Token> {
-        let mut tokens: Vec<Token> = vec![];
+        self.lex_spanned().into_iter().map(|(token, _)| token).collect()
+    }
+
+    /// Lexes the program and keeps the comments, which are left out of the tokens
+    pub fn lex_with_comments(&mut self) -> (Vec<(Token, Span)>, Vec<Comment>) {
+        let tokens = self.lex_spanned();
+        (tokens, std::mem::take(&mut self.comments))
+    }
 
-        while let Some(char) = self.program.next() {
+    /// Lexes the program and keeps the position at which every token starts
+    pub fn lex_spanned(&mut self) -> Vec<(Token, Span)> {
+        let mut tokens: Vec<(Token, Span)> = vec![];
+
+        loop {
+            let span = Span::new(self.line, self.column);
+            let Some(char) = self.next_char() else { break };
             if SKIPPABLES.contains(&char) { continue }
             let token = match char {
                 // Types
-                c if c.is_ascii_alphabetic() => self.parse_identifier(c),
-                c if c.is_digit(10) => self.parse_number(c),
-                c if c == '"' => self.parse_string(),
+                'f' if self.program.peek() == Some(&'"') => {
+                    self.next_char();
+                    self.parse_string(true)
+                }
+                c if c.is_ascii_alphabetic() || c == '_' => self.parse_identifier(c),
+                c if c.is_ascii_digit() => self.parse_number(c),
+                '"' => self.parse_string(false),
 
                 // Special characters
                 ';' => Token::Semicolon,
                 ':' => Token::Colon,
                 ',' => Token::Comma,
-                '.' => Token::Dot,
+                '.' => if let Some(&'.') = self.program.peek() {
+                    self.next_char();
+                    Token::DotDot
+                } else { Token::Dot },
 
                 // Brackets
                 '(' => Token::LParent,
@@ -81,53 +157,102 @@ impl Lexer {
 
                 // Operators
                 '=' => if let Some(&'=') = self.program.peek() {
-                    self.program.next();
+                    self.next_char();
                     Token::Equal
                 } else { Token::Assign },
                 '+' => Token::Add,
                 '-' => Token::Subtract,
-                '*' => Token::Multiply,
+                '*' => if let Some(&'*') = self.program.peek() {
+                    self.next_char();
+                    Token::Power
+                } else { Token::Multiply },
+                '/' if self.program.peek() == Some(&'/') => {
+                    let trailing = tokens.last().is_some_and(|(_, last)| last.line == span.line);
+                    self.parse_comment(span, trailing);
+                    continue
+                }
                 '/' => Token::Divide,
                 '%' => Token::Modular,
 
                 '!' => if let Some(&'=') = self.program.peek() {
-                    self.program.next();
+                    self.next_char();
                     Token::NotEqual
                 } else { Token::Invert },
                 '&' => if matches!(self.program.peek(), Some(&'&')) {
-                    self.program.next();
+                    self.next_char();
                     Token::And
                 } else {
                     Token::SingleAnd
                 }
-                '|' if matches!(self.program.peek(), Some(&'|')) => {
-                    self.program.next();
+                '|' => if matches!(self.program.peek(), Some(&'|')) {
+                    self.next_char();
                     Token::Or
+                } else {
+                    Token::BitOr
                 }
-                '<' => if let Some(&'=') = self.program.peek() {
-                    self.program.next();
-                    Token::LessThanEqual
-                } else { Token::LessThan },
-                '>' => if let Some(&'=') = self.program.peek() {
-                    self.program.next();
-                    Token::GreaterThanEqual
-                } else { Token::GreaterThan },
+                '^' => Token::BitXor,
+                '~' => Token::BitNot,
+                '<' => match self.program.peek() {
+                    Some(&'=') => {
+                        self.next_char();
+                        Token::LessThanEqual
+                    }
+                    Some(&'<') => {
+                        self.next_char();
+                        Token::ShiftLeft
+                    }
+                    _ => Token::LessThan,
+                },
+                '>' => match self.program.peek() {
+                    Some(&'=') => {
+                        self.next_char();
+                        Token::GreaterThanEqual
+                    }
+                    Some(&'>') => {
+                        self.next_char();
+                        Token::ShiftRight
+                    }
+                    _ => Token::GreaterThan,
+                },
 
                 _ => Token::Illegal,
             };
-            tokens.push(token);
+            tokens.push((token, span));
         }
 
         tokens
     }
 
+    /// Reads a comment up to the end of its line
+    fn parse_comment(&mut self, span: Span, trailing: bool) {
+        self.next_char();
+        let mut text = String::new();
+        while let Some(&c) = self.program.peek() {
+            if c == '\n' { break }
+            text.push(c);
+            self.next_char();
+        }
+        self.comments.push(Comment { text: text.trim_end().to_string(), span, trailing });
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let char = self.program.next()?;
+        if char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(char)
+    }
+
     pub fn parse_identifier(&mut self, c: char) -> Token {
         let mut identifier = String::from(c);
 
         while let Some(c) = self.program.peek() {
-            if !c.is_ascii_alphabetic() { break }
+            if !(c.is_ascii_alphanumeric() || c == &'_') { break }
             identifier.push(*c);
-            self.program.next();
+            self.next_char();
         }
 
         if let Some(keyword) = self.keywords.get(&identifier) {
@@ -140,10 +265,24 @@ impl Lexer {
     pub fn parse_number(&mut self, c: char) -> Token {
         let mut number = String::from(c);
 
-        while let Some(c) = self.program.peek() {
-            if !(c.is_digit(10) || c == &'.') { break }
-            number.push(*c);
-            self.program.next();
+        while let Some(&c) = self.program.peek() {
+            if !(c.is_ascii_digit() || c == '.') { break }
+            // A second dot starts a range like `1..3`
+            if c == '.' && self.program.peek_second() == Some(&'.') { break }
+            number.push(c);
+            self.next_char();
+        }
+
+        match self.program.peek() {
+            Some('n') if !number.contains('.') => {
+                self.next_char();
+                return Token::BigInteger(number)
+            }
+            Some('d') => {
+                self.next_char();
+                return Token::Decimal(number)
+            }
+            _ => {}
         }
 
         if number.contains(".") { return Token::Float(number) }
@@ -151,14 +290,73 @@ impl Lexer {
         Token::Integer(number)
     }
 
-    pub fn parse_string(&mut self) -> Token {
+    /// Interpolated strings get split into segments, `{{` and `}}` are escaped braces
+    pub fn parse_string(&mut self, formatted: bool) -> Token {
         let mut string = String::new();
+        let mut segments = vec![];
+        // Braces around the interpolated value and whether a string inside of it is open
+        let mut depth = 0;
+        let mut quoted = false;
+        let mut valid = true;
+
+        while let Some(c) = self.next_char() {
+            if !formatted {
+                if c == '"' { break }
+                string.push(c);
+                continue
+            }
 
-        while let Some(c) = self.program.next() {
-            if c == '"' { break }
+            match c {
+                '"' if depth == 0 => break,
+                '"' => quoted = !quoted,
+                '{' | '}' if depth == 0 && self.program.peek() == Some(&c) => {
+                    self.next_char();
+                }
+                '{' if depth == 0 => {
+                    if !string.is_empty() { segments.push(FormatSegment::Literal(std::mem::take(&mut string))) }
+                    depth = 1;
+                    continue
+                }
+                '}' if depth == 0 => valid = false,
+                '{' if !quoted => depth += 1,
+                '}' if !quoted => {
+                    depth -= 1;
+                    if depth == 0 {
+                        segments.push(format_value(std::mem::take(&mut string)));
+                        continue
+                    }
+                }
+                _ => {}
+            }
             string.push(c);
         }
 
-        Token::String(string)
+        if !formatted { return Token::String(string) }
+        if depth != 0 || !valid { return Token::InvalidFormatString }
+
+        if !string.is_empty() { segments.push(FormatSegment::Literal(string)) }
+        Token::FormatString(segments)
+    }
+}
+
+/// Splits an interpolated value at the last colon which is not nested inside of it
+fn format_value(value: String) -> FormatSegment {
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut colon = None;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' | '[' | '{' if !quoted => depth += 1,
+            ')' | ']' | '}' if !quoted => depth -= 1,
+            ':' if !quoted && depth == 0 => colon = Some(index),
+            _ => {}
+        }
+    }
+
+    match colon {
+        Some(index) => FormatSegment::Value { source: value[..index].to_string(), spec: value[index + 1..].to_string() },
+        None => FormatSegment::Value { source: value, spec: String::new() },
     }
 }
\ No newline at end of file
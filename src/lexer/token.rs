@@ -11,13 +11,22 @@ pub enum Token {
     Integer(String),
     Float(String),
     String(String),
+    // Interpolated string literal with the `f` prefix
+    FormatString(Vec<FormatSegment>),
+    // Interpolated string literal with an unterminated or unbalanced brace
+    InvalidFormatString,
     Boolean(String),
+    // Literals with the `n` suffix
+    BigInteger(String),
+    // Literals with the `d` suffix
+    Decimal(String),
 
     // Special characters
     Semicolon,
     Colon,
     Comma,
     Dot,
+    DotDot,
 
     // Brackets
     LParent,
@@ -34,8 +43,15 @@ pub enum Token {
     Multiply,
     Divide,
     Modular,
+    Power,
 
+    // Reference prefix and bitwise and infix
     SingleAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
 
     Invert,
     And,
@@ -61,14 +77,87 @@ pub enum Token {
     BooleanType,
     NullType,
     ArrayType,
+    BigIntegerType,
+    DecimalType,
+    // Keyword of map types, the parser turns `map<key, value>` into a `MapType`
+    Map,
+    MapType(Box<Token>, Box<Token>),
+    // Parsed from `(type, type, ...)`
+    TupleType(Vec<Token>),
 
     Return,
 
     Error,
+
+    // Modules
+    Import,
+    Public,
+}
+
+/// Part of an interpolated string literal
+#[derive(Eq, PartialEq)]
+#[derive(Clone)]
+#[derive(Debug)]
+pub enum FormatSegment {
+    Literal(String),
+    // Source of the interpolated expression and its format specifier, written as `{source:spec}`
+    Value { source: String, spec: String },
 }
 
 impl Token {
     pub fn equal_variant(&self, other: &Token) -> bool {
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
-}
\ No newline at end of file
+
+    /// Name of a type token like it is written in the source
+    pub fn type_name(&self) -> String {
+        match self {
+            Token::IntegerType => "int".to_string(),
+            Token::FloatType => "float".to_string(),
+            Token::StringType => "string".to_string(),
+            Token::BooleanType => "bool".to_string(),
+            Token::NullType => "null".to_string(),
+            Token::ArrayType => "array".to_string(),
+            Token::BigIntegerType => "bigint".to_string(),
+            Token::DecimalType => "decimal".to_string(),
+            Token::Map => "map".to_string(),
+            Token::MapType(key, value) => format!("map<{}, {}>", key.type_name(), value.type_name()),
+            Token::TupleType(types) => format!("({})", types.iter().map(Token::type_name).collect::<Vec<String>>().join(", ")),
+            token => format!("{:?}", token),
+        }
+    }
+}
+
+/// Comment which starts with `//` and runs up to the end of the line
+#[derive(Eq, PartialEq)]
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct Comment {
+    // Text after the slashes
+    pub text: String,
+    pub span: Span,
+    // Whether the comment follows a token on the same line
+    pub trailing: bool,
+}
+
+/// Position of a token inside the source of a program
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug, Default)]
+pub struct Span {
+    // Line and column start at one, zero means the position is unknown
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}", self.line)
+    }
+}
@@ -1,5 +1,14 @@
 pub mod lexer;
 pub mod error;
 pub mod parser;
+pub mod optimizer;
+pub mod resolver;
 pub mod evaluator;
+pub mod vm;
 pub mod repl;
+pub mod formatter;
+pub mod analysis;
+pub mod lsp;
+pub mod linter;
+pub mod debugger;
+pub mod dap;
@@ -0,0 +1,73 @@
+use std::collections::{HashMap, HashSet};
+use crate::lexer::token::{Comment, Span};
+use crate::linter::{Level, Lint};
+
+// Comments which configure the lints of the line they are written on, or the next line if they stand on their own
+const LINE_PREFIX: &str = "lint:";
+// Comments which configure the lints of the whole file
+const FILE_PREFIX: &str = "lint-file:";
+
+/// Levels which are set by the comments of a file, like `// lint: allow(unused-variable, L006)`
+#[derive(Debug, Default)]
+pub struct Directives {
+    pub file: HashMap<Lint, Level>,
+    pub lines: HashMap<usize, HashMap<Lint, Level>>,
+    // Positions of comments with levels or lints which do not exist, with the message they are reported with
+    pub unknown: Vec<(Span, String)>,
+}
+
+impl Directives {
+    pub fn parse(comments: &[Comment]) -> Self {
+        let mut directives = Directives::default();
+        // Lines which contain nothing but a comment, directives on them skip each other
+        let comment_lines = comments.iter()
+            .filter(|comment| !comment.trailing)
+            .map(|comment| comment.span.line)
+            .collect::<HashSet<usize>>();
+
+        for comment in comments {
+            let text = comment.text.trim();
+            if let Some(levels) = text.strip_prefix(FILE_PREFIX) {
+                let levels = directives.parse_levels(levels, comment.span);
+                directives.file.extend(levels);
+            } else if let Some(levels) = text.strip_prefix(LINE_PREFIX) {
+                let mut line = comment.span.line;
+                if !comment.trailing {
+                    line += 1;
+                    while comment_lines.contains(&line) { line += 1 }
+                }
+                let levels = directives.parse_levels(levels, comment.span);
+                directives.lines.entry(line).or_default().extend(levels);
+            }
+        }
+        directives
+    }
+
+    /// Level a comment sets for the lint on the line, a comment on the line wins over one for the file
+    pub fn level(&self, lint: Lint, line: usize) -> Option<Level> {
+        self.lines.get(&line).and_then(|levels| levels.get(&lint))
+            .or_else(|| self.file.get(&lint))
+            .copied()
+    }
+
+    /// Parses groups like `allow(a, b) deny(c)` of the comment at the span, unknown levels and lints are remembered and skipped
+    fn parse_levels(&mut self, text: &str, span: Span) -> Vec<(Lint, Level)> {
+        let mut levels = vec![];
+        let mut rest = text;
+        while let Some(open) = rest.find('(') {
+            let Some(close) = rest[open..].find(')').map(|close| open + close) else { break };
+            let name = rest[..open].trim();
+            match Level::parse(name) {
+                Some(level) => for name in rest[open + 1..close].split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                    match Lint::parse(name) {
+                        Some(lint) => levels.push((lint, level)),
+                        None => self.unknown.push((span, format!("Found unknown lint {} in a lint comment!", name))),
+                    }
+                }
+                None => self.unknown.push((span, format!("Found unknown lint level {} in a lint comment!", name))),
+            }
+            rest = &rest[close + 1..];
+        }
+        levels
+    }
+}
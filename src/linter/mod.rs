@@ -0,0 +1,420 @@
+pub mod directive;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{stdin, Read};
+use std::path::PathBuf;
+use std::vec::IntoIter;
+use colored::Colorize;
+use crate::analysis::{analyze, Analysis, SymbolKind};
+use crate::lexer::token::{Comment, Span, Token};
+use crate::lexer::Lexer;
+use crate::linter::directive::Directives;
+use crate::parser::ast::expression::Expression;
+use crate::parser::ast::statement::Statement;
+use crate::parser::layout::{Declaration, Layout, StatementLayout};
+use crate::parser::Parser;
+
+/// Code which runs, but most likely does not do what it was meant to
+#[derive(Eq, PartialEq, Hash)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum Lint {
+    UnusedVariable,
+    UnusedParameter,
+    ShadowedBinding,
+    UnreachableCode,
+    InfiniteLoop,
+    FloatEquality,
+    MissingReturn,
+    UnknownDirective,
+}
+
+// Lints with their code and name, codes stay the same once a lint is released
+pub const LINTS: [(Lint, &str, &str); 8] = [
+    (Lint::UnusedVariable, "L001", "unused-variable"),
+    (Lint::UnusedParameter, "L002", "unused-parameter"),
+    (Lint::ShadowedBinding, "L003", "shadowed-binding"),
+    (Lint::UnreachableCode, "L004", "unreachable-code"),
+    (Lint::InfiniteLoop, "L005", "infinite-loop"),
+    (Lint::FloatEquality, "L006", "float-equality"),
+    (Lint::MissingReturn, "L007", "missing-return"),
+    (Lint::UnknownDirective, "L008", "unknown-directive"),
+];
+
+impl Lint {
+    pub fn code(self) -> &'static str {
+        LINTS.iter().find(|(lint, _, _)| *lint == self).map(|(_, code, _)| *code).unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        LINTS.iter().find(|(lint, _, _)| *lint == self).map(|(_, _, name)| *name).unwrap()
+    }
+
+    /// Finds a lint by its code or its name
+    pub fn parse(text: &str) -> Option<Lint> {
+        LINTS.iter().find(|(_, code, name)| code.eq_ignore_ascii_case(text) || *name == text).map(|(lint, _, _)| *lint)
+    }
+}
+
+/// How a lint is reported, denied lints fail the `lint` subcommand
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub fn parse(text: &str) -> Option<Level> {
+        match text {
+            "allow" => Some(Level::Allow),
+            "warn" => Some(Level::Warn),
+            "deny" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// Levels of the lints before the comments of a file change them, every lint warns by default
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    levels: HashMap<Lint, Level>,
+}
+
+impl Config {
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        self.levels.get(&lint).copied().unwrap_or(Level::Warn)
+    }
+}
+
+#[derive(Debug)]
+pub struct Warning {
+    pub lint: Lint,
+    pub level: Level,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let level = if self.level == Level::Deny { "error" } else { "warning" };
+        write!(f, "{}[{} {}]: {}", level, self.lint.code(), self.lint.name(), self.message)
+    }
+}
+
+/// Settings of the `lint` subcommand
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct LintArguments {
+    /// Codes or names of lints which are not reported
+    #[arg(long)]
+    pub allow: Vec<String>,
+    /// Codes or names of lints which are reported as warnings
+    #[arg(long)]
+    pub warn: Vec<String>,
+    /// Codes or names of lints which are reported as errors
+    #[arg(long)]
+    pub deny: Vec<String>,
+    /// Files which get linted, the standard input is linted without them
+    pub paths: Vec<PathBuf>,
+}
+
+/// Lints the files of the arguments and returns whether all of them could be parsed without denied lints
+pub fn run(arguments: &LintArguments) -> bool {
+    let mut config = Config::default();
+    for (names, level) in [(&arguments.allow, Level::Allow), (&arguments.warn, Level::Warn), (&arguments.deny, Level::Deny)] {
+        for name in names {
+            match Lint::parse(name) {
+                Some(lint) => config.set(lint, level),
+                None => {
+                    eprintln!("{}", format!("Found unknown lint {}!", name).bright_red());
+                    return false
+                }
+            }
+        }
+    }
+
+    let sources = if arguments.paths.is_empty() {
+        let mut source = String::new();
+        vec![("<stdin>".to_string(), stdin().read_to_string(&mut source).map(|_| source))]
+    } else {
+        arguments.paths.iter().map(|path| (path.display().to_string(), fs::read_to_string(path))).collect()
+    };
+
+    let mut success = true;
+    for (path, source) in sources {
+        match source.map_err(anyhow::Error::from).and_then(|source| lint(&source, &config)) {
+            Ok(warnings) => for warning in warnings {
+                let location = format!("{}:{}:{}", path, warning.span.line, warning.span.column);
+                match warning.level {
+                    Level::Deny => {
+                        println!("{}: {}", location, warning.to_string().bright_red());
+                        success = false;
+                    }
+                    _ => println!("{}: {}", location, warning.to_string().bright_yellow()),
+                }
+            }
+            Err(err) => {
+                eprintln!("{}", format!("Cannot lint {}: {:#}", path, err).bright_red());
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Parses the source and returns the lints it triggers, in the order of the source
+pub fn lint(source: &str, config: &Config) -> anyhow::Result<Vec<Warning>> {
+    let (tokens, comments) = Lexer::from_string(source.to_string()).lex_with_comments();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let statements = parser.parse()?;
+    let analysis = analyze(&statements, parser.layout().clone());
+    Ok(check(&statements, parser.layout().clone(), &analysis, &comments, config))
+}
+
+/// Checks a parsed program, the layout and analysis have to belong to its statements.
+/// Comments of the source may change the level of the lints for their line or the whole file.
+pub fn check(statements: &[Statement], layout: Layout, analysis: &Analysis, comments: &[Comment], config: &Config) -> Vec<Warning> {
+    let mut linter = Linter {
+        analysis,
+        statements: layout.statements.into_iter(),
+        declarations: layout.declarations.into_iter(),
+        span: Span::default(),
+        found: vec![],
+    };
+    linter.block(statements.iter());
+    linter.symbols();
+
+    let directives = Directives::parse(comments);
+    let unknown = directives.unknown.iter().map(|(span, message)| (Lint::UnknownDirective, *span, message.clone()));
+    let mut warnings = linter.found.into_iter()
+        .chain(unknown)
+        .filter_map(|(lint, span, message)| {
+            let level = directives.level(lint, span.line).unwrap_or(config.level(lint));
+            (level != Level::Allow).then_some(Warning { lint, level, span, message })
+        })
+        .collect::<Vec<Warning>>();
+    warnings.sort_by_key(|warning| (warning.span.line, warning.span.column));
+    warnings
+}
+
+struct Linter<'a> {
+    analysis: &'a Analysis,
+    // Positions of the parser, consumed in the order the statements are visited in
+    statements: IntoIter<StatementLayout>,
+    declarations: IntoIter<Declaration>,
+    // Position of the statement which is being checked
+    span: Span,
+    found: Vec<(Lint, Span, String)>,
+}
+
+impl Linter<'_> {
+    /// Checks the statements of a block or the program, only the first statement after a return is reported
+    fn block<'s>(&mut self, statements: impl Iterator<Item = &'s Statement>) {
+        let mut returned = false;
+        let mut reported = false;
+        for statement in statements {
+            let span = self.statement(statement);
+            if returned && !reported {
+                self.found.push((Lint::UnreachableCode, span, "Statement is never reached, every path before it returns!".to_string()));
+                reported = true;
+            }
+            returned |= always_returns(statement);
+        }
+    }
+
+    /// Checks the statement and returns its position
+    fn statement(&mut self, statement: &Statement) -> Span {
+        let span = self.statements.next().map(|layout| layout.span).unwrap_or_default();
+        let outer = std::mem::replace(&mut self.span, span);
+
+        match statement {
+            Statement::Let { value, .. } => {
+                self.declaration();
+                self.expression(value);
+            }
+            Statement::Destructure { pattern, value, .. } => {
+                for _ in pattern.names() {
+                    self.declaration();
+                }
+                self.expression(value);
+            }
+            Statement::Return { value } |
+            Statement::Expression { value } => self.expression(value),
+            Statement::Function { name, parameter, typee, body } => {
+                let name_span = self.declaration();
+                for _ in parameter {
+                    self.declaration();
+                }
+                self.expression(body);
+
+                let Expression::Block { statements } = body.as_ref() else { unreachable!() };
+                if !typee.equal_variant(&Token::NullType) && !block_returns(statements) {
+                    self.found.push((Lint::MissingReturn, name_span, format!("Function {} does not return a value on every path!", name)));
+                }
+            }
+            Statement::Import { names, .. } => {
+                for _ in names.iter().flatten() {
+                    self.declaration();
+                }
+            }
+            Statement::Export { statement } => {
+                self.statement(statement);
+            }
+        }
+
+        self.span = outer;
+        span
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Infix { left, operation, right } => {
+                if matches!(operation, Token::Equal | Token::NotEqual) && (self.is_float(left) || self.is_float(right)) {
+                    let operator = if operation == &Token::Equal { "==" } else { "!=" };
+                    let span = position(expression).unwrap_or(self.span);
+                    self.found.push((Lint::FloatEquality, span, format!("Floats are compared with {}, which is unreliable because of rounding!", operator)));
+                }
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::While { condition, consequence } => {
+                if matches!(condition.as_ref(), Expression::Boolean { value } if value == "true") && !contains_return(consequence) {
+                    self.found.push((Lint::InfiniteLoop, self.span, "Loop never ends, its condition is always true and it does not return!".to_string()));
+                }
+                self.expression(condition);
+                self.expression(consequence);
+            }
+            Expression::Block { statements } => self.block(statements.iter().map(Box::as_ref)),
+            Expression::Format { .. } => {
+                // Interpolated values are parsed on their own, so they take no positions out of the layout
+                let statements = std::mem::take(&mut self.statements);
+                let declarations = std::mem::take(&mut self.declarations);
+                for child in expression.children() {
+                    self.expression(child);
+                }
+                self.statements = statements;
+                self.declarations = declarations;
+            }
+            expression => {
+                for child in expression.children() {
+                    self.expression(child);
+                }
+            }
+        }
+    }
+
+    /// Position of the next declared name, in the order the parser found them
+    fn declaration(&mut self) -> Span {
+        self.declarations.next().map(|declaration| declaration.span).unwrap_or_default()
+    }
+
+    /// Whether the value of the expression is known to be a float before the program runs
+    fn is_float(&self, expression: &Expression) -> bool {
+        let typee = |span: Span| self.analysis.symbol_at(span).and_then(|symbol| symbol.typee.as_ref());
+        match expression {
+            Expression::Float { .. } => true,
+            Expression::Identifier { span, .. } => typee(*span) == Some(&Token::FloatType),
+            Expression::Call { name, span, .. } => name == "to_float" || typee(*span) == Some(&Token::FloatType),
+            Expression::Prefix { prefix: Token::Subtract, value } => self.is_float(value),
+            Expression::Infix { left, operation: Token::Add | Token::Subtract | Token::Multiply | Token::Divide | Token::Modular | Token::Power, right } => {
+                self.is_float(left) || self.is_float(right)
+            }
+            _ => false,
+        }
+    }
+
+    /// Finds the variables and parameters which are never used or hide another binding
+    fn symbols(&mut self) {
+        let used = self.analysis.references.iter().filter_map(|reference| reference.symbol).collect::<HashSet<usize>>();
+
+        for (index, symbol) in self.analysis.symbols.iter().enumerate() {
+            let unused = match symbol.kind {
+                SymbolKind::Variable => Some((Lint::UnusedVariable, "Variable")),
+                SymbolKind::Parameter => Some((Lint::UnusedParameter, "Parameter")),
+                SymbolKind::Function | SymbolKind::Import => None,
+            };
+            // Names starting with an underscore are unused on purpose
+            if let Some((lint, kind)) = unused.filter(|_| !used.contains(&index) && !symbol.exported && !symbol.name.starts_with('_')) {
+                self.found.push((lint, symbol.span, format!("{} {} is never used!", kind, symbol.name)));
+            }
+
+            if let Some(shadowed) = symbol.shadows {
+                let line = self.analysis.symbols[shadowed].span.line;
+                self.found.push((Lint::ShadowedBinding, symbol.span, format!("{} shadows the binding declared at line {}!", symbol.name, line)));
+            }
+        }
+    }
+}
+
+/// First position inside the expression, literals have none
+fn position(expression: &Expression) -> Option<Span> {
+    match expression {
+        Expression::Identifier { span, .. } |
+        Expression::Call { span, .. } => Some(*span),
+        expression => expression.children().into_iter().find_map(position),
+    }
+}
+
+/// Whether a return is evaluated somewhere inside the expression, functions declared inside it do not count
+fn contains_return(expression: &Expression) -> bool {
+    match expression {
+        Expression::Block { statements } => statements.iter().any(|statement| match statement.as_ref() {
+            Statement::Return { .. } => true,
+            Statement::Let { value, .. } |
+            Statement::Destructure { value, .. } |
+            Statement::Expression { value } => contains_return(value),
+            Statement::Function { .. } |
+            Statement::Import { .. } |
+            Statement::Export { .. } => false,
+        }),
+        expression => expression.children().into_iter().any(contains_return),
+    }
+}
+
+/// Whether the statement returns on every path, so the statements after it never run
+fn always_returns(statement: &Statement) -> bool {
+    match statement {
+        Statement::Return { .. } => true,
+        Statement::Expression { value } => match value.as_ref() {
+            Expression::If { consequence, alternative: Some(alternative), .. } => {
+                [consequence, alternative].iter().all(|block| match block.as_ref() {
+                    Expression::Block { statements } => statements.iter().any(|statement| always_returns(statement)),
+                    _ => false,
+                })
+            }
+            _ => false,
+        }
+        _ => false,
+    }
+}
+
+/// Whether a function body produces a value on every path, by a return or by the value of its last statement
+fn block_returns(statements: &[Box<Statement>]) -> bool {
+    if statements.iter().any(|statement| always_returns(statement)) { return true }
+
+    match statements.last().map(Box::as_ref) {
+        Some(Statement::Expression { value }) => match value.as_ref() {
+            Expression::If { consequence, alternative: Some(alternative), .. } => {
+                [consequence, alternative].iter().all(|block| match block.as_ref() {
+                    Expression::Block { statements } => block_returns(statements),
+                    _ => false,
+                })
+            }
+            // Null is the value when the condition does not hold
+            Expression::If { alternative: None, .. } => false,
+            // Loops which never end cannot reach the end of the function
+            Expression::While { condition, .. } => matches!(condition.as_ref(), Expression::Boolean { value } if value == "true"),
+            _ => true,
+        }
+        Some(Statement::Let { .. } | Statement::Destructure { .. } | Statement::Return { .. }) => true,
+        Some(Statement::Function { .. } | Statement::Import { .. } | Statement::Export { .. }) | None => false,
+    }
+}
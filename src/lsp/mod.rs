@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use anyhow::Context;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics};
+use lsp_types::request::{Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location, NumberOrString, MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities, SymbolKind as LspSymbolKind, TextDocumentSyncCapability, TextDocumentSyncKind, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use crate::analysis::{analyze, Analysis, Symbol, SymbolKind};
+use crate::error::ResolveError;
+use crate::evaluator::builtins::builtins;
+use crate::lexer::keyword_map;
+use crate::lexer::token::Span;
+use crate::lexer::Lexer;
+use crate::linter::{self, Config, Level};
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+
+// Name diagnostics are published under
+const SOURCE: &str = "p_lang";
+
+/// Last version of an open file which could be parsed with its analysis, the spans of the analysis belong to this text
+struct Document {
+    text: String,
+    analysis: Analysis,
+}
+
+/// Answers the requests of an editor about the open `.p` files
+struct Server<'a> {
+    connection: &'a Connection,
+    documents: HashMap<Url, Document>,
+}
+
+/// Runs the language server over the standard input and output until the editor shuts it down
+pub fn run() -> anyhow::Result<()> {
+    let (connection, threads) = Connection::stdio();
+    serve(&connection)?;
+    drop(connection);
+    threads.join().context("Cannot close the connection to the editor")
+}
+
+/// Initializes the connection and handles its messages until the editor shuts the server down
+pub fn serve(connection: &Connection) -> anyhow::Result<()> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server { connection, documents: HashMap::new() };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? { return Ok(()) }
+                let response = server.request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                let method = notification.method.clone();
+                // A malformed notification only loses its own changes
+                if let Err(err) = server.notification(notification) {
+                    eprintln!("Cannot handle the notification {}: {:#}", method, err);
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+impl Server<'_> {
+    fn request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => respond(request, |params| self.hover(params)),
+            GotoDefinition::METHOD => respond(request, |params| self.definition(params)),
+            DocumentSymbolRequest::METHOD => respond(request, |params| self.symbols(params)),
+            Completion::METHOD => respond(request, |params| self.complete(params)),
+            method => return Response::new_err(id, ErrorCode::MethodNotFound as i32, format!("Unknown request {}", method)),
+        };
+
+        match result {
+            Ok(result) => Response { id, result: Some(result), error: None },
+            Err(err) => Response::new_err(id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+
+    fn notification(&mut self, notification: Notification) -> anyhow::Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+                self.update(params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+                // The whole text is sent with every change
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(params.text_document.uri, change.text),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                self.publish(params.text_document.uri, vec![])
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Analyzes the new text of a document and publishes its diagnostics
+    fn update(&mut self, uri: Url, text: String) -> anyhow::Result<()> {
+        let directory = uri.to_file_path().ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+        let (analysis, diagnostics) = check(&text, &directory);
+
+        // Text which cannot be parsed keeps the previous version with its analysis
+        if let Some(analysis) = analysis {
+            self.documents.insert(uri.clone(), Document { text, analysis });
+        }
+        self.publish(uri, diagnostics)
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> anyhow::Result<()> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+        self.connection.sender.send(Message::Notification(Notification::new(PublishDiagnostics::METHOD.to_string(), params)))?;
+        Ok(())
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let Document { text, analysis } = self.documents.get(&position.text_document.uri)?;
+        let symbol = analysis.symbol_at(span(text, position.position))?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```p\n{}\n```", symbol),
+            }),
+            range: None,
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let Document { text, analysis } = self.documents.get(&position.text_document.uri)?;
+        let symbol = analysis.symbol_at(span(text, position.position))?;
+
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: position.text_document.uri,
+            range: range(text, symbol.span),
+        }))
+    }
+
+    fn symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let Document { text, analysis } = self.documents.get(&params.text_document.uri)?;
+        Some(DocumentSymbolResponse::Nested(document_symbols(text, analysis, None)))
+    }
+
+    fn complete(&self, params: CompletionParams) -> Option<Vec<CompletionItem>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let mut items = keyword_map().into_keys()
+            .map(|keyword| item(keyword, CompletionItemKind::KEYWORD, None))
+            .chain(builtins().map(|builtin| item(builtin.name.to_string(), CompletionItemKind::FUNCTION, Some(format!("builtin {}", builtin.name)))))
+            .collect::<Vec<CompletionItem>>();
+
+        if let Some(Document { text, analysis }) = self.documents.get(uri) {
+            for symbol in analysis.visible(span(text, params.text_document_position.position)) {
+                let kind = match symbol.kind {
+                    SymbolKind::Function => CompletionItemKind::FUNCTION,
+                    SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::Import => CompletionItemKind::VARIABLE,
+                };
+                items.push(item(symbol.name.clone(), kind, Some(symbol.to_string())));
+            }
+        }
+        items.sort_by(|a, b| a.label.cmp(&b.label));
+        Some(items)
+    }
+}
+
+/// Parses, analyzes, lints and resolves the text of a document. The analysis is missing if the text cannot be parsed.
+fn check(text: &str, directory: &Path) -> (Option<Analysis>, Vec<lsp_types::Diagnostic>) {
+    let (tokens, comments) = Lexer::from_string(text.to_string()).lex_with_comments();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let mut statements = match parser.parse() {
+        Ok(statements) => statements,
+        Err(err) => return (None, vec![diagnostic(text, parser.span(), format!("{:#}", err))]),
+    };
+
+    let analysis = analyze(&statements, parser.layout().clone());
+    let mut diagnostics = analysis.diagnostics.iter()
+        .map(|diagnostic| self::diagnostic(text, diagnostic.span, diagnostic.error.to_string()))
+        .collect::<Vec<lsp_types::Diagnostic>>();
+
+    for warning in linter::check(&statements, parser.layout().clone(), &analysis, &comments, &Config::default()) {
+        let severity = if warning.level == Level::Deny { DiagnosticSeverity::ERROR } else { DiagnosticSeverity::WARNING };
+        diagnostics.push(lsp_types::Diagnostic {
+            severity: Some(severity),
+            code: Some(NumberOrString::String(warning.lint.code().to_string())),
+            ..diagnostic(text, warning.span, warning.message)
+        });
+    }
+
+    if let Err(err) = Resolver::default().in_directory(directory).resolve(&mut statements) {
+        let span = err.downcast_ref::<ResolveError>().and_then(ResolveError::span).unwrap_or_default();
+        diagnostics.push(diagnostic(text, span, format!("{:#}", err)));
+    }
+    (Some(analysis), diagnostics)
+}
+
+fn diagnostic(text: &str, span: Span, message: String) -> lsp_types::Diagnostic {
+    lsp_types::Diagnostic {
+        range: range(text, span),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+/// Symbols of the functions and variables declared inside the parent, with the ones declared inside them as children
+#[allow(deprecated)]
+fn document_symbols(text: &str, analysis: &Analysis, parent: Option<usize>) -> Vec<DocumentSymbol> {
+    analysis.children(parent)
+        .filter(|(_, symbol)| matches!(symbol.kind, SymbolKind::Function | SymbolKind::Variable))
+        .map(|(index, symbol)| {
+            let (kind, children) = match symbol.kind {
+                SymbolKind::Function => (LspSymbolKind::FUNCTION, Some(document_symbols(text, analysis, Some(index)))),
+                _ => (LspSymbolKind::VARIABLE, None),
+            };
+            DocumentSymbol {
+                name: symbol.name.clone(),
+                detail: Some(symbol.to_string()),
+                kind,
+                tags: None,
+                deprecated: None,
+                range: lines(text, symbol),
+                selection_range: range(text, symbol.span),
+                children,
+            }
+        })
+        .collect()
+}
+
+fn item(label: String, kind: CompletionItemKind, detail: Option<String>) -> CompletionItem {
+    CompletionItem { label, kind: Some(kind), detail, ..CompletionItem::default() }
+}
+
+/// Handles the parameters of the request and returns the result, missing results are sent as null
+fn respond<P: DeserializeOwned, R: Serialize>(request: Request, handle: impl FnOnce(P) -> R) -> anyhow::Result<Value> {
+    let params = serde_json::from_value::<P>(request.params)?;
+    Ok(serde_json::to_value(handle(params))?)
+}
+
+/// Spans count lines and characters from one, positions of the protocol count lines from zero and columns in UTF-16 code units
+fn span(text: &str, position: Position) -> Span {
+    let column = match text.lines().nth(position.line as usize) {
+        Some(line) => {
+            let mut units = 0;
+            line.chars()
+                .take_while(|char| {
+                    units += char.len_utf16();
+                    units <= position.character as usize
+                })
+                .count()
+        }
+        None => position.character as usize,
+    };
+    Span::new(position.line as usize + 1, column + 1)
+}
+
+fn position(text: &str, span: Span) -> Position {
+    let line = span.line.saturating_sub(1);
+    let column = span.column.saturating_sub(1);
+    let character = text.lines().nth(line)
+        .map_or(column, |text| text.chars().take(column).map(char::len_utf16).sum());
+    Position::new(line as u32, character as u32)
+}
+
+/// Range of the word which starts at the span, or of the single character at it
+fn range(text: &str, span: Span) -> Range {
+    let length = text.lines().nth(span.line.saturating_sub(1))
+        .map(|line| {
+            let word = line.chars().skip(span.column.saturating_sub(1))
+                .take_while(|char| char.is_ascii_alphanumeric() || *char == '_' || *char == '.')
+                .count();
+            word.max(1)
+        })
+        .unwrap_or(0);
+    Range::new(position(text, span), position(text, Span::new(span.line, span.column + length)))
+}
+
+/// Range of the whole lines of the statement which declares the symbol
+fn lines(text: &str, symbol: &Symbol) -> Range {
+    let end = symbol.lines.end.max(symbol.span.line).saturating_sub(1);
+    let length = text.lines().nth(end).map_or(0, |line| line.encode_utf16().count());
+    Range::new(Position::new(symbol.lines.start.saturating_sub(1) as u32, 0), Position::new(end as u32, length as u32))
+}
@@ -1,11 +1,60 @@
-use crate::repl::run_repl;
+use std::process::ExitCode;
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use interpreter::debugger::DebugArguments;
+use interpreter::formatter::FormatArguments;
+use interpreter::linter::LintArguments;
+use interpreter::repl::{run_repl, Options};
 
-mod lexer;
-mod parser;
-mod evaluator;
-mod error;
-mod repl;
+#[derive(Parser, Debug)]
+struct Arguments {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(short='p')]
+    path: Option<String>,
+    #[command(flatten)]
+    options: Options,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Formats programs into their canonical style
+    #[command(alias = "pfmt")]
+    Fmt(FormatArguments),
+    /// Reports code which most likely does not do what it was meant to
+    Lint(LintArguments),
+    /// Runs a program step by step with commands from the standard input
+    Debug(DebugArguments),
+    /// Runs the language server over the standard input and output
+    Lsp,
+    /// Runs the debug adapter over the standard input and output
+    Dap,
+}
+
+fn main() -> ExitCode {
+    let arguments = Arguments::parse();
 
-fn main() {
-    run_repl();
+    match arguments.command {
+        Some(Command::Fmt(arguments)) => if interpreter::formatter::run(&arguments) { ExitCode::SUCCESS } else { ExitCode::FAILURE },
+        Some(Command::Lint(arguments)) => if interpreter::linter::run(&arguments) { ExitCode::SUCCESS } else { ExitCode::FAILURE },
+        Some(Command::Debug(arguments)) => if interpreter::debugger::run(&arguments) { ExitCode::SUCCESS } else { ExitCode::FAILURE },
+        Some(Command::Lsp) => match interpreter::lsp::run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{}", format!("{:#}", err).bright_red());
+                ExitCode::FAILURE
+            }
+        },
+        Some(Command::Dap) => match interpreter::dap::run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{}", format!("{:#}", err).bright_red());
+                ExitCode::FAILURE
+            }
+        },
+        None => {
+            run_repl(arguments.path, arguments.options);
+            ExitCode::SUCCESS
+        }
+    }
 }
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+use crate::evaluator::environment::Environment;
+use crate::evaluator::object::{Object, OwnerShip};
+use crate::lexer::token::Token;
+use crate::parser::ast::expression::{evaluate_infix_expression, evaluate_literal, evaluate_prefix_expression, short_circuit, Expression, FormatPart};
+use crate::parser::ast::statement::Statement;
+
+/// Rewrites a program into an equivalent one which does less work at runtime.
+/// Runs before the resolver, since it changes the scopes of the program.
+#[derive(Default)]
+pub struct Optimizer {
+    // Folded values are instances, so this environment is never looked into
+    environment: Environment,
+}
+
+impl Optimizer {
+    pub fn optimize(&self, statements: Vec<Statement>) -> Vec<Statement> {
+        statements.into_iter().map(|statement| self.statement(statement)).collect()
+    }
+
+    fn statement(&self, statement: Statement) -> Statement {
+        match statement {
+            Statement::Let { name, typee, value } => Statement::Let { name, typee, value: self.boxed(*value) },
+            Statement::Return { value } => Statement::Return { value: self.boxed(*value) },
+            Statement::Expression { value } => Statement::Expression { value: self.boxed(*value) },
+            Statement::Function { name, parameter, typee, body } => Statement::Function { name, parameter, typee, body: Arc::new(self.expression(Arc::unwrap_or_clone(body))) },
+            Statement::Destructure { pattern, typee, value } => Statement::Destructure { pattern, typee, value: self.boxed(*value) },
+            // Modules get optimized when the resolver loads them, see `Resolver::optimized`
+            Statement::Import { .. } => statement,
+            Statement::Export { statement } => Statement::Export { statement: Box::new(self.statement(*statement)) },
+        }
+    }
+
+    fn boxed(&self, expression: Expression) -> Box<Expression> {
+        Box::new(self.expression(expression))
+    }
+
+    fn expression(&self, expression: Expression) -> Expression {
+        match expression {
+            Expression::Prefix { prefix, value } => {
+                let value = self.boxed(*value);
+                if prefix != Token::SingleAnd {
+                    if let Some(object) = constant(&value) {
+                        if let Ok(OwnerShip::Instance(object)) = evaluate_prefix_expression(&prefix, OwnerShip::Instance(object), &self.environment) {
+                            if let Some(folded) = literal(object) { return folded }
+                        }
+                    }
+                }
+                Expression::Prefix { prefix, value }
+            }
+            Expression::Infix { left, operation, right } => {
+                let left = self.boxed(*left);
+                let right = self.boxed(*right);
+                if let Some(left_obj) = constant(&left) {
+                    if let Ok(Some(object)) = short_circuit(&left_obj, &operation) {
+                        if let Some(folded) = literal(object) { return folded }
+                    }
+                }
+                // Folding fails for operations which fail at runtime, they keep failing there
+                if let (Some(left_obj), Some(right_obj)) = (constant(&left), constant(&right)) {
+                    if let Ok(object) = evaluate_infix_expression(left_obj, right_obj, &operation) {
+                        if let Some(folded) = literal(object) { return folded }
+                    }
+                }
+                Expression::Infix { left, operation, right }
+            }
+            Expression::If { condition, consequence, alternative } => {
+                let condition = self.condition(*condition);
+                let consequence = self.boxed(*consequence);
+                let alternative = alternative.map(|alternative| self.boxed(*alternative));
+
+                match constant(&condition) {
+                    Some(Object::Boolean(true)) => branch(*consequence),
+                    Some(Object::Boolean(false)) => match alternative {
+                        Some(alternative) => branch(*alternative),
+                        None => Expression::Block { statements: vec![] },
+                    }
+                    _ => Expression::If { condition: Box::new(condition), consequence, alternative },
+                }
+            }
+            Expression::While { condition, consequence } => {
+                let condition = self.condition(*condition);
+                match constant(&condition) {
+                    Some(Object::Boolean(false)) => Expression::Block { statements: vec![] },
+                    _ => Expression::While { condition: Box::new(condition), consequence: self.boxed(*consequence) },
+                }
+            }
+            Expression::Call { name, arguments, span, slot } => Expression::Call {
+                name,
+                arguments: arguments.into_iter().map(|argument| self.boxed(*argument)).collect(),
+                span,
+                slot,
+            },
+            Expression::Error { value } => Expression::Error { value: self.boxed(*value) },
+            Expression::Assign { assign_to, value } => Expression::Assign { assign_to: self.boxed(*assign_to), value: self.boxed(*value) },
+            Expression::Array { values } => Expression::Array { values: values.into_iter().map(|value| self.boxed(*value)).collect() },
+            Expression::Tuple { values } => Expression::Tuple { values: values.into_iter().map(|value| self.boxed(*value)).collect() },
+            Expression::Map { entries } => Expression::Map {
+                entries: entries.into_iter().map(|(key, value)| (self.boxed(*key), self.boxed(*value))).collect(),
+            },
+            Expression::Block { statements } => {
+                // Statements after a `ret` can never be reached
+                let mut optimized = vec![];
+                for statement in statements {
+                    let statement = self.statement(*statement);
+                    let returns = matches!(statement, Statement::Return { .. });
+                    optimized.push(Box::new(statement));
+                    if returns { break }
+                }
+                Expression::Block { statements: optimized }
+            }
+            Expression::Access { source, index } => Expression::Access { source: self.boxed(*source), index: self.boxed(*index) },
+            Expression::Format { parts } => Expression::Format {
+                parts: parts.into_iter().map(|part| match part {
+                    FormatPart::Value { value, spec } => FormatPart::Value { value: self.boxed(*value), spec },
+                    literal => literal,
+                }).collect(),
+            },
+            Expression::Slice { source, start, end } => Expression::Slice {
+                source: self.boxed(*source),
+                start: start.map(|start| self.boxed(*start)),
+                end: end.map(|end| self.boxed(*end)),
+            },
+            expression => expression,
+        }
+    }
+
+    /// Conditions have to be booleans anyway, so comparisons with boolean constants can be dropped.
+    /// Operands which are no booleans still fail, just with the error of the condition.
+    fn condition(&self, condition: Expression) -> Expression {
+        match self.expression(condition) {
+            Expression::Infix { left, operation, right } => {
+                let (boolean, operand) = match (constant(&left), constant(&right)) {
+                    (Some(Object::Boolean(boolean)), None) => (boolean, right.clone()),
+                    (None, Some(Object::Boolean(boolean))) => (boolean, left.clone()),
+                    _ => return Expression::Infix { left, operation, right },
+                };
+
+                match (&operation, boolean) {
+                    (Token::Equal, true) | (Token::NotEqual, false) | (Token::And, true) | (Token::Or, false) => self.condition(*operand),
+                    (Token::Equal, false) | (Token::NotEqual, true) => self.condition(Expression::Prefix { prefix: Token::Invert, value: operand }),
+                    _ => Expression::Infix { left, operation, right },
+                }
+            }
+            Expression::Prefix { prefix: Token::Invert, value } => match *value {
+                Expression::Prefix { prefix: Token::Invert, value } => self.condition(*value),
+                value => Expression::Prefix { prefix: Token::Invert, value: Box::new(value) },
+            }
+            condition => condition,
+        }
+    }
+}
+
+/// Value of a literal expression
+fn constant(expression: &Expression) -> Option<Object> {
+    match expression {
+        Expression::Integer { .. } |
+        Expression::Float { .. } |
+        Expression::String { .. } |
+        Expression::Boolean { .. } |
+        Expression::BigInteger { .. } |
+        Expression::Decimal { .. } => evaluate_literal(expression).ok(),
+        _ => None,
+    }
+}
+
+/// Literal expression which evaluates to the object
+fn literal(object: Object) -> Option<Expression> {
+    match object {
+        Object::Integer(value) => Some(Expression::Integer { value: value.to_string() }),
+        Object::Float(value) => Some(Expression::Float { value: value.to_string() }),
+        Object::String(value) => Some(Expression::String { value }),
+        Object::Boolean(value) => Some(Expression::Boolean { value: value.to_string() }),
+        Object::BigInteger(value) => Some(Expression::BigInteger { value: value.to_string() }),
+        Object::Decimal(value) => Some(Expression::Decimal { value: value.to_string() }),
+        _ => None,
+    }
+}
+
+/// Branch of an `if` whose condition is constant. The branch keeps its own scope if it declares variables.
+fn branch(branch: Expression) -> Expression {
+    match branch {
+        Expression::Block { statements } if !statements.iter().any(|statement| matches!(statement.as_ref(), Statement::Let { .. } | Statement::Function { .. } | Statement::Destructure { .. })) => {
+            Expression::Block { statements }
+        }
+        branch => Expression::If {
+            condition: Box::new(Expression::Boolean { value: "true".to_string() }),
+            consequence: Box::new(branch),
+            alternative: None,
+        },
+    }
+}
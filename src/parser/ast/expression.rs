@@ -1,18 +1,29 @@
-use std::ops::Deref;
 use anyhow::bail;
-use clap::arg;
-use crate::error::EvalError::{CannotAccessArrayWith, CannotApplyOn, CannotArrayAccess, CannotAssignDifferentType, CannotAssignTo, CannotCall, CannotConvertInto, ExpectedTypeButFound, IllegalPrefixOperation, IncompatibleTypes, IndexOutOfRange, InvalidAmountOfArguments, InvalidTypeButFound, UnknownIdentifier};
-use crate::evaluator::environment::Environment;
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+use crate::error::EvalError::{CannotAccessArrayWith, CannotApplyOn, CannotArrayAccess, CannotAssignDifferentType, CannotAssignTo, CannotCall, CannotConvertInto, CannotSlice, DivisionByZero, ExpectedTypeButFound, IllegalPrefixOperation, IncompatibleTypes, IndexOutOfRange, IntegerOverflow, InvalidAmountOfArguments, InvalidTypeButFound, InvalidMapKey, KeyNotFound, ModuloByZero, NegationOverflow, NegativeExponent, ResultTooLarge, ShiftOutOfRange, SliceOutOfRange, UnknownIdentifier};
+use crate::error::StackTrace;
+use crate::evaluator::builtins::builtin;
+use crate::evaluator::environment::{Address, Environment, Frame, Location, Variable};
 use crate::evaluator::evaluate_block;
-use crate::evaluator::object::{Object, OwnerShip};
-use crate::lexer::token::Token;
+use crate::evaluator::format::FormatSpec;
+use crate::evaluator::object::{Function, Key, Object, OwnerShip};
+use crate::lexer::token::{Span, Token};
 use crate::parser::ast::statement::Statement;
+use crate::resolver::Slot;
+
+// Amount of bits a big integer or the digits of a decimal may grow to with a single shift or power
+pub const MAX_BIG_BITS: u64 = 1 << 20;
 
 #[derive(Eq, PartialEq)]
 #[derive(Debug, Clone)]
 pub enum Expression {
     Identifier {
         name: String,
+        span: Span,
+        // Filled in by the resolver, unresolved identifiers are looked up by name
+        slot: Option<Slot>,
     },
     Integer {
         value: String,
@@ -26,6 +37,12 @@ pub enum Expression {
     Boolean {
         value: String,
     },
+    BigInteger {
+        value: String,
+    },
+    Decimal {
+        value: String,
+    },
     Prefix {
         prefix: Token,
         value: Box<Expression>,
@@ -47,6 +64,8 @@ pub enum Expression {
     Call {
         name: String,
         arguments: Vec<Box<Expression>>,
+        span: Span,
+        slot: Option<Slot>,
     },
     Error {
         value: Box<Expression>
@@ -58,6 +77,12 @@ pub enum Expression {
     Array {
         values: Vec<Box<Expression>>
     },
+    Map {
+        entries: Vec<(Box<Expression>, Box<Expression>)>
+    },
+    Tuple {
+        values: Vec<Box<Expression>>
+    },
     Block {
         statements: Vec<Box<Statement>>
     },
@@ -65,83 +90,57 @@ pub enum Expression {
         source: Box<Expression>,
         index: Box<Expression>,
     },
+    Slice {
+        source: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+    },
+    Format {
+        parts: Vec<FormatPart>,
+    },
+}
+
+/// Part of an interpolated string
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub enum FormatPart {
+    Literal(String),
+    Value {
+        value: Box<Expression>,
+        spec: FormatSpec,
+    },
 }
 
 impl Expression {
     pub fn evaluate(&self, environment: &mut Environment) -> anyhow::Result<(OwnerShip, bool)> {
+        environment.before_expression(self)?;
         match self {
-            Expression::Identifier { name } => {
-                if let Some(value) = environment.get(name) {
-                    Ok((OwnerShip::Reference(name.clone()), false))
-                } else {
-                    bail!(UnknownIdentifier(name.to_string()))
-                }
-            }
-            Expression::Integer { value } => {
-                if let Ok(value) = value.parse::<i128>() {
-                    Ok((OwnerShip::Instance(Object::Integer(value)), false))
-                } else {
-                    bail!(CannotConvertInto(value.clone(), "Integer".to_string()))
-                }
-            }
-            Expression::Float { value } => {
-                if let Ok(value) = value.parse::<f64>() {
-                    Ok((OwnerShip::Instance(Object::Float(value)), false))
-                } else {
-                    bail!(CannotConvertInto(value.clone(), "Float".to_string()))
-                }
-            }
-            Expression::String { value } => {
-                Ok((OwnerShip::Instance(Object::String(value.clone())), false))
+            Expression::Identifier { name, slot, .. } => {
+                Ok((locate_variable(name, *slot, environment)?, false))
             }
-            Expression::Boolean { value } => {
-                if value == "true" {
-                    Ok((OwnerShip::Instance(Object::Boolean(true)), false))
-                } else {
-                    Ok((OwnerShip::Instance(Object::Boolean(false)), false))
-                }
+            Expression::Integer { .. } |
+            Expression::Float { .. } |
+            Expression::String { .. } |
+            Expression::Boolean { .. } |
+            Expression::BigInteger { .. } |
+            Expression::Decimal { .. } => {
+                Ok((OwnerShip::Instance(evaluate_literal(self)?), false))
             }
             Expression::Prefix { prefix, value } => {
                 let (object_ownership, _) = value.evaluate(environment)?;
-                let object = object_ownership.value(environment)?;
-
-                let object = match prefix {
-                    Token::Subtract => {
-                        match object {
-                            Object::Integer(val) => OwnerShip::Instance(Object::Integer(-val)),
-                            Object::Float(val) => OwnerShip::Instance(Object::Float(-val)),
-                            _ => bail!(IllegalPrefixOperation(Token::Subtract, value.clone()))
-                        }
-                    }
-                    Token::Invert => {
-                        match object {
-                            Object::Boolean(val) => OwnerShip::Instance(Object::Boolean(!val)),
-                            _ => bail!(IllegalPrefixOperation(Token::Invert, value.clone())),
-                        }
-                    }
-                    Token::SingleAnd => {
-                        match object_ownership {
-                            OwnerShip::Reference(_) => object_ownership,
-                            _ => bail!(IllegalPrefixOperation(Token::Invert, value.clone())),
-                        }
-                    }
-                    _ => unreachable!(),
-                };
-
-                Ok((object, false))
+                Ok((evaluate_prefix_expression(prefix, object_ownership, environment)?, false))
             }
             Expression::Infix { left, operation, right } => {
-                Ok((
-                    OwnerShip::Instance(evaluate_infix_expression(left, left.evaluate(environment)?.0.value(environment)?, right.evaluate(environment)?.0.value(environment)?, operation)?),
-                    false
-                ))
+                let left_obj = left.evaluate(environment)?.0.value(environment)?;
+                if let Some(object) = short_circuit(&left_obj, operation)? {
+                    return Ok((OwnerShip::Instance(object), false))
+                }
+                let right_obj = right.evaluate(environment)?.0.value(environment)?;
+
+                Ok((OwnerShip::Instance(evaluate_infix_expression(left_obj, right_obj, operation)?), false))
             }
             Expression::If { condition, consequence, alternative } => {
-                let condition = condition.evaluate(environment)?.0.value(environment)?;
-                let condition = match condition {
-                    Object::Boolean(val) => val,
-                    obj => bail!(InvalidTypeButFound(Token::BooleanType, obj)),
-                };
+                let condition = evaluate_condition(condition.evaluate(environment)?.0.value(environment)?)?;
 
                 if condition {
                     environment.stack_mut().create_scope();
@@ -160,152 +159,648 @@ impl Expression {
                 }
             }
             Expression::While { condition, consequence } => {
-                let condition_obj = condition.evaluate(environment)?.0.value(environment)?;
-                let mut condition_val = match condition_obj {
-                    Object::Boolean(val) => val,
-                    obj => bail!(InvalidTypeButFound(Token::BooleanType, obj)),
-                };
+                let mut condition_val = evaluate_condition(condition.evaluate(environment)?.0.value(environment)?)?;
 
                 let mut result = (OwnerShip::Instance(Object::Null), false);
 
                 while condition_val {
-                    result = consequence.evaluate(environment)?;
+                    environment.stack_mut().create_scope();
+                    let iteration = consequence.evaluate(environment);
+                    environment.stack_mut().drop_scope();
+                    result = iteration?;
+                    // A `ret` inside of the loop leaves the loop as well
+                    if result.1 { break }
 
+                    environment.retain(result.0.clone());
                     let condition_obj = condition.evaluate(environment)?.0.value(environment)?;
-                    condition_val = match condition_obj {
-                        Object::Boolean(val) => val,
-                        obj => bail!(InvalidTypeButFound(Token::BooleanType, obj)),
-                    };
+                    environment.release(1);
+                    condition_val = evaluate_condition(condition_obj)?;
                 }
 
                 Ok(result)
             }
-            Expression::Call { name, arguments } => {
-                let function = environment
-                    .get(name)
-                    .ok_or(UnknownIdentifier(name.clone()))?
-                    .value(environment)?;
-
-                match function {
-                    Object::Function {
-                        parameters,
-                        typee,
-                        body,
-                    } => {
-                        environment.stack_mut().create_scope();
+            Expression::Call { name, arguments, span, slot } => {
+                let function = locate_function(name, *slot, arguments.len(), environment)?;
 
-                        if parameters.len() != arguments.len() { bail!(InvalidAmountOfArguments(parameters.len(), arguments.len())) }
-
-                        for i in 0..parameters.len() {
-                            let val = arguments[i].evaluate(environment)?.0.value(environment)?;
-                            match val {
-                                Object::Integer(_) if parameters[i].1.equal_variant(&Token::IntegerType) => {}
-                                Object::Float(_) if parameters[i].1.equal_variant(&Token::FloatType) => {}
-                                Object::String(_) if parameters[i].1.equal_variant(&Token::StringType) => {}
-                                Object::Boolean(_) if parameters[i].1.equal_variant(&Token::BooleanType) => {}
-                                obj => bail!(ExpectedTypeButFound(parameters[i].1.clone(), obj)),
-                            }
-                            environment.stack_mut().add(parameters[i].0.clone(), OwnerShip::Instance(val));
-                        }
-
-                        let mut result = body.evaluate(environment)?.0.value(environment)?;
-
-                        if !typee.equal_variant(&Token::NullType) {
-                            match result {
-                                Object::Integer(_) if typee.equal_variant(&Token::IntegerType) => {}
-                                Object::Float(_) if typee.equal_variant(&Token::FloatType) => {}
-                                Object::String(_) if typee.equal_variant(&Token::StringType) => {}
-                                Object::Boolean(_) if typee.equal_variant(&Token::BooleanType) => {}
-                                Object::Null if typee.equal_variant(&Token::NullType) => {}
-                                obj => bail!(ExpectedTypeButFound(typee.clone(), obj)),
-                            }
-                        } else {
-                            result = Object::Null;
-                        }
+                let mut values = vec![];
+                for argument in arguments {
+                    let value = argument.evaluate(environment)?.0.value(environment)?;
+                    environment.retain(OwnerShip::Instance(value.clone()));
+                    values.push(value);
+                }
+                environment.release(values.len());
 
-                        environment.stack_mut().drop_scope();
+                let result = call_function(name, *span, &function, values, environment, |function, environment| {
+                    Ok(function.body.evaluate(environment)?.0)
+                })?;
 
-                        return Ok((OwnerShip::Instance(result), false));
-                    }
-                    obj => bail!(CannotCall(obj.clone()))
-                }
+                Ok((OwnerShip::Instance(result), false))
             }
             Expression::Error { value } => {
                 Ok((OwnerShip::Instance(Object::Error(Box::new(value.evaluate(environment)?.0.value(environment)?))), false))
             }
             Expression::Assign { assign_to, value } => {
-                let identifier = match assign_to.evaluate(environment)?.0 {
-                    OwnerShip::Reference(ident) => {
-                        let mut identifier = ident;
-                        loop {
-                            match environment.get(&identifier).ok_or(UnknownIdentifier(identifier.clone()))? {
-                                OwnerShip::Reference(ident) => {
-                                    identifier = ident.clone();
-                                }
-                                OwnerShip::Instance(_) => break,
-                            }
-                        }
-                        identifier
+                let (location, target) = match assign_to.as_ref() {
+                    // Entries of maps can be assigned before they exist
+                    Expression::Access { source, index } => {
+                        let collection = evaluate_collection(source, environment)?;
+                        environment.retain(OwnerShip::Instance(collection.clone()));
+                        let index_obj = index.evaluate(environment)?.0.value(environment)?;
+                        environment.release(1);
+                        (access_location(&collection, index_obj, environment)?, OwnerShip::Instance(collection))
+                    }
+                    _ => {
+                        let target = assign_to.evaluate(environment)?.0;
+                        (assign_location(&target, environment)?, target)
                     }
-                    _ => bail!(CannotAssignTo(assign_to.clone()))
                 };
+                let old = target_value(&location, environment)?;
 
-                if environment.get(&identifier).is_none() { bail!(UnknownIdentifier(identifier.clone())) }
-
-                let old_ownership = environment.get(&identifier).unwrap();
-                let old = old_ownership.value(environment)?;
+                environment.retain(target);
                 let value_ownership = value.evaluate(environment)?.0;
-                let value = value_ownership.value(environment)?;
-                if !old.equal_variant(&value) {
-                    bail!(CannotAssignDifferentType(value, old, identifier.clone()))
-                }
-
-                *(environment.get_mut(&identifier).unwrap()) = value_ownership.clone();
+                environment.release(1);
 
-                Ok((value_ownership, false))
+                Ok((assign(&location, old, is_reference(value), value_ownership, environment)?, false))
             }
             Expression::Array { values } => {
                 let mut objs = vec![];
                 for val in values {
                     let ownership = val.evaluate(environment)?.0;
-                    let address = environment.heap_mut().set(ownership);
-                    objs.push(OwnerShip::Reference(address));
+                    let address = allocate(is_reference(val), ownership, environment)?;
+                    environment.retain(OwnerShip::Heap(address));
+                    objs.push(OwnerShip::Heap(address));
                 }
+                environment.release(objs.len());
                 let address = environment.heap_mut().set(OwnerShip::Instance(Object::Array(objs)));
-                Ok((OwnerShip::Reference(address), false))
+                Ok((OwnerShip::Heap(address), false))
+            }
+            Expression::Map { entries } => {
+                let mut pairs = vec![];
+                for (key, value) in entries {
+                    let key_obj = key.evaluate(environment)?.0.value(environment)?;
+                    let ownership = value.evaluate(environment)?.0;
+                    let address = allocate(is_reference(value), ownership, environment)?;
+                    environment.retain(OwnerShip::Heap(address));
+                    pairs.push((key_obj, OwnerShip::Heap(address)));
+                }
+                environment.release(pairs.len());
+                Ok((OwnerShip::Instance(create_map(pairs, environment)?), false))
+            }
+            Expression::Tuple { values } => {
+                let mut objs = vec![];
+                for val in values {
+                    let ownership = val.evaluate(environment)?.0;
+                    let ownership = bind(is_reference(val), ownership, environment)?;
+                    environment.retain(ownership.clone());
+                    objs.push(ownership);
+                }
+                environment.release(objs.len());
+                Ok((OwnerShip::Instance(Object::Tuple(objs)), false))
             }
             Expression::Block { statements } => {
                 evaluate_block(statements, false, environment)
             }
             Expression::Access { source, index } => {
-                let array = match source.clone().deref() {
-                    Expression::Identifier { name } => environment.get(name).ok_or(UnknownIdentifier(name.clone()))?.clone(),
-                    _ => source.evaluate(environment)?.0,
-                };
-
-                let array = match array.value(environment)? {
-                    Object::Array(array) => array,
-                    _ => bail!(CannotArrayAccess(source.clone()))
-                };
+                let collection = evaluate_collection(source, environment)?;
 
+                environment.retain(OwnerShip::Instance(collection.clone()));
                 let index_obj = index.evaluate(environment)?.0.value(environment)?;
-                let index_val = match index_obj {
-                    Object::Integer(val) => val,
-                    _ => bail!(CannotAccessArrayWith(index.clone()))
-                };
+                environment.release(1);
+
+                Ok((access(&collection, index_obj, environment)?, false))
+            }
+            Expression::Slice { source, start, end } => {
+                let collection = evaluate_collection(source, environment)?;
 
-                if index_val < 0 { bail!(IndexOutOfRange(index_val)) }
+                environment.retain(OwnerShip::Instance(collection.clone()));
+                let mut bounds = vec![];
+                for bound in [start, end] {
+                    bounds.push(match bound {
+                        Some(bound) => Some(bound.evaluate(environment)?.0.value(environment)?),
+                        None => None,
+                    });
+                }
+                environment.release(1);
 
-                if index_val as usize >= array.len() { bail!(IndexOutOfRange(index_val)) }
+                let end = bounds.pop().unwrap();
+                let start = bounds.pop().unwrap();
+                Ok((slice(&collection, start, end, environment)?, false))
+            }
+            Expression::Format { parts } => {
+                let mut values = vec![];
+                for part in parts {
+                    let FormatPart::Value { value, .. } = part else { continue };
+                    let object = value.evaluate(environment)?.0.value(environment)?;
+                    environment.retain(OwnerShip::Instance(object.clone()));
+                    values.push(object);
+                }
+                environment.release(values.len());
 
-                Ok((array[index_val as usize].clone(), false))
+                Ok((OwnerShip::Instance(interpolate(parts, values, environment)?), false))
             }
         }
     }
+
+    /// Expressions which are directly part of this one, statements inside blocks are not included
+    pub fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Identifier { .. } |
+            Expression::Integer { .. } |
+            Expression::Float { .. } |
+            Expression::String { .. } |
+            Expression::Boolean { .. } |
+            Expression::BigInteger { .. } |
+            Expression::Decimal { .. } |
+            Expression::Block { .. } => vec![],
+            Expression::Prefix { value, .. } |
+            Expression::Error { value } => vec![value],
+            Expression::Infix { left, right, .. } => vec![left, right],
+            Expression::If { condition, consequence, alternative } => {
+                let mut children = vec![condition.as_ref(), consequence];
+                children.extend(alternative.as_deref());
+                children
+            }
+            Expression::While { condition, consequence } => vec![condition, consequence],
+            Expression::Call { arguments, .. } |
+            Expression::Array { values: arguments } |
+            Expression::Tuple { values: arguments } => arguments.iter().map(Box::as_ref).collect(),
+            Expression::Assign { assign_to, value } => vec![assign_to, value],
+            Expression::Map { entries } => entries.iter().flat_map(|(key, value)| [key.as_ref(), value]).collect(),
+            Expression::Access { source, index } => vec![source, index],
+            Expression::Slice { source, start, end } => [Some(source), start.as_ref(), end.as_ref()].into_iter().flatten().map(Box::as_ref).collect(),
+            Expression::Format { parts } => parts.iter()
+                .filter_map(|part| match part {
+                    FormatPart::Value { value, .. } => Some(value.as_ref()),
+                    FormatPart::Literal(_) => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Array or map an access expression is applied on
+fn evaluate_collection(source: &Expression, environment: &mut Environment) -> anyhow::Result<Object> {
+    let collection = match source {
+        Expression::Identifier { name, slot, .. } => locate_value(name, *slot, environment)?,
+        _ => source.evaluate(environment)?.0,
+    };
+    access_source(&collection, environment)
+}
+
+/// Reference to the variable with the given name
+pub fn locate_variable(name: &str, slot: Option<Slot>, environment: &Environment) -> anyhow::Result<OwnerShip> {
+    let index = environment.stack().locate(name, slot).ok_or(UnknownIdentifier(name.to_string()))?;
+    Ok(OwnerShip::Reference(Variable { name: name.to_string(), index }))
+}
+
+/// Ownership which is stored inside the variable with the given name
+pub fn locate_value(name: &str, slot: Option<Slot>, environment: &Environment) -> anyhow::Result<OwnerShip> {
+    Ok(environment.stack()
+        .locate(name, slot)
+        .and_then(|index| environment.stack().value(index))
+        .ok_or(UnknownIdentifier(name.to_string()))?
+        .clone())
+}
+
+/// Copy of the value of the variable with the given name
+pub fn read_variable(name: &str, slot: Option<Slot>, environment: &Environment) -> anyhow::Result<Object> {
+    environment.stack()
+        .locate(name, slot)
+        .and_then(|index| environment.stack().value(index))
+        .ok_or_else(|| UnknownIdentifier(name.to_string()))?
+        .value(environment)
+}
+
+/// Converts an integer, float, string or boolean literal into its object
+pub fn evaluate_literal(expression: &Expression) -> anyhow::Result<Object> {
+    Ok(match expression {
+        Expression::Integer { value } => convert_number(value, &Token::IntegerType)?,
+        Expression::Float { value } => convert_number(value, &Token::FloatType)?,
+        Expression::String { value } => Object::String(value.clone()),
+        Expression::Boolean { value } => Object::Boolean(value == "true"),
+        Expression::BigInteger { value } => convert_number(value, &Token::BigIntegerType)?,
+        Expression::Decimal { value } => convert_number(value, &Token::DecimalType)?,
+        _ => unreachable!(),
+    })
+}
+
+/// Parses the digits of a number literal into an object of the given type
+pub fn convert_number(value: &str, typee: &Token) -> anyhow::Result<Object> {
+    Ok(match typee {
+        Token::IntegerType => match value.parse::<i128>() {
+            Ok(value) => Object::Integer(value),
+            Err(_) => bail!(CannotConvertInto(value.to_string(), "Integer".to_string())),
+        }
+        Token::FloatType => match value.parse::<f64>() {
+            Ok(value) => Object::Float(value),
+            Err(_) => bail!(CannotConvertInto(value.to_string(), "Float".to_string())),
+        }
+        Token::BigIntegerType => match value.parse::<BigInt>() {
+            Ok(value) => Object::BigInteger(value),
+            Err(_) => bail!(CannotConvertInto(value.to_string(), "BigInteger".to_string())),
+        }
+        Token::DecimalType => match value.parse::<BigDecimal>() {
+            Ok(value) => Object::Decimal(value),
+            Err(_) => bail!(CannotConvertInto(value.to_string(), "Decimal".to_string())),
+        }
+        _ => unreachable!(),
+    })
+}
+
+pub fn evaluate_prefix_expression(
+    prefix: &Token,
+    object_ownership: OwnerShip,
+    environment: &Environment,
+) -> anyhow::Result<OwnerShip> {
+    let object = object_ownership.value(environment)?;
+
+    Ok(match prefix {
+        Token::Subtract => {
+            match object {
+                Object::Integer(val) => OwnerShip::Instance(Object::Integer(val.checked_neg().ok_or(NegationOverflow(val))?)),
+                Object::Float(val) => OwnerShip::Instance(Object::Float(-val)),
+                Object::BigInteger(val) => OwnerShip::Instance(Object::BigInteger(-val)),
+                Object::Decimal(val) => OwnerShip::Instance(Object::Decimal(-val)),
+                obj => bail!(IllegalPrefixOperation(Token::Subtract, obj))
+            }
+        }
+        Token::Invert => {
+            match object {
+                Object::Boolean(val) => OwnerShip::Instance(Object::Boolean(!val)),
+                obj => bail!(IllegalPrefixOperation(Token::Invert, obj)),
+            }
+        }
+        Token::BitNot => {
+            match object {
+                Object::Integer(val) => OwnerShip::Instance(Object::Integer(!val)),
+                Object::BigInteger(val) => OwnerShip::Instance(Object::BigInteger(!val)),
+                obj => bail!(IllegalPrefixOperation(Token::BitNot, obj)),
+            }
+        }
+        Token::SingleAnd => {
+            match object_ownership {
+                OwnerShip::Reference(_) |
+                OwnerShip::Heap(_) => object_ownership,
+                _ => bail!(IllegalPrefixOperation(Token::Invert, object)),
+            }
+        }
+        _ => unreachable!(),
+    })
+}
+
+/// `&&` and `||` skip their right side once the left side decides the result.
+/// The left side has to be a boolean, the right side is checked by `evaluate_infix_expression`.
+pub fn short_circuit(left_obj: &Object, operation: &Token) -> anyhow::Result<Option<Object>> {
+    match (operation, left_obj) {
+        (Token::And, Object::Boolean(false)) => Ok(Some(Object::Boolean(false))),
+        (Token::Or, Object::Boolean(true)) => Ok(Some(Object::Boolean(true))),
+        (Token::And | Token::Or, Object::Boolean(_)) => Ok(None),
+        (Token::And | Token::Or, _) => bail!(CannotApplyOn(operation.clone(), left_obj.clone())),
+        _ => Ok(None),
+    }
+}
+
+/// Conditions of `if` and `while` have to be booleans
+pub fn evaluate_condition(condition: Object) -> anyhow::Result<bool> {
+    match condition {
+        Object::Boolean(val) => Ok(val),
+        obj => bail!(InvalidTypeButFound(Token::BooleanType, obj)),
+    }
+}
+
+/// Looks up the function or builtin which gets called and checks the amount of arguments.
+/// Declared functions are referred to by their variable, the call finds the scope they were declared in through it.
+pub fn locate_function(name: &str, slot: Option<Slot>, arguments: usize, environment: &Environment) -> anyhow::Result<OwnerShip> {
+    let function = match environment.stack().locate(name, slot) {
+        Some(index) => OwnerShip::Reference(Variable { name: name.to_string(), index }),
+        None => OwnerShip::Instance(Object::Builtin(builtin(name).ok_or(UnknownIdentifier(name.to_string()))?)),
+    };
+
+    let expected = match &function.value(environment)? {
+        Object::Function(function) => function.parameters.len(),
+        Object::Builtin(builtin) => builtin.arguments,
+        obj => bail!(CannotCall(obj.clone()))
+    };
+    if expected != arguments { bail!(InvalidAmountOfArguments(expected, arguments)) }
+
+    Ok(function)
+}
+
+/// Location which gets changed when assigning to the evaluated target of an assignment
+pub fn assign_location(target: &OwnerShip, environment: &Environment) -> anyhow::Result<Location> {
+    match environment.resolve(target)? {
+        Some(location) => Ok(location),
+        None => bail!(CannotAssignTo(target.value(environment)?))
+    }
+}
+
+/// Value the new value of an assignment has to match. New entries of a map have to match the other entries.
+pub fn target_value(location: &Location, environment: &Environment) -> anyhow::Result<Option<Object>> {
+    match location {
+        Location::Entry { map, .. } => environment.entries(*map)?.values().next().map(|entry| entry.value(environment)).transpose(),
+        location => Ok(Some(environment.lookup(location)?.value(environment)?)),
+    }
+}
+
+/// Stores the new value at the location, it has to have the same type as the old one
+pub fn assign(
+    location: &Location,
+    old: Option<Object>,
+    reference: bool,
+    value_ownership: OwnerShip,
+    environment: &mut Environment,
+) -> anyhow::Result<OwnerShip> {
+    let new = value_ownership.value(environment)?;
+    if let Some(old) = old {
+        if !old.equal_variant(&new) {
+            bail!(CannotAssignDifferentType(new, old, location.to_string()))
+        }
+    }
+
+    let value_ownership = bind(reference, value_ownership, environment)?;
+    match location {
+        Location::Entry { map, key } => {
+            let address = environment.heap_mut().set(value_ownership.clone());
+            environment.entries_mut(*map)?.insert(key.clone(), OwnerShip::Heap(address));
+        }
+        location => *environment.lookup_mut(location)? = value_ownership.clone(),
+    }
+
+    Ok(value_ownership)
+}
+
+/// Moves an element of an array literal onto the heap
+pub fn allocate(reference: bool, ownership: OwnerShip, environment: &mut Environment) -> anyhow::Result<Address> {
+    let ownership = bind(reference, ownership, environment)?;
+    Ok(environment.heap_mut().set(ownership))
+}
+
+/// Array or map an access expression is applied on
+pub fn access_source(collection: &OwnerShip, environment: &Environment) -> anyhow::Result<Object> {
+    match collection.value(environment)? {
+        collection @ (Object::Array(_) | Object::Map(_) | Object::Tuple(_) | Object::String(_)) => Ok(collection),
+        obj => bail!(CannotArrayAccess(obj))
+    }
+}
+
+/// Element of an array, entry of a map or character of a string
+pub fn access(collection: &Object, index_obj: Object, environment: &Environment) -> anyhow::Result<OwnerShip> {
+    match collection {
+        Object::Array(values) |
+        Object::Tuple(values) => access_array(values, index_obj),
+        Object::Map(map) => {
+            let key = Key::try_from(index_obj)?;
+            Ok(environment.entries(*map)?.get(&key).ok_or(KeyNotFound(key.to_string()))?.clone())
+        }
+        Object::String(value) => {
+            let index_val = match index_obj {
+                Object::Integer(val) => val,
+                obj => bail!(CannotAccessArrayWith(obj))
+            };
+            let char = value.chars().nth(position(index_val, value.chars().count())?).unwrap();
+            Ok(OwnerShip::Instance(Object::String(char.to_string())))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Location which gets changed when assigning to an access, entries which are missing in a map get inserted.
+/// Keys of new entries have to match the type of the other keys.
+pub fn access_location(
+    collection: &Object,
+    index_obj: Object,
+    environment: &Environment,
+) -> anyhow::Result<Location> {
+    let Object::Map(map) = collection else {
+        let element = access(collection, index_obj, environment)?;
+        return assign_location(&element, environment)
+    };
+
+    let key = Key::try_from(index_obj)?;
+    let entries = environment.entries(*map)?;
+    if let Some(entry) = entries.get(&key) {
+        return assign_location(entry, environment)
+    }
+    if let Some(other) = entries.keys().next() {
+        if std::mem::discriminant(other) != std::mem::discriminant(&key) {
+            bail!(InvalidMapKey(key.into()))
+        }
+    }
+
+    Ok(Location::Entry { map: *map, key })
+}
+
+/// Creates a map on the heap out of evaluated keys and values which already got allocated
+pub fn create_map(pairs: Vec<(Object, OwnerShip)>, environment: &mut Environment) -> anyhow::Result<Object> {
+    let entries = pairs.into_iter()
+        .map(|(key, value)| Ok((Key::try_from(key)?, value)))
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Object::Map(environment.heap_mut().set(OwnerShip::Instance(Object::MapEntries(entries)))))
+}
+
+/// Checks an object against a type, maps and tuples are checked element by element
+pub fn is_type(object: &Object, typee: &Token, environment: &Environment) -> anyhow::Result<bool> {
+    if let Token::TupleType(types) = typee {
+        let Object::Tuple(values) = object else { return Ok(false) };
+        if values.len() != types.len() { return Ok(false) }
+
+        for (value, typee) in values.iter().zip(types) {
+            if !is_type(&value.value(environment)?, typee, environment)? { return Ok(false) }
+        }
+        return Ok(true)
+    }
+
+    let Token::MapType(key_type, value_type) = typee else {
+        return Ok(matches!(
+            (object, typee),
+            (Object::Integer(_), Token::IntegerType) |
+            (Object::Float(_), Token::FloatType) |
+            (Object::String(_), Token::StringType) |
+            (Object::Boolean(_), Token::BooleanType) |
+            (Object::BigInteger(_), Token::BigIntegerType) |
+            (Object::Decimal(_), Token::DecimalType) |
+            (Object::Array(_), Token::ArrayType)
+        ))
+    };
+    let Object::Map(map) = object else { return Ok(false) };
+
+    for (key, value) in environment.entries(*map)? {
+        if !is_type(&key.clone().into(), key_type, environment)? || !is_type(&value.value(environment)?, value_type, environment)? {
+            return Ok(false)
+        }
+    }
+    Ok(true)
+}
+
+pub fn access_array(array: &[OwnerShip], index_obj: Object) -> anyhow::Result<OwnerShip> {
+    let index_val = match index_obj {
+        Object::Integer(val) => val,
+        obj => bail!(CannotAccessArrayWith(obj))
+    };
+
+    Ok(array[position(index_val, array.len())?].clone())
+}
+
+/// Joins the literal parts of an interpolated string with the formatted values
+pub fn interpolate(parts: &[FormatPart], values: Vec<Object>, environment: &Environment) -> anyhow::Result<Object> {
+    let mut values = values.into_iter();
+    let mut string = String::new();
+
+    for part in parts {
+        match part {
+            FormatPart::Literal(literal) => string.push_str(literal),
+            FormatPart::Value { spec, .. } => {
+                let value = values.next().unwrap().resolve(environment)?;
+                string.push_str(&spec.apply(&value)?);
+            }
+        }
+    }
+    Ok(Object::String(string))
+}
+
+/// Position of an index inside a sequence, negative indices count from the end
+fn position(index_val: i128, length: usize) -> anyhow::Result<usize> {
+    let position = if index_val < 0 { index_val + length as i128 } else { index_val };
+    if position < 0 || position >= length as i128 { bail!(IndexOutOfRange(index_val)) }
+    Ok(position as usize)
+}
+
+/// Range of a slice inside a sequence, missing bounds default to its start and end and negative bounds count from the end
+pub fn slice_bounds(start: Option<i128>, end: Option<i128>, length: usize) -> anyhow::Result<(usize, usize)> {
+    let normalize = |bound: i128| if bound < 0 { bound + length as i128 } else { bound };
+    let (from, to) = (start.map_or(0, normalize), end.map_or(length as i128, normalize));
+
+    if from < 0 || from > to || to > length as i128 {
+        bail!(SliceOutOfRange(start.unwrap_or(0), end.unwrap_or(length as i128), length))
+    }
+    Ok((from as usize, to as usize))
+}
+
+/// New array with copies of the sliced elements or the sliced part of a string
+pub fn slice(
+    collection: &Object,
+    start: Option<Object>,
+    end: Option<Object>,
+    environment: &mut Environment,
+) -> anyhow::Result<OwnerShip> {
+    let bound = |bound: Option<Object>| match bound {
+        Some(Object::Integer(value)) => Ok(Some(value)),
+        Some(obj) => bail!(ExpectedTypeButFound(Token::IntegerType, obj)),
+        None => Ok(None),
+    };
+    let (start, end) = (bound(start)?, bound(end)?);
+
+    match collection {
+        Object::Array(values) => {
+            let (from, to) = slice_bounds(start, end, values.len())?;
+            let mut elements = vec![];
+            for value in &values[from..to] {
+                elements.push(OwnerShip::Heap(allocate(false, value.clone(), environment)?));
+            }
+            Ok(OwnerShip::Heap(environment.heap_mut().set(OwnerShip::Instance(Object::Array(elements)))))
+        }
+        Object::String(value) => {
+            let (from, to) = slice_bounds(start, end, value.chars().count())?;
+            Ok(OwnerShip::Instance(Object::String(value.chars().skip(from).take(to - from).collect())))
+        }
+        obj => bail!(CannotSlice(obj.clone())),
+    }
+}
+
+/// Explicit references like `&a` get stored as they are, the values of other expressions get copied
+pub fn is_reference(expression: &Expression) -> bool {
+    matches!(expression, Expression::Prefix { prefix: Token::SingleAnd, .. })
+}
+
+/// Copies the value of an expression before it gets stored, unless it is an explicit reference
+pub fn bind(reference: bool, ownership: OwnerShip, environment: &Environment) -> anyhow::Result<OwnerShip> {
+    if reference {
+        Ok(ownership)
+    } else {
+        ownership.bind(environment)
+    }
+}
+
+/// Calls a located function or builtin with already evaluated arguments, function bodies are evaluated by the given backend.
+/// Errors get the trace of the innermost call attached.
+pub fn call_function(
+    name: &str,
+    span: Span,
+    function: &OwnerShip,
+    arguments: Vec<Object>,
+    environment: &mut Environment,
+    body: impl FnOnce(&Function, &mut Environment) -> anyhow::Result<OwnerShip>,
+) -> anyhow::Result<Object> {
+    let link = match function {
+        OwnerShip::Reference(variable) => Some(environment.stack().scope_of(variable.index)),
+        _ => None,
+    };
+    let function = function.value(environment)?;
+    environment.call_stack_mut().push(Frame {
+        name: name.to_string(),
+        span,
+        arguments: arguments.clone(),
+    });
+
+    let result = match &function {
+        Object::Function(function) => {
+            environment.stack_mut().create_frame(link);
+            let result = bind_arguments(function, arguments, environment)
+                .and_then(|_| body(function, environment))
+                .and_then(|result| check_return(function, result.value(environment)?, environment));
+            environment.stack_mut().drop_frame();
+            result
+        }
+        Object::Builtin(builtin) => (builtin.function)(arguments, environment),
+        _ => unreachable!(),
+    };
+
+    let result = result.map_err(|err| {
+        if err.downcast_ref::<StackTrace>().is_some() { return err }
+        let trace = environment.call_stack().trace(&err);
+        err.context(trace)
+    });
+    environment.call_stack_mut().pop();
+
+    result
+}
+
+/// Binds the arguments inside the already created scope of the function
+fn bind_arguments(function: &Function, arguments: Vec<Object>, environment: &mut Environment) -> anyhow::Result<()> {
+    for ((name, parameter_type), val) in function.parameters.iter().zip(arguments) {
+        match &val {
+            Object::Integer(_) if parameter_type.equal_variant(&Token::IntegerType) => {}
+            Object::Float(_) if parameter_type.equal_variant(&Token::FloatType) => {}
+            Object::String(_) if parameter_type.equal_variant(&Token::StringType) => {}
+            Object::Boolean(_) if parameter_type.equal_variant(&Token::BooleanType) => {}
+            Object::BigInteger(_) if parameter_type.equal_variant(&Token::BigIntegerType) => {}
+            Object::Decimal(_) if parameter_type.equal_variant(&Token::DecimalType) => {}
+            Object::Map(_) | Object::Tuple(_) if is_type(&val, parameter_type, environment)? => {}
+            obj => bail!(ExpectedTypeButFound(parameter_type.clone(), obj.clone())),
+        }
+        environment.stack_mut().add(name.clone(), OwnerShip::Instance(val));
+    }
+    Ok(())
+}
+
+fn check_return(function: &Function, result: Object, environment: &Environment) -> anyhow::Result<Object> {
+    if function.typee.equal_variant(&Token::NullType) { return Ok(Object::Null) }
+
+    match &result {
+        Object::Integer(_) if function.typee.equal_variant(&Token::IntegerType) => {}
+        Object::Float(_) if function.typee.equal_variant(&Token::FloatType) => {}
+        Object::String(_) if function.typee.equal_variant(&Token::StringType) => {}
+        Object::Boolean(_) if function.typee.equal_variant(&Token::BooleanType) => {}
+        Object::BigInteger(_) if function.typee.equal_variant(&Token::BigIntegerType) => {}
+        Object::Decimal(_) if function.typee.equal_variant(&Token::DecimalType) => {}
+        Object::Map(_) | Object::Tuple(_) if is_type(&result, &function.typee, environment)? => {}
+        obj => bail!(ExpectedTypeButFound(function.typee.clone(), obj.clone())),
+    }
+
+    Ok(result)
 }
 
 pub fn evaluate_infix_expression(
-    left: &Box<Expression>,
     left_obj: Object,
     right_obj: Object,
     operation: &Token,
@@ -315,15 +810,32 @@ pub fn evaluate_infix_expression(
         float_op,
         string_op,
         bool_op,
+        big_integer_op,
+        decimal_op,
         int_float_mixable,
     ) = get_apply_functions(operation);
 
+    // Integers are promoted to big integers and decimals without losing precision, floats never mix with them
     Ok(match left_obj {
         Object::Integer(left_val) if integer_op.is_some() => match right_obj {
-            Object::Integer(right_val) => integer_op.unwrap()(left_val, right_val),
+            Object::Integer(right_val) => integer_op.unwrap()(left_val, right_val)?,
             Object::Float(right_val) if int_float_mixable => float_op.unwrap()(left_val as f64, right_val),
+            Object::BigInteger(right_val) => big_integer_op.unwrap()(BigInt::from(left_val), right_val)?,
+            Object::Decimal(right_val) if decimal_op.is_some() => decimal_op.unwrap()(BigDecimal::from(left_val), right_val)?,
             obj => bail!(IncompatibleTypes(Token::IntegerType, obj, operation.clone()))
         }
+        Object::BigInteger(left_val) if big_integer_op.is_some() => match right_obj {
+            Object::BigInteger(right_val) => big_integer_op.unwrap()(left_val, right_val)?,
+            Object::Integer(right_val) => big_integer_op.unwrap()(left_val, BigInt::from(right_val))?,
+            Object::Decimal(right_val) if decimal_op.is_some() => decimal_op.unwrap()(BigDecimal::from(left_val), right_val)?,
+            obj => bail!(IncompatibleTypes(Token::BigIntegerType, obj, operation.clone()))
+        }
+        Object::Decimal(left_val) if decimal_op.is_some() => match right_obj {
+            Object::Decimal(right_val) => decimal_op.unwrap()(left_val, right_val)?,
+            Object::Integer(right_val) => decimal_op.unwrap()(left_val, BigDecimal::from(right_val))?,
+            Object::BigInteger(right_val) => decimal_op.unwrap()(left_val, BigDecimal::from(right_val))?,
+            obj => bail!(IncompatibleTypes(Token::DecimalType, obj, operation.clone()))
+        }
         Object::Float(left_val) if float_op.is_some() => match right_obj {
             Object::Integer(right_val) => float_op.unwrap()(left_val, right_val as f64),
             Object::Float(right_val) if int_float_mixable => float_op.unwrap()(left_val, right_val),
@@ -337,58 +849,172 @@ pub fn evaluate_infix_expression(
             Object::Boolean(right_val) => bool_op.unwrap()(left_val, right_val),
             obj => bail!(IncompatibleTypes(Token::BooleanType, obj, operation.clone()))
         }
-        _ => bail!(CannotApplyOn(operation.clone(), left.clone()))
+        obj => bail!(CannotApplyOn(operation.clone(), obj))
     })
 }
 
-pub fn get_apply_functions(operator: &Token) -> (
-    Option<fn(i128, i128) -> Object>,
+pub type ApplyFunctions = (
+    Option<fn(i128, i128) -> anyhow::Result<Object>>,
     Option<fn(f64, f64) -> Object>,
     Option<fn(String, String) -> Object>,
     Option<fn(bool, bool) -> Object>,
+    Option<fn(BigInt, BigInt) -> anyhow::Result<Object>>,
+    Option<fn(BigDecimal, BigDecimal) -> anyhow::Result<Object>>,
     bool,
-) {
+);
+
+pub fn get_apply_functions(operator: &Token) -> ApplyFunctions {
     match operator {
         Token::Add => (
-            Some(|a: i128, b: i128| Object::Integer(a + b)),
+            Some(|a: i128, b: i128| Ok(Object::Integer(a.checked_add(b).ok_or(IntegerOverflow(Token::Add, a, b))?))),
             Some(|a: f64, b: f64| Object::Float(a + b)),
             Some(|a: String, b: String| Object::String(format!("{}{}", a, b))),
             None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a + b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Decimal(a + b))),
             true,
         ),
         Token::Subtract => (
-            Some(|a: i128, b: i128| Object::Integer(a - b)),
+            Some(|a: i128, b: i128| Ok(Object::Integer(a.checked_sub(b).ok_or(IntegerOverflow(Token::Subtract, a, b))?))),
             Some(|a: f64, b: f64| Object::Float(a - b)),
             None,
             None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a - b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Decimal(a - b))),
             true,
         ),
         Token::Multiply => (
-            Some(|a: i128, b: i128| Object::Integer(a * b)),
+            Some(|a: i128, b: i128| Ok(Object::Integer(a.checked_mul(b).ok_or(IntegerOverflow(Token::Multiply, a, b))?))),
             Some(|a: f64, b: f64| Object::Float(a * b)),
             None,
             None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a * b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Decimal(a * b))),
             true,
         ),
         Token::Divide => (
-            Some(|a: i128, b: i128| Object::Integer(a / b)),
+            Some(|a: i128, b: i128| {
+                if b == 0 { bail!(DivisionByZero(a.to_string())) }
+                Ok(Object::Integer(a.checked_div(b).ok_or(IntegerOverflow(Token::Divide, a, b))?))
+            }),
             Some(|a: f64, b: f64| Object::Float(a / b)),
             None,
             None,
+            Some(|a: BigInt, b: BigInt| {
+                if b.is_zero() { bail!(DivisionByZero(a.to_string())) }
+                Ok(Object::BigInteger(a / b))
+            }),
+            Some(|a: BigDecimal, b: BigDecimal| {
+                if b.is_zero() { bail!(DivisionByZero(a.to_string())) }
+                Ok(Object::Decimal(a / b))
+            }),
             true,
         ),
         Token::Modular => (
-            Some(|a: i128, b: i128| Object::Integer(a % b)),
+            Some(|a: i128, b: i128| {
+                if b == 0 { bail!(ModuloByZero(a.to_string())) }
+                Ok(Object::Integer(a.checked_rem(b).ok_or(IntegerOverflow(Token::Modular, a, b))?))
+            }),
             Some(|a: f64, b: f64| Object::Float(a % b)),
             None,
             None,
+            Some(|a: BigInt, b: BigInt| {
+                if b.is_zero() { bail!(ModuloByZero(a.to_string())) }
+                Ok(Object::BigInteger(a % b))
+            }),
+            Some(|a: BigDecimal, b: BigDecimal| {
+                if b.is_zero() { bail!(ModuloByZero(a.to_string())) }
+                Ok(Object::Decimal(a % b))
+            }),
+            true,
+        ),
+        Token::Power => (
+            Some(|a: i128, b: i128| {
+                if b < 0 { bail!(NegativeExponent(b.to_string())) }
+                let exponent = u32::try_from(b).map_err(|_| IntegerOverflow(Token::Power, a, b))?;
+                Ok(Object::Integer(a.checked_pow(exponent).ok_or(IntegerOverflow(Token::Power, a, b))?))
+            }),
+            Some(|a: f64, b: f64| Object::Float(a.powf(b))),
+            None,
+            None,
+            Some(|a: BigInt, b: BigInt| {
+                if b.is_negative() { bail!(NegativeExponent(b.to_string())) }
+                let exponent = b.to_u32().ok_or(CannotConvertInto(b.to_string(), "exponent".to_string()))?;
+                check_power_bits(&a, exponent)?;
+                Ok(Object::BigInteger(a.pow(exponent)))
+            }),
+            Some(|a: BigDecimal, b: BigDecimal| {
+                if b.is_negative() { bail!(NegativeExponent(b.to_string())) }
+                let exponent = b.to_u32().filter(|_| b.is_integer()).ok_or(CannotConvertInto(b.to_string(), "exponent".to_string()))?;
+                check_power_bits(&a.as_bigint_and_exponent().0, exponent)?;
+                Ok(Object::Decimal(power_decimal(a, exponent)))
+            }),
             true,
         ),
+        Token::SingleAnd => (
+            Some(|a: i128, b: i128| Ok(Object::Integer(a & b))),
+            None,
+            None,
+            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a & b))),
+            None,
+            false,
+        ),
+        Token::BitOr => (
+            Some(|a: i128, b: i128| Ok(Object::Integer(a | b))),
+            None,
+            None,
+            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a | b))),
+            None,
+            false,
+        ),
+        Token::BitXor => (
+            Some(|a: i128, b: i128| Ok(Object::Integer(a ^ b))),
+            None,
+            None,
+            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a ^ b))),
+            None,
+            false,
+        ),
+        Token::ShiftLeft => (
+            Some(|a: i128, b: i128| {
+                let amount = u32::try_from(b).ok().filter(|amount| *amount < i128::BITS).ok_or(ShiftOutOfRange(b.to_string()))?;
+                // Bits which got shifted out, including the sign, do not come back
+                if (a << amount) >> amount != a { bail!(IntegerOverflow(Token::ShiftLeft, a, b)) }
+                Ok(Object::Integer(a << amount))
+            }),
+            None,
+            None,
+            None,
+            Some(|a: BigInt, b: BigInt| {
+                let amount = b.to_u32().ok_or(ShiftOutOfRange(b.to_string()))?;
+                if a.bits() + amount as u64 > MAX_BIG_BITS { bail!(ResultTooLarge(Token::ShiftLeft, b.to_string())) }
+                Ok(Object::BigInteger(a << amount))
+            }),
+            None,
+            false,
+        ),
+        Token::ShiftRight => (
+            Some(|a: i128, b: i128| {
+                let amount = u32::try_from(b).ok().filter(|amount| *amount < i128::BITS).ok_or(ShiftOutOfRange(b.to_string()))?;
+                Ok(Object::Integer(a >> amount))
+            }),
+            None,
+            None,
+            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::BigInteger(a >> b.to_u32().ok_or(ShiftOutOfRange(b.to_string()))?))),
+            None,
+            false,
+        ),
         Token::And => (
             None,
             None,
             None,
             Some(|a: bool, b: bool| Object::Boolean(a && b)),
+            None,
+            None,
             false,
         ),
         Token::Or => (
@@ -396,50 +1022,86 @@ pub fn get_apply_functions(operator: &Token) -> (
             None,
             None,
             Some(|a: bool, b: bool| Object::Boolean(a || b)),
+            None,
+            None,
             false
         ),
         Token::Equal => (
-            Some(|a: i128, b: i128| Object::Boolean(a == b)),
+            Some(|a: i128, b: i128| Ok(Object::Boolean(a == b))),
             Some(|a: f64, b: f64| Object::Boolean(a == b)),
             Some(|a: String, b: String| Object::Boolean(a == b)),
             Some(|a: bool, b: bool| Object::Boolean(a == b)),
+            Some(|a: BigInt, b: BigInt| Ok(Object::Boolean(a == b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Boolean(a == b))),
             false,
         ),
         Token::NotEqual => (
-            Some(|a: i128, b: i128| Object::Boolean(a != b)),
+            Some(|a: i128, b: i128| Ok(Object::Boolean(a != b))),
             Some(|a: f64, b: f64| Object::Boolean(a != b)),
             Some(|a: String, b: String| Object::Boolean(a != b)),
             Some(|a: bool, b: bool| Object::Boolean(a != b)),
+            Some(|a: BigInt, b: BigInt| Ok(Object::Boolean(a != b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Boolean(a != b))),
             false,
         ),
         Token::GreaterThan => (
-            Some(|a: i128, b: i128| Object::Boolean(a > b)),
+            Some(|a: i128, b: i128| Ok(Object::Boolean(a > b))),
             Some(|a: f64, b: f64| Object::Boolean(a > b)),
+            Some(|a: String, b: String| Object::Boolean(a > b)),
             None,
-            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::Boolean(a > b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Boolean(a > b))),
             true,
         ),
         Token::LessThan => (
-            Some(|a: i128, b: i128| Object::Boolean(a < b)),
+            Some(|a: i128, b: i128| Ok(Object::Boolean(a < b))),
             Some(|a: f64, b: f64| Object::Boolean(a < b)),
+            Some(|a: String, b: String| Object::Boolean(a < b)),
             None,
-            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::Boolean(a < b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Boolean(a < b))),
             true,
         ),
         Token::GreaterThanEqual => (
-            Some(|a: i128, b: i128| Object::Boolean(a >= b)),
+            Some(|a: i128, b: i128| Ok(Object::Boolean(a >= b))),
             Some(|a: f64, b: f64| Object::Boolean(a >= b)),
+            Some(|a: String, b: String| Object::Boolean(a >= b)),
             None,
-            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::Boolean(a >= b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Boolean(a >= b))),
             true,
         ),
         Token::LessThanEqual => (
-            Some(|a: i128, b: i128| Object::Boolean(a <= b)),
+            Some(|a: i128, b: i128| Ok(Object::Boolean(a <= b))),
             Some(|a: f64, b: f64| Object::Boolean(a <= b)),
+            Some(|a: String, b: String| Object::Boolean(a <= b)),
             None,
-            None,
+            Some(|a: BigInt, b: BigInt| Ok(Object::Boolean(a <= b))),
+            Some(|a: BigDecimal, b: BigDecimal| Ok(Object::Boolean(a <= b))),
             true,
         ),
         _ => unreachable!(),
     }
-}
\ No newline at end of file
+}
+
+/// Fails if the power of the integer is sure to have more than `MAX_BIG_BITS` bits
+fn check_power_bits(base: &BigInt, exponent: u32) -> anyhow::Result<()> {
+    // Powers of -1, 0 and 1 never grow
+    if base.bits() > 1 && (base.bits() - 1) * exponent as u64 >= MAX_BIG_BITS {
+        bail!(ResultTooLarge(Token::Power, exponent.to_string()))
+    }
+    Ok(())
+}
+
+/// Exponentiation by squaring, decimals only support integral exponents
+fn power_decimal(mut base: BigDecimal, mut exponent: u32) -> BigDecimal {
+    let mut result = BigDecimal::from(1);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = &result * &base;
+        }
+        base = &base * &base;
+        exponent >>= 1;
+    }
+    result
+}
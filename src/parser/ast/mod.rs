@@ -1,2 +1,3 @@
 pub mod statement;
 pub mod expression;
+pub mod pattern;
@@ -0,0 +1,46 @@
+use std::fmt::{Display, Formatter};
+
+/// Left side of a destructuring `let`, which binds the parts of a tuple or array to names
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Identifier(String),
+    Tuple(Vec<Pattern>),
+    // May contain a single rest pattern, which binds the remaining elements as a new array
+    Array(Vec<Pattern>),
+    Rest(String),
+}
+
+impl Pattern {
+    /// Names which get bound by the pattern, in the order they are declared in
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Pattern::Identifier(name) |
+            Pattern::Rest(name) => vec![name],
+            Pattern::Tuple(patterns) |
+            Pattern::Array(patterns) => patterns.iter().flat_map(Pattern::names).collect(),
+        }
+    }
+
+    pub fn names_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Pattern::Identifier(name) |
+            Pattern::Rest(name) => vec![name],
+            Pattern::Tuple(patterns) |
+            Pattern::Array(patterns) => patterns.iter_mut().flat_map(Pattern::names_mut).collect(),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let join = |patterns: &[Pattern]| patterns.iter().map(Pattern::to_string).collect::<Vec<String>>().join(", ");
+
+        match self {
+            Pattern::Identifier(name) => write!(f, "{}", name),
+            Pattern::Tuple(patterns) => write!(f, "({})", join(patterns)),
+            Pattern::Array(patterns) => write!(f, "[{}]", join(patterns)),
+            Pattern::Rest(name) => write!(f, "..{}", name),
+        }
+    }
+}
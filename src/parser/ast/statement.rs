@@ -1,9 +1,12 @@
-use anyhow::bail;
-use crate::error::EvalError::{InvalidType, InvalidTypeButFound};
+use std::sync::Arc;
+use anyhow::{bail, Context};
+use crate::error::EvalError::{CannotDestructure, InvalidType, InvalidTypeButFound};
 use crate::evaluator::environment::Environment;
-use crate::evaluator::object::{Object, OwnerShip};
-use crate::lexer::token::Token;
-use crate::parser::ast::expression::Expression;
+use crate::evaluator::object::{Function, Object, OwnerShip};
+use crate::lexer::token::{Span, Token};
+use crate::parser::ast::expression::{allocate, bind, is_reference, is_type, Expression};
+use crate::parser::ast::pattern::Pattern;
+use crate::resolver::module::Module;
 
 #[derive(Eq, PartialEq)]
 #[derive(Debug)]
@@ -24,12 +27,30 @@ pub enum Statement {
         name: String,
         parameter: Vec<(String, Token)>,
         typee: Token,
-        body: Box<Expression>,
+        // Shared with the functions which get created when the statement is evaluated
+        body: Arc<Expression>,
+    },
+    Destructure {
+        pattern: Pattern,
+        typee: Token,
+        value: Box<Expression>,
+    },
+    Import {
+        path: String,
+        // Exported names with the names they get bound to, without them the exports are only reachable through the namespace
+        names: Option<Vec<(String, String)>>,
+        span: Span,
+        // Loaded by the resolver
+        module: Option<Arc<Module>>,
+    },
+    Export {
+        statement: Box<Statement>,
     },
 }
 
 impl Statement {
     pub fn evaluate(&self, environment: &mut Environment) -> anyhow::Result<(OwnerShip, bool)> {
+        environment.before_statement(self)?;
         match self {
             Statement::Let {
                 name,
@@ -37,18 +58,7 @@ impl Statement {
                 value,
             } => {
                 let (value_ownership, _) = value.evaluate(environment)?;
-                let value = value_ownership.value(environment)?;
-                match value {
-                    Object::Integer(_) => if !typee.equal_variant(&Token::IntegerType) { bail!(InvalidType(typee.clone(), Token::IntegerType)) }
-                    Object::Float(_) => if !typee.equal_variant(&Token::FloatType) { bail!(InvalidType(typee.clone(), Token::FloatType)) }
-                    Object::String(_) => if !typee.equal_variant(&Token::StringType) { bail!(InvalidType(typee.clone(), Token::StringType)) }
-                    Object::Boolean(_) => if !typee.equal_variant(&Token::BooleanType) { bail!(InvalidType(typee.clone(), Token::BooleanType)) }
-                    Object::Array(_) => if !typee.equal_variant(&Token::ArrayType) { bail!(InvalidType(typee.clone(), Token::BooleanType)) }
-                    Object::Error(_) => {}
-                    obj => bail!(InvalidTypeButFound(typee.clone(), obj)),
-                }
-                environment.stack_mut().add(name.to_string(), value_ownership.clone());
-                Ok((value_ownership, false))
+                Ok((declare_variable(name, typee, is_reference(value), value_ownership, environment)?, false))
             }
             Statement::Return {
                 value
@@ -67,13 +77,154 @@ impl Statement {
                 typee,
                 body,
             } => {
-                environment.stack_mut().add(name.clone(), OwnerShip::Instance(Object::Function {
-                   typee: typee.clone(),
-                    parameters: parameter.clone(),
-                    body: body.clone()
-                }));
+                let function = Function::new(name.clone(), parameter.clone(), typee.clone(), body.clone());
+                environment.stack_mut().add(name.clone(), OwnerShip::Instance(Object::Function(Arc::new(function))));
                 Ok((OwnerShip::Instance(Object::Null), false))
             }
+            Statement::Destructure {
+                pattern,
+                typee,
+                value,
+            } => {
+                let (value_ownership, _) = value.evaluate(environment)?;
+                Ok((declare_pattern(pattern, typee, value_ownership, environment)?, false))
+            }
+            Statement::Import {
+                names,
+                module,
+                ..
+            } => {
+                let module = module.as_ref().expect("Imports have to be resolved before they are evaluated!");
+                import(module, names, environment, |module, environment| {
+                    for statement in &module.statements {
+                        environment.collect_garbage_if_needed();
+                        statement.evaluate(environment)?;
+                    }
+                    Ok(())
+                })
+            }
+            Statement::Export {
+                statement,
+            } => statement.evaluate(environment),
         }
     }
-}
\ No newline at end of file
+}
+/// Checks the value of a `let` statement against its declared type and binds it in the current scope
+pub fn declare_variable(
+    name: &str,
+    typee: &Token,
+    reference: bool,
+    value_ownership: OwnerShip,
+    environment: &mut Environment,
+) -> anyhow::Result<OwnerShip> {
+    match value_ownership.value(environment)? {
+        Object::Integer(_) => if !typee.equal_variant(&Token::IntegerType) { bail!(InvalidType(typee.clone(), Token::IntegerType)) }
+        Object::Float(_) => if !typee.equal_variant(&Token::FloatType) { bail!(InvalidType(typee.clone(), Token::FloatType)) }
+        Object::String(_) => if !typee.equal_variant(&Token::StringType) { bail!(InvalidType(typee.clone(), Token::StringType)) }
+        Object::Boolean(_) => if !typee.equal_variant(&Token::BooleanType) { bail!(InvalidType(typee.clone(), Token::BooleanType)) }
+        Object::BigInteger(_) => if !typee.equal_variant(&Token::BigIntegerType) { bail!(InvalidType(typee.clone(), Token::BigIntegerType)) }
+        Object::Decimal(_) => if !typee.equal_variant(&Token::DecimalType) { bail!(InvalidType(typee.clone(), Token::DecimalType)) }
+        Object::Array(_) => if !typee.equal_variant(&Token::ArrayType) { bail!(InvalidType(typee.clone(), Token::BooleanType)) }
+        obj @ (Object::Map(_) | Object::Tuple(_)) => if !is_type(&obj, typee, environment)? { bail!(InvalidTypeButFound(typee.clone(), obj)) }
+        Object::Error(_) => {}
+        obj => bail!(InvalidTypeButFound(typee.clone(), obj)),
+    }
+    let value_ownership = bind(reference, value_ownership, environment)?;
+    environment.stack_mut().add(name.to_string(), value_ownership.clone());
+    Ok(value_ownership)
+}
+
+/// Runs a module the first time it gets imported and binds the imported names to its exports
+pub fn import(
+    module: &Arc<Module>,
+    names: &Option<Vec<(String, String)>>,
+    environment: &mut Environment,
+    run: impl FnOnce(&Module, &mut Environment) -> anyhow::Result<()>,
+) -> anyhow::Result<(OwnerShip, bool)> {
+    if environment.module(&module.path).is_none() {
+        run(module, environment).with_context(|| format!("Cannot run the module {}", module.path.display()))?;
+        environment.add_module(module.clone());
+    }
+
+    for (name, binding) in names.iter().flatten() {
+        let export = module.export(name).expect("Imported names have to be resolved before they are evaluated!");
+        environment.stack_mut().add(binding.clone(), OwnerShip::Reference(export.variable.clone()));
+    }
+    Ok((OwnerShip::Instance(Object::Null), false))
+}
+
+/// Binds the parts of the value of a destructuring `let` to the names of the pattern in the current scope
+pub fn declare_pattern(
+    pattern: &Pattern,
+    typee: &Token,
+    value_ownership: OwnerShip,
+    environment: &mut Environment,
+) -> anyhow::Result<OwnerShip> {
+    let value = OwnerShip::Instance(value_ownership.value(environment)?);
+    bind_pattern(pattern, Some(typee), value.clone(), environment)?;
+    Ok(value)
+}
+
+/// Elements of arrays have no declared type, so their patterns are bound without checking it
+fn bind_pattern(
+    pattern: &Pattern,
+    typee: Option<&Token>,
+    ownership: OwnerShip,
+    environment: &mut Environment,
+) -> anyhow::Result<()> {
+    match pattern {
+        Pattern::Identifier(name) => match typee {
+            Some(typee) => {
+                declare_variable(name, typee, false, ownership, environment)?;
+            }
+            None => {
+                let ownership = bind(false, ownership, environment)?;
+                environment.stack_mut().add(name.clone(), ownership);
+            }
+        }
+        Pattern::Tuple(patterns) => {
+            let values = match ownership.value(environment)? {
+                Object::Tuple(values) if values.len() == patterns.len() => values,
+                obj => bail!(CannotDestructure(pattern.to_string(), obj)),
+            };
+            for (index, (pattern, value)) in patterns.iter().zip(values).enumerate() {
+                let typee = match typee {
+                    Some(Token::TupleType(types)) => Some(&types[index]),
+                    _ => None,
+                };
+                bind_pattern(pattern, typee, value, environment)?;
+            }
+        }
+        Pattern::Array(patterns) => {
+            let values = match ownership.value(environment)? {
+                Object::Array(values) => values,
+                obj => bail!(CannotDestructure(pattern.to_string(), obj)),
+            };
+            let rest = patterns.iter().position(|pattern| matches!(pattern, Pattern::Rest(_)));
+            let fits = match rest {
+                Some(_) => values.len() + 1 >= patterns.len(),
+                None => values.len() == patterns.len(),
+            };
+            if !fits { bail!(CannotDestructure(pattern.to_string(), Object::Array(values))) }
+
+            // The rest takes every element which is not matched by the patterns before or after it
+            let rest_len = values.len() + 1 - patterns.len();
+            let mut values = values.into_iter();
+            for pattern in patterns {
+                match pattern {
+                    Pattern::Rest(name) => {
+                        let mut elements = vec![];
+                        for value in values.by_ref().take(rest_len) {
+                            elements.push(OwnerShip::Heap(allocate(false, value, environment)?));
+                        }
+                        let address = environment.heap_mut().set(OwnerShip::Instance(Object::Array(elements)));
+                        environment.stack_mut().add(name.clone(), OwnerShip::Heap(address));
+                    }
+                    pattern => bind_pattern(pattern, None, values.next().unwrap(), environment)?,
+                }
+            }
+        }
+        Pattern::Rest(_) => unreachable!(),
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::lexer::token::Span;
+
+/// Lines of a statement or block in the source
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug, Default)]
+pub struct Lines {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Layout of the statement as it was written
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug, Default)]
+pub struct StatementLayout {
+    // Position of the first token of the statement
+    pub span: Span,
+    pub lines: Lines,
+    // Expression statements may leave out their semicolon
+    pub semicolon: bool,
+}
+
+/// Name which gets declared by a `let`, a function, a parameter or an import, with the position it is written at
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub name: String,
+    pub span: Span,
+}
+
+/// Positions of the statements, blocks and declared names of a program, in the order they got parsed.
+/// The syntax tree does not keep them, but tools which print the source again or point into it need them.
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Default, Clone)]
+pub struct Layout {
+    pub statements: Vec<StatementLayout>,
+    pub blocks: Vec<Lines>,
+    pub declarations: Vec<Declaration>,
+}
@@ -1,26 +1,58 @@
-use std::iter::Peekable;
-use std::vec::IntoIter;
+use std::sync::Arc;
 use anyhow::bail;
-use crate::error::ParseError::{ExpectedButFound, ExpectedButFoundExpression, ExpectedTokenButFound, RanOutOfTokens, UnexpectedTokenFound};
-use crate::lexer::token::Token;
-use crate::parser::ast::expression::Expression;
+use crate::error::ParseError::{ExpectedButFound, ExpectedButFoundExpression, ExpectedTokenButFound, InvalidFormatSpec, InvalidFormatString, RanOutOfTokens, UnexpectedTokenFound};
+use crate::evaluator::format::FormatSpec;
+use crate::lexer::token::{FormatSegment, Span, Token};
+use crate::lexer::Lexer;
+use crate::parser::ast::expression::{Expression, FormatPart};
+use crate::parser::ast::pattern::Pattern;
 use crate::parser::ast::statement::Statement;
+use crate::parser::layout::{Declaration, Layout, Lines, StatementLayout};
 use crate::parser::precedences::Precedences;
+use crate::parser::token_stream::TokenStream;
 
 pub mod ast;
+pub mod layout;
 pub mod precedences;
+pub mod token_stream;
 
 pub struct Parser {
-    tokens: Peekable<IntoIter<Token>>,
+    tokens: TokenStream,
+    layout: Layout,
 }
 
 impl Parser {
     pub fn from_tokens(tokens: Vec<Token>) -> Self {
+        Self::from_spanned_tokens(tokens.into_iter().map(|token| (token, Span::default())).collect())
+    }
+
+    pub fn from_spanned_tokens(tokens: Vec<(Token, Span)>) -> Self {
         Self {
-            tokens: tokens.into_iter().peekable()
+            tokens: TokenStream::new(tokens),
+            layout: Layout::default(),
         }
     }
 
+    /// Positions of the statements and blocks which got parsed so far
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Position of the last token which got parsed, a parse error is found at it or right after it
+    pub fn span(&self) -> Span {
+        self.tokens.span()
+    }
+
+    /// Line at which the next token starts
+    fn line(&mut self) -> usize {
+        self.tokens.peek_span().map(|span| span.line).unwrap_or(self.tokens.span().line)
+    }
+
+    /// Records the name which was just parsed as a declaration
+    fn declare(&mut self, name: &str) {
+        self.layout.declarations.push(Declaration { name: name.to_string(), span: self.tokens.span() });
+    }
+
     pub fn parse(&mut self) -> anyhow::Result<Vec<Statement>> {
         let mut statements = vec![];
 
@@ -32,27 +64,102 @@ impl Parser {
     }
 
     pub fn parse_statement(&mut self) -> anyhow::Result<Statement> {
-        match self.tokens.peek().unwrap() {
+        let index = self.layout.statements.len();
+        let start = self.line();
+        let span = self.tokens.peek_span().unwrap_or_default();
+        self.layout.statements.push(StatementLayout::default());
+
+        let statement = match self.tokens.peek().ok_or(RanOutOfTokens)? {
             Token::Let => self.parse_let_statement(),
             Token::Function => self.parse_function_statement(),
             Token::Return => self.parse_return_statement(),
+            Token::Import => self.parse_import_statement(),
+            Token::Public => self.parse_export_statement(),
             _ => self.parse_expression_statement(),
-        }
+        }?;
+
+        self.layout.statements[index] = StatementLayout {
+            span,
+            lines: Lines { start, end: self.tokens.span().line },
+            semicolon: self.tokens.previous() == Some(&Token::Semicolon),
+        };
+        Ok(statement)
+    }
+
+    /// Parses `import "path";` and `import { a, b } from "path";`
+    pub fn parse_import_statement(&mut self) -> anyhow::Result<Statement> {
+        self.tokens.next();
+        let span = self.tokens.span();
+
+        let names = match self.tokens.peek() {
+            Some(Token::LBrace) => {
+                self.tokens.next();
+                let mut names = vec![];
+                loop {
+                    match self.tokens.next().ok_or(RanOutOfTokens)? {
+                        Token::Identifier(name) => {
+                            self.declare(&name);
+                            names.push((name.clone(), name))
+                        }
+                        token => bail!(ExpectedButFound("Identifier".to_string(), token)),
+                    }
+                    match self.tokens.next().ok_or(RanOutOfTokens)? {
+                        Token::Comma => {}
+                        Token::RBrace => break,
+                        token => bail!(ExpectedButFound("',' or '}'".to_string(), token)),
+                    }
+                }
+                self.assert_next_token(Token::Identifier("from".to_string()))?;
+                Some(names)
+            }
+            _ => None,
+        };
+
+        let path = match self.tokens.next().ok_or(RanOutOfTokens)? {
+            Token::String(path) => path,
+            token => bail!(ExpectedButFound("Path".to_string(), token)),
+        };
+
+        self.assert_next_token(Token::Semicolon)?;
+
+        Ok(Statement::Import {
+            path,
+            names,
+            span,
+            module: None,
+        })
+    }
+
+    /// Parses a declaration which is marked with `pub`
+    pub fn parse_export_statement(&mut self) -> anyhow::Result<Statement> {
+        self.tokens.next();
+
+        let statement = match self.tokens.peek().ok_or(RanOutOfTokens)? {
+            Token::Let | Token::Function => self.parse_statement()?,
+            token => bail!(ExpectedButFound("Declaration".to_string(), token.clone())),
+        };
+
+        Ok(Statement::Export {
+            statement: Box::new(statement),
+        })
     }
 
     pub fn parse_let_statement(&mut self) -> anyhow::Result<Statement> {
         self.tokens.next();
 
+        if let Some(Token::LParent | Token::LBracket) = self.tokens.peek() {
+            return self.parse_destructure_statement()
+        }
+
         let name = match self.tokens.next().ok_or(RanOutOfTokens)? {
             Token::Identifier(val) => val,
             token => bail!(ExpectedButFound("Identifier".to_string(), token)),
         };
+        self.declare(&name);
 
         self.assert_next_token(Token::Colon)?;
 
-        let typee = self.tokens.next().ok_or(RanOutOfTokens).unwrap();
-
-        Self::assert_type(&typee)?;
+        let typee = self.parse_type()?;
 
         self.assert_next_token(Token::Assign)?;
 
@@ -67,6 +174,79 @@ impl Parser {
         })
     }
 
+    pub fn parse_destructure_statement(&mut self) -> anyhow::Result<Statement> {
+        let pattern = self.parse_pattern()?;
+
+        self.assert_next_token(Token::Colon)?;
+
+        let typee = self.parse_type()?;
+
+        Self::assert_pattern_type(&pattern, &typee)?;
+
+        self.assert_next_token(Token::Assign)?;
+
+        let value = Box::new(self.parse_expression(Precedences::Lowest)?);
+
+        self.assert_next_token(Token::Semicolon)?;
+
+        Ok(Statement::Destructure {
+            pattern,
+            typee,
+            value,
+        })
+    }
+
+    pub fn parse_pattern(&mut self) -> anyhow::Result<Pattern> {
+        match self.tokens.next().ok_or(RanOutOfTokens)? {
+            Token::Identifier(name) => {
+                self.declare(&name);
+                Ok(Pattern::Identifier(name))
+            }
+            Token::LParent => {
+                let mut patterns = vec![self.parse_pattern()?];
+                self.assert_next_token(Token::Comma)?;
+                patterns.push(self.parse_pattern()?);
+
+                while let Some(token) = self.tokens.peek() {
+                    if token.equal_variant(&Token::RParent) { break }
+                    self.assert_next_token(Token::Comma)?;
+                    patterns.push(self.parse_pattern()?);
+                }
+                self.assert_next_token(Token::RParent)?;
+
+                Ok(Pattern::Tuple(patterns))
+            }
+            Token::LBracket => {
+                let mut patterns = vec![];
+
+                while let Some(token) = self.tokens.peek() {
+                    if token.equal_variant(&Token::RBracket) { break }
+                    if !patterns.is_empty() { self.assert_next_token(Token::Comma)?; }
+
+                    if let Some(Token::DotDot) = self.tokens.peek() {
+                        self.tokens.next();
+                        if patterns.iter().any(|pattern| matches!(pattern, Pattern::Rest(_))) {
+                            bail!(UnexpectedTokenFound(Token::DotDot))
+                        }
+                        match self.tokens.next().ok_or(RanOutOfTokens)? {
+                            Token::Identifier(name) => {
+                                self.declare(&name);
+                                patterns.push(Pattern::Rest(name))
+                            }
+                            token => bail!(ExpectedButFound("Identifier".to_string(), token)),
+                        }
+                    } else {
+                        patterns.push(self.parse_pattern()?);
+                    }
+                }
+                self.assert_next_token(Token::RBracket)?;
+
+                Ok(Pattern::Array(patterns))
+            }
+            token => bail!(ExpectedButFound("Pattern".to_string(), token)),
+        }
+    }
+
     pub fn parse_return_statement(&mut self) -> anyhow::Result<Statement> {
         self.tokens.next();
 
@@ -86,6 +266,7 @@ impl Parser {
             Token::Identifier(val) => val,
             token => bail!(ExpectedButFound("Identifier".to_string(), token))
         };
+        self.declare(&name);
 
         self.assert_next_token(Token::LParent)?;
 
@@ -97,9 +278,9 @@ impl Parser {
                 Token::Identifier(val) => val,
                 token => bail!(ExpectedButFound("Identifier".to_string(), token))
             };
+            self.declare(&name);
             self.assert_next_token(Token::Colon)?;
-            let typee = self.tokens.next().ok_or(RanOutOfTokens)?;
-            Self::assert_type(&typee)?;
+            let typee = self.parse_type()?;
             parameter.push((name, typee));
 
             while let Some(token) = self.tokens.peek() {
@@ -111,12 +292,11 @@ impl Parser {
                     Token::Identifier(val) => val,
                     token => bail!(ExpectedButFound("Identifier".to_string(), token))
                 };
+                self.declare(&name);
 
                 self.assert_next_token(Token::Colon)?;
 
-                let typee = self.tokens.next().ok_or(RanOutOfTokens)?;
-
-                Self::assert_type(&typee)?;
+                let typee = self.parse_type()?;
 
                 parameter.push((name, typee));
             }
@@ -125,11 +305,9 @@ impl Parser {
         self.assert_next_token(Token::RParent)?;
 
         self.assert_next_token(Token::Colon)?;
-        let typee = self.tokens.next().ok_or(RanOutOfTokens).unwrap();
-
-        Self::assert_type(&typee)?;
+        let typee = self.parse_type()?;
 
-        let body = Box::new(self.parse_block_expression()?);
+        let body = Arc::new(self.parse_block_expression()?);
 
         Ok(Statement::Function {
             name,
@@ -153,18 +331,24 @@ impl Parser {
 
     pub fn parse_expression(&mut self, precedences: Precedences) -> anyhow::Result<Expression> {
         let mut left_expr = match self.tokens.next().ok_or(RanOutOfTokens)? {
-            Token::Identifier(name) => Expression::Identifier { name },
+            Token::Identifier(name) => self.parse_identifier(name)?,
             Token::Integer(value) => Expression::Integer { value },
             Token::Float(value) => Expression::Float { value },
             Token::String(value) => Expression::String { value },
             Token::Boolean(value) => Expression::Boolean { value },
+            Token::BigInteger(value) => Expression::BigInteger { value },
+            Token::Decimal(value) => Expression::Decimal { value },
             Token::Subtract => self.parse_prefix_expression(Token::Subtract)?,
             Token::Invert => self.parse_prefix_expression(Token::Invert)?,
             Token::SingleAnd => self.parse_prefix_expression(Token::SingleAnd)?,
+            Token::BitNot => self.parse_prefix_expression(Token::BitNot)?,
+            Token::FormatString(segments) => self.parse_format_string(segments)?,
+            Token::InvalidFormatString => bail!(InvalidFormatString(self.tokens.span())),
             Token::LParent => self.parse_grouped_expression()?,
             Token::If => self.parse_if_expression()?,
             Token::While => self.parse_while_expression()?,
             Token::LBracket => self.parse_array_expression()?,
+            Token::LBrace => self.parse_map_expression()?,
             Token::Error => self.parse_error_expression()?,
             token => bail!(UnexpectedTokenFound(token))
         };
@@ -186,6 +370,12 @@ impl Parser {
                 Token::GreaterThanEqual => self.parse_infix_expression(left_expr, Token::GreaterThanEqual),
                 Token::Or => self.parse_infix_expression(left_expr, Token::Or),
                 Token::And => self.parse_infix_expression(left_expr, Token::And),
+                Token::Power => self.parse_infix_expression(left_expr, Token::Power),
+                Token::SingleAnd => self.parse_infix_expression(left_expr, Token::SingleAnd),
+                Token::BitOr => self.parse_infix_expression(left_expr, Token::BitOr),
+                Token::BitXor => self.parse_infix_expression(left_expr, Token::BitXor),
+                Token::ShiftLeft => self.parse_infix_expression(left_expr, Token::ShiftLeft),
+                Token::ShiftRight => self.parse_infix_expression(left_expr, Token::ShiftRight),
                 Token::LParent => self.parse_call_expression(left_expr),
                 Token::Assign => self.parse_assign_expression(left_expr),
                 Token::LBracket => self.parse_access_expression(left_expr),
@@ -198,12 +388,26 @@ impl Parser {
         Ok(left_expr)
     }
 
+    /// Parentheses either group an expression or create a tuple if they contain commas
     pub fn parse_grouped_expression(&mut self) -> anyhow::Result<Expression> {
-        let expr = self.parse_expression(Precedences::Lowest);
+        let expr = self.parse_expression(Precedences::Lowest)?;
+
+        if let Some(&Token::Comma) = self.tokens.peek() {
+            let mut values = vec![Box::new(expr)];
+            while let Some(&Token::Comma) = self.tokens.peek() {
+                self.tokens.next();
+                values.push(Box::new(self.parse_expression(Precedences::Lowest)?));
+            }
+            self.assert_next_token(Token::RParent)?;
+
+            return Ok(Expression::Tuple {
+                values
+            })
+        }
 
         self.assert_next_token(Token::RParent)?;
 
-        return expr;
+        Ok(expr)
     }
 
     pub fn parse_array_expression(&mut self) -> anyhow::Result<Expression> {
@@ -230,6 +434,63 @@ impl Parser {
         })
     }
 
+    /// Braces at the start of an expression always create a map, blocks only follow `if`, `while` and functions
+    /// Names inside a namespace are written as `namespace.name`
+    pub fn parse_identifier(&mut self, name: String) -> anyhow::Result<Expression> {
+        let span = self.tokens.span();
+        if let Some(Token::Dot) = self.tokens.peek() {
+            self.tokens.next();
+            return match self.tokens.next().ok_or(RanOutOfTokens)? {
+                Token::Identifier(member) => Ok(Expression::Identifier { name: format!("{}.{}", name, member), span, slot: None }),
+                token => bail!(ExpectedButFound("Identifier".to_string(), token)),
+            }
+        }
+        Ok(Expression::Identifier { name, span, slot: None })
+    }
+
+    /// Parses the sources of the interpolated values, they get the position of the string they are part of
+    pub fn parse_format_string(&mut self, segments: Vec<FormatSegment>) -> anyhow::Result<Expression> {
+        let span = self.tokens.span();
+        let mut parts = vec![];
+
+        for segment in segments {
+            parts.push(match segment {
+                FormatSegment::Literal(literal) => FormatPart::Literal(literal),
+                FormatSegment::Value { source, spec } => {
+                    let tokens = Lexer::from_string(source).at(span).lex_spanned();
+                    let mut parser = Parser::from_spanned_tokens(tokens);
+                    let value = Box::new(parser.parse_expression(Precedences::Lowest)?);
+                    if let Some(token) = parser.tokens.next() { bail!(UnexpectedTokenFound(token)) }
+
+                    let spec = FormatSpec::parse(&spec).ok_or(InvalidFormatSpec(spec))?;
+                    FormatPart::Value { value, spec }
+                }
+            });
+        }
+
+        Ok(Expression::Format { parts })
+    }
+
+    pub fn parse_map_expression(&mut self) -> anyhow::Result<Expression> {
+        let mut entries = vec![];
+
+        while let Some(token) = self.tokens.peek() {
+            if token.equal_variant(&Token::RBrace) { break }
+            if !entries.is_empty() { self.assert_next_token(Token::Comma)?; }
+
+            let key = Box::new(self.parse_expression(Precedences::Lowest)?);
+            self.assert_next_token(Token::Colon)?;
+            let value = Box::new(self.parse_expression(Precedences::Lowest)?);
+            entries.push((key, value));
+        }
+
+        self.assert_next_token(Token::RBrace)?;
+
+        Ok(Expression::Map {
+            entries
+        })
+    }
+
     pub fn parse_prefix_expression(&mut self, prefix: Token) -> anyhow::Result<Expression> {
         let value = Box::new(self.parse_expression(Precedences::Prefix)?);
         Ok(Expression::Prefix {
@@ -239,7 +500,11 @@ impl Parser {
     }
 
     pub fn parse_infix_expression(&mut self, left: Expression, infix: Token) -> anyhow::Result<Expression> {
-        let precedence = infix.precedence();
+        let precedence = match infix {
+            // Right associative, `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+            Token::Power => Precedences::Prefix,
+            _ => infix.precedence(),
+        };
         let right = self.parse_expression(precedence)?;
         Ok(Expression::Infix {
             left: Box::new(left),
@@ -258,8 +523,8 @@ impl Parser {
     }
 
     pub fn parse_call_expression(&mut self, left: Expression) -> anyhow::Result<Expression> {
-        let name = match left {
-            Expression::Identifier { name } => name,
+        let (name, span) = match left {
+            Expression::Identifier { name, span, .. } => (name, span),
             expr => bail!(ExpectedButFoundExpression("Identifier".to_string(), expr))
         };
 
@@ -279,6 +544,8 @@ impl Parser {
         Ok(Expression::Call {
             name,
             arguments,
+            span,
+            slot: None,
         })
     }
 
@@ -291,8 +558,6 @@ impl Parser {
 
         let consequence = Box::new(self.parse_block_expression()?);
 
-        println!("{:?}", self.tokens.peek());
-
         if let Some(&Token::Else) = self.tokens.peek() {
             self.assert_next_token(Token::Else)?;
             let alternative = Box::new(self.parse_block_expression()?);
@@ -326,6 +591,10 @@ impl Parser {
     }
 
     pub fn parse_block_expression(&mut self) -> anyhow::Result<Expression> {
+        let index = self.layout.blocks.len();
+        let start = self.line();
+        self.layout.blocks.push(Lines::default());
+
         self.assert_next_token(Token::LBrace)?;
 
         let mut statements = vec![];
@@ -337,6 +606,7 @@ impl Parser {
             }
             statements.push(Box::new(self.parse_statement()?));
         }
+        self.layout.blocks[index] = Lines { start, end: self.tokens.span().line };
 
         Ok(Expression::Block {
             statements
@@ -356,13 +626,30 @@ impl Parser {
     }
 
     pub fn parse_access_expression(&mut self, left: Expression) -> anyhow::Result<Expression> {
-        let index = Box::new(self.parse_expression(Precedences::Lowest)?);
+        let start = match self.tokens.peek() {
+            Some(Token::DotDot) => None,
+            _ => Some(Box::new(self.parse_expression(Precedences::Lowest)?)),
+        };
 
+        let Some(Token::DotDot) = self.tokens.peek() else {
+            self.assert_next_token(Token::RBracket)?;
+            return Ok(Expression::Access {
+                source: Box::new(left),
+                index: start.unwrap(),
+            })
+        };
+        self.tokens.next();
+
+        let end = match self.tokens.peek() {
+            Some(Token::RBracket) => None,
+            _ => Some(Box::new(self.parse_expression(Precedences::Lowest)?)),
+        };
         self.assert_next_token(Token::RBracket)?;
 
-        Ok(Expression::Access {
+        Ok(Expression::Slice {
             source: Box::new(left),
-            index
+            start,
+            end,
         })
     }
 
@@ -373,6 +660,41 @@ impl Parser {
         Ok(())
     }
 
+    pub fn parse_type(&mut self) -> anyhow::Result<Token> {
+        let typee = match self.tokens.next().ok_or(RanOutOfTokens)? {
+            Token::Map => {
+                self.assert_next_token(Token::LessThan)?;
+                let key = self.parse_type()?;
+                self.assert_next_token(Token::Comma)?;
+                let value = self.parse_type()?;
+                // Nested types end with `>>`, which is lexed as a shift
+                match self.tokens.peek_mut() {
+                    Some(token @ Token::ShiftRight) => *token = Token::GreaterThan,
+                    _ => self.assert_next_token(Token::GreaterThan)?,
+                }
+                Token::MapType(Box::new(key), Box::new(value))
+            }
+            Token::LParent => {
+                let mut types = vec![self.parse_type()?];
+                self.assert_next_token(Token::Comma)?;
+                types.push(self.parse_type()?);
+
+                while let Some(&Token::Comma) = self.tokens.peek() {
+                    self.tokens.next();
+                    types.push(self.parse_type()?);
+                }
+                self.assert_next_token(Token::RParent)?;
+
+                Token::TupleType(types)
+            }
+            typee => typee,
+        };
+
+        Self::assert_type(&typee)?;
+
+        Ok(typee)
+    }
+
     pub fn assert_type(typee: &Token) -> anyhow::Result<()> {
         match typee {
             Token::IntegerType |
@@ -380,9 +702,28 @@ impl Parser {
             Token::StringType |
             Token::BooleanType |
             Token::NullType |
-            Token::ArrayType => {}
+            Token::ArrayType |
+            Token::BigIntegerType |
+            Token::DecimalType |
+            Token::MapType(..) |
+            Token::TupleType(_) => {}
             token => bail!(ExpectedButFound("Type".to_string(), token.clone()))
         }
         Ok(())
     }
+
+    /// Tuple patterns need a tuple type with as many elements, array patterns need the array type
+    pub fn assert_pattern_type(pattern: &Pattern, typee: &Token) -> anyhow::Result<()> {
+        match (pattern, typee) {
+            (Pattern::Identifier(_), _) => {}
+            (Pattern::Tuple(patterns), Token::TupleType(types)) if patterns.len() == types.len() => {
+                for (pattern, typee) in patterns.iter().zip(types) {
+                    Self::assert_pattern_type(pattern, typee)?;
+                }
+            }
+            (Pattern::Array(_), Token::ArrayType) => {}
+            (pattern, typee) => bail!(ExpectedButFound(format!("Type of {}", pattern), typee.clone())),
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
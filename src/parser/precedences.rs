@@ -8,9 +8,15 @@ pub enum Precedences {
     OrAnd,
     Equals,
     LessGreater,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
     Sum,
     Product,
     Prefix,
+    // Binds tighter than prefixes, so `-2 ** 2` is `-(2 ** 2)`
+    Power,
     Postfix,
     Call,
     Assign,
@@ -28,6 +34,16 @@ impl Token {
             Token::Divide |
             Token::Modular => Precedences::Product,
 
+            Token::Power => Precedences::Power,
+
+            Token::BitOr => Precedences::BitOr,
+            Token::BitXor => Precedences::BitXor,
+            // Only used as infix, as a prefix it always creates a reference
+            Token::SingleAnd => Precedences::BitAnd,
+
+            Token::ShiftLeft |
+            Token::ShiftRight => Precedences::Shift,
+
             Token::Equal |
             Token::NotEqual => Precedences::Equals,
 
@@ -0,0 +1,53 @@
+use std::iter::Peekable;
+use std::vec::IntoIter;
+use crate::lexer::token::{Span, Token};
+
+/// Iterates over the tokens of a program while remembering where each of them was found
+pub struct TokenStream {
+    tokens: Peekable<IntoIter<(Token, Span)>>,
+
+    // Last token returned by next and its span
+    previous: Option<Token>,
+    span: Span,
+}
+
+impl TokenStream {
+    pub fn new(tokens: Vec<(Token, Span)>) -> Self {
+        Self {
+            tokens: tokens.into_iter().peekable(),
+            previous: None,
+            span: Span::default(),
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&Token> {
+        self.tokens.peek().map(|(token, _)| token)
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut Token> {
+        self.tokens.peek_mut().map(|(token, _)| token)
+    }
+
+    pub fn peek_span(&mut self) -> Option<Span> {
+        self.tokens.peek().map(|(_, span)| *span)
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn previous(&self) -> Option<&Token> {
+        self.previous.as_ref()
+    }
+}
+
+impl Iterator for TokenStream {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (token, span) = self.tokens.next()?;
+        self.span = span;
+        self.previous = Some(token.clone());
+        Some(token)
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::bail;
+use crate::error::CommandError::{MissingArgument, UnknownCommand};
+
+/// Names, arguments and descriptions of the commands, listed by `:help`
+pub const COMMANDS: [(&str, &str, &str); 8] = [
+    (":tokens", "<program>", "Prints the tokens of a program"),
+    (":ast", "<program>", "Prints the syntax tree of a program"),
+    (":type", "<program>", "Runs a program and prints the type of its value"),
+    (":env", "", "Lists the variables of the session and the usage of the heap"),
+    (":load", "<file>", "Runs a file in the session"),
+    (":reset", "", "Clears all variables of the session"),
+    (":time", "", "Toggles printing how long each phase of running a program takes"),
+    (":help", "", "Lists the commands"),
+];
+
+/// Input of the REPL which starts with a colon and controls the session instead of being run
+#[derive(Eq, PartialEq)]
+#[derive(Debug)]
+pub enum Command {
+    Tokens(String),
+    Ast(String),
+    Type(String),
+    Env,
+    Load(String),
+    Reset,
+    Time,
+    Help,
+}
+
+impl Command {
+    pub fn is_command(input: &str) -> bool {
+        input.trim_start().starts_with(':')
+    }
+
+    pub fn parse(input: &str) -> anyhow::Result<Command> {
+        let (name, argument) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let argument = argument.trim().to_string();
+        let required = |argument: String, expected: &str| {
+            if argument.is_empty() { bail!(MissingArgument(name.to_string(), expected.to_string())) }
+            Ok(argument)
+        };
+
+        Ok(match name {
+            ":tokens" => Command::Tokens(required(argument, "a program")?),
+            ":ast" => Command::Ast(required(argument, "a program")?),
+            ":type" => Command::Type(required(argument, "a program")?),
+            ":env" => Command::Env,
+            ":load" => Command::Load(required(argument, "a file")?),
+            ":reset" => Command::Reset,
+            ":time" => Command::Time,
+            ":help" => Command::Help,
+            _ => bail!(UnknownCommand(name.to_string())),
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+use rustyline::error::ReadlineError;
+use rustyline::history::FileHistory;
+use rustyline::Editor;
+use crate::error::ParseError;
+use crate::lexer::Lexer;
+use crate::lexer::token::Token;
+use crate::parser::Parser;
+use crate::repl::command::Command;
+use crate::repl::helper::ReplHelper;
+
+const PROMPT: &str = ">> ";
+// Prompt of the lines which continue an incomplete input
+const CONTINUATION_PROMPT: &str = ".. ";
+const HISTORY_FILE: &str = ".p_lang_history";
+
+/// Reads the input of the REPL with line editing, highlighting and completion, inputs can span multiple lines
+pub struct LineReader {
+    editor: Editor<ReplHelper, FileHistory>,
+    // File the history is persisted in, missing if there is no home directory
+    history: Option<PathBuf>,
+}
+
+impl LineReader {
+    pub fn new() -> rustyline::Result<Self> {
+        let mut editor = Editor::new()?;
+        editor.set_helper(Some(ReplHelper::default()));
+        let history = dirs::home_dir().map(|home| home.join(HISTORY_FILE));
+        if let Some(history) = &history {
+            // There is no history yet when the REPL runs for the first time
+            let _ = editor.load_history(history);
+        }
+        Ok(Self { editor, history })
+    }
+
+    /// Reads lines until they form a complete input, Ctrl-C drops the lines read so far.
+    /// Returns nothing once the input ends.
+    pub fn read(&mut self) -> Option<String> {
+        let mut input = String::new();
+        loop {
+            let prompt = if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT };
+            match self.editor.readline(prompt) {
+                Ok(line) => {
+                    if !input.is_empty() { input.push('\n') }
+                    input.push_str(&line);
+                    if input.trim().is_empty() {
+                        input.clear();
+                        continue
+                    }
+                    if is_complete(&input) { break }
+                }
+                Err(ReadlineError::Interrupted) => input.clear(),
+                Err(_) => return None,
+            }
+        }
+
+        let _ = self.editor.add_history_entry(input.as_str());
+        Some(input.trim().to_string())
+    }
+
+    /// Sets the identifiers which get completed, private globals of modules are left out
+    pub fn set_names<'a>(&mut self, names: impl Iterator<Item = &'a str>) {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.names = names.filter(|name| !name.contains("::")).map(str::to_string).collect();
+        }
+    }
+
+    /// Writes the history to its file, so the next session can use it
+    pub fn save_history(&mut self) -> rustyline::Result<()> {
+        match &self.history {
+            Some(history) => self.editor.save_history(history),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether the input is a command or has balanced braces, brackets and parentheses and its last statement is complete
+pub fn is_complete(input: &str) -> bool {
+    if Command::is_command(input) { return true }
+
+    let tokens = Lexer::from_string(input.to_string()).lex_spanned();
+
+    let mut depth = 0;
+    for (token, _) in &tokens {
+        match token {
+            Token::LParent | Token::LBrace | Token::LBracket => depth += 1,
+            Token::RParent | Token::RBrace | Token::RBracket => depth -= 1,
+            _ => {}
+        }
+    }
+    if depth > 0 { return false }
+
+    // Every other error is reported once the input runs
+    match Parser::from_spanned_tokens(tokens).parse() {
+        Ok(_) => true,
+        Err(err) => !matches!(err.downcast_ref::<ParseError>(), Some(ParseError::RanOutOfTokens)),
+    }
+}
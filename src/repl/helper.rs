@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+use colored::{Color, Colorize};
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::{Context, Helper, Hinter, Validator};
+use crate::lexer::{keyword_map, Lexer};
+use crate::lexer::token::{Span, Token};
+use crate::repl::command::COMMANDS;
+
+/// Highlights and completes the line which is being edited in the REPL
+#[derive(Helper, Hinter, Validator, Default)]
+pub struct ReplHelper {
+    // Identifiers which are bound in the session, updated after every input
+    pub names: Vec<String>,
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned(highlight(line))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _forced: bool) -> bool {
+        true
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _context: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(line, pos, &self.names))
+    }
+}
+
+/// Color of the tokens of a class, tokens like operators and identifiers keep the color of the terminal
+fn color(token: &Token) -> Option<Color> {
+    match token {
+        Token::Integer(_) | Token::Float(_) | Token::BigInteger(_) | Token::Decimal(_) | Token::Boolean(_) => Some(Color::Yellow),
+        Token::String(_) | Token::FormatString(_) => Some(Color::Green),
+        Token::Function | Token::Let | Token::If | Token::Else | Token::While | Token::Return | Token::Error |
+        Token::Import | Token::Public => Some(Color::Magenta),
+        Token::IntegerType | Token::FloatType | Token::StringType | Token::BooleanType | Token::NullType |
+        Token::ArrayType | Token::BigIntegerType | Token::DecimalType | Token::Map => Some(Color::Cyan),
+        Token::Illegal | Token::InvalidFormatString => Some(Color::Red),
+        _ => None,
+    }
+}
+
+/// Colors the tokens and comments of the line by their class, the whitespace between them is kept as it is
+pub fn highlight(line: &str) -> String {
+    let chars = line.chars().collect::<Vec<char>>();
+    // Offsets of the first character of every line, spans count lines and columns from one
+    let mut lines = vec![0];
+    lines.extend(chars.iter().enumerate().filter(|(_, char)| **char == '\n').map(|(offset, _)| offset + 1));
+    let offset = |span: Span| (lines[span.line - 1] + span.column - 1).min(chars.len());
+
+    let (tokens, comments) = Lexer::from_string(line.to_string()).lex_with_comments();
+    let mut parts = tokens.iter().map(|(token, span)| (offset(*span), color(token)))
+        .chain(comments.iter().map(|comment| (offset(comment.span), Some(Color::BrightBlack))))
+        .collect::<Vec<(usize, Option<Color>)>>();
+    parts.sort_by_key(|(start, _)| *start);
+
+    let mut highlighted = chars[..parts.first().map_or(chars.len(), |(start, _)| *start)].iter().collect::<String>();
+    for (index, (start, color)) in parts.iter().enumerate() {
+        let end = parts.get(index + 1).map_or(chars.len(), |(start, _)| *start);
+        let text = chars[*start..end].iter().collect::<String>();
+        // Whitespace up to the next part belongs to neither of them
+        let trimmed = text.trim_end();
+        match color {
+            Some(color) => highlighted.push_str(&trimmed.color(*color).to_string()),
+            None => highlighted.push_str(trimmed),
+        }
+        highlighted.push_str(&text[trimmed.len()..]);
+    }
+    highlighted
+}
+
+/// Start of the word in front of the cursor and the keywords, bound identifiers and commands it can be completed to
+pub fn complete(line: &str, pos: usize, names: &[String]) -> (usize, Vec<String>) {
+    let before = &line[..pos];
+    let start = before.rfind(|char: char| !(char.is_ascii_alphanumeric() || char == '_' || char == '.' || char == ':'))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let word = &before[start..];
+    if word.is_empty() { return (pos, vec![]) }
+
+    let mut candidates = if word.starts_with(':') && before.trim_start() == word {
+        COMMANDS.iter().map(|(name, _, _)| name.to_string()).collect::<Vec<String>>()
+    } else {
+        keyword_map().into_keys().chain(names.iter().cloned()).collect::<Vec<String>>()
+    };
+    candidates.retain(|candidate| candidate.starts_with(word));
+    candidates.sort();
+    candidates.dedup();
+    (start, candidates)
+}
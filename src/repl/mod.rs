@@ -1,72 +1,212 @@
+pub mod command;
+pub mod editor;
+pub mod helper;
+
 use std::fs;
-use std::io::{stdin, stdout, Write};
+use std::path::Path;
 use std::time::Instant;
 use colored::Colorize;
+use crate::error::StackTrace;
+use crate::parser::ast::statement::Statement;
 use crate::lexer::Lexer;
+use crate::optimizer::Optimizer;
 use crate::parser::Parser;
+use crate::repl::command::{Command, COMMANDS};
+use crate::repl::editor::LineReader;
+use crate::resolver::Resolver;
 
-use clap::Parser as ClapParser;
 use crate::evaluator::environment::Environment;
-use crate::evaluator::Evaluator;
+use crate::evaluator::object::{Object, OwnerShip};
+use crate::evaluator::printer::print;
+use crate::evaluator::{execute_with, Backend};
+
+/// Settings for running programs
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct Options {
+    /// Backend which evaluates the entered programs
+    #[arg(short, long, value_enum, default_value_t = Backend::Tree)]
+    pub backend: Backend,
+    /// Folds constants and removes dead code before evaluating
+    #[arg(short = 'O', long)]
+    pub optimize: bool,
+    /// Prints the optimized syntax tree, implies --optimize
+    #[arg(long)]
+    pub dump_optimized: bool,
+}
 
-#[derive(ClapParser, Debug)]
-struct Arguments {
-    #[arg(short='p')]
-    path: Option<String>
+/// State of the REPL which is kept between the entered inputs
+#[derive(Default)]
+pub struct Session {
+    pub environment: Environment,
+    pub options: Options,
+    // Prints how long each phase of running a program takes
+    pub timing: bool,
 }
 
-pub fn run_program(program: String, environment: &mut Environment) {
-    let start = Instant::now();
-    let mut lexer = Lexer::from_string(program);
-    let tokens = lexer.lex();
-    println!("{}", format!("{:?}", tokens).bright_blue());
-    println!("{}", format!("Lexing took {:?}", start.elapsed()).magenta());
-
-    let start = Instant::now();
-    let mut parser = Parser::from_tokens(tokens);
-    let ast = parser.parse();
-    if let Ok(ast) = ast {
-        println!("{}", format!("{:#?}", ast).bright_blue());
-        println!("{}", format!("Parsing took {:?}", start.elapsed()).magenta());
-
-        let mut evaluator = Evaluator::new(ast.into_iter());
-        let result = evaluator.evaluate(environment);
-        if let Err(err) = result {
-            eprintln!("{}", format!("{}", err).bright_red());
-            println!("{}", format!("Evaluating took {:?}", start.elapsed()).magenta());
-        } else {
-            println!("{}", format!("Evaluation succeeded!").bright_blue());
-            println!("{}", format!("Parsing took {:?}", start.elapsed()).magenta());
+impl Session {
+    pub fn new(options: Options) -> Self {
+        Self { options, ..Self::default() }
+    }
+
+    /// Runs an entered input, which is either a command or a program
+    pub fn run(&mut self, input: String) {
+        if !Command::is_command(&input) {
+            return self.run_program(input, Path::new("."))
+        }
+
+        match Command::parse(&input) {
+            Ok(command) => self.run_command(command),
+            Err(err) => eprintln!("{}", format!("{}", err).bright_red()),
         }
-    } else if let Err(err) = ast {
-        eprintln!("{}", format!("{}", err).bright_red());
-        println!("{}", format!("Parsing took {:?}", start.elapsed()).magenta());
-        return;
     }
-}
 
-pub fn run_repl() {
-    println!("{}", "Welcome to p_lang! Just enter your commands: ".bright_green());
+    pub fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Tokens(program) => {
+                let tokens = Lexer::from_string(program).lex();
+                println!("{}", format!("{:?}", tokens).bright_blue());
+            }
+            Command::Ast(program) => match parse(program) {
+                Ok(ast) => println!("{}", format!("{:#?}", ast).bright_blue()),
+                Err(err) => eprintln!("{}", format!("{}", err).bright_red()),
+            }
+            Command::Type(program) => match self.evaluate(program) {
+                Ok(object) => println!("{}", object.type_name().bright_blue()),
+                Err(err) => eprintln!("{}", format!("{}", err.root_cause()).bright_red()),
+            }
+            Command::Env => {
+                for name in self.environment.stack().globals() {
+                    let value = self.environment.get(name)
+                        .map(|ownership| ownership.resolve(&self.environment));
+                    match value {
+                        Some(Ok(object)) => println!("{}: {} = {}", name, object.type_name().bright_cyan(), object),
+                        _ => println!("{}", name),
+                    }
+                }
+                let statistics = self.environment.heap().statistics();
+                println!("{}", format!(
+                    "Heap: {} live values, {} allocated, {} freed in {} collections",
+                    statistics.live, statistics.allocated, statistics.freed, statistics.collections,
+                ).magenta());
+            }
+            Command::Load(path) => match fs::read_to_string(&path) {
+                Ok(program) => self.run_program(program, Path::new(&path).parent().unwrap_or(Path::new("."))),
+                Err(err) => eprintln!("{}", format!("Cannot read {}: {}", path, err).bright_red()),
+            }
+            Command::Reset => {
+                self.environment = Environment::default();
+                println!("{}", "Cleared the session!".bright_green());
+            }
+            Command::Time => {
+                self.timing = !self.timing;
+                println!("{}", format!("Timing is {}!", if self.timing { "on" } else { "off" }).bright_green());
+            }
+            Command::Help => {
+                for (name, argument, description) in COMMANDS {
+                    println!("{:<18}{}", format!("{} {}", name, argument).bright_cyan(), description);
+                }
+                println!("{:<18}Leaves the REPL", "exit".bright_cyan());
+            }
+        }
+    }
+
+    /// Runs a program, paths it imports are relative to the given directory
+    pub fn run_program(&mut self, program: String, directory: &Path) {
+        let start = Instant::now();
+        let ast = parse(program);
+        self.time("Parsing", start);
+
+        let mut ast = match ast {
+            Ok(ast) => ast,
+            Err(err) => return eprintln!("{}", format!("{}", err).bright_red()),
+        };
+
+        if self.options.optimize || self.options.dump_optimized {
+            let start = Instant::now();
+            ast = Optimizer::default().optimize(ast);
+            if self.options.dump_optimized {
+                println!("{}", format!("{:#?}", ast).bright_cyan());
+            }
+            self.time("Optimizing", start);
+        }
+
+        let start = Instant::now();
+        if let Err(err) = Resolver::from_environment(&self.environment)
+            .in_directory(directory)
+            .optimized(self.options.optimize || self.options.dump_optimized)
+            .resolve(&mut ast) {
+            return eprintln!("{}", format!("{}", err).bright_red());
+        }
+        self.time("Resolving", start);
+
+        let start = Instant::now();
+        if let Err(err) = execute_with(ast, self.options.backend, &mut self.environment, &mut show) {
+            eprintln!("{}", format!("{}", err.root_cause()).bright_red());
+            if let Some(trace) = err.downcast_ref::<StackTrace>() {
+                eprintln!("{}", trace.calls().red());
+            }
+        }
+        self.time("Evaluating", start);
+    }
 
-    let args = Arguments::parse();
+    /// Runs a program on a copy of the environment and returns the value of its last statement, the session is left unchanged
+    fn evaluate(&self, program: String) -> anyhow::Result<Object> {
+        let mut ast = parse(program)?;
+        Resolver::from_environment(&self.environment).resolve(&mut ast)?;
 
-    let mut environment = Environment::default();
+        let mut environment = self.environment.clone();
+        let mut value = Object::Null;
+        for statement in ast {
+            environment.release_all();
+            let (ownership, _) = statement.evaluate(&mut environment)?;
+            value = ownership.resolve(&environment)?;
+        }
+        Ok(value)
+    }
 
-    if args.path.is_some() {
-        run_program(fs::read_to_string(args.path.unwrap()).unwrap(), &mut environment)
+    fn time(&self, phase: &str, start: Instant) {
+        if self.timing {
+            println!("{}", format!("{} took {:?}", phase, start.elapsed()).magenta());
+        }
     }
+}
 
-    loop {
-        let program = read_from_stdin(">> ");
-        if &program == &"exit" { break }
-        run_program(program, &mut environment);
+/// Prints the value of an entered expression, unless there is nothing to show
+fn show(ownership: &OwnerShip, environment: &Environment) {
+    match ownership.value(environment) {
+        Ok(Object::Null) => {}
+        Ok(_) => match print(ownership, environment) {
+            Ok(text) => println!("{}", text),
+            Err(err) => eprintln!("{}", format!("{}", err).bright_red()),
+        }
+        Err(err) => eprintln!("{}", format!("{}", err).bright_red()),
     }
 }
 
-pub fn read_from_stdin(prompt: &str) -> String {
-    let mut buf = String::new();
-    print!("{prompt}");
-    stdout().lock().flush().unwrap();
-    stdin().read_line(&mut buf).unwrap();
-    buf.trim().to_string()
-}
\ No newline at end of file
+fn parse(program: String) -> anyhow::Result<Vec<Statement>> {
+    let tokens = Lexer::from_string(program).lex_spanned();
+    Parser::from_spanned_tokens(tokens).parse()
+}
+
+/// Runs the file at the path, if there is one, and then reads programs from the terminal
+pub fn run_repl(path: Option<String>, options: Options) {
+    println!("{}", "Welcome to p_lang! Just enter your commands or :help: ".bright_green());
+
+    let mut session = Session::new(options);
+
+    if let Some(path) = path {
+        session.run_command(Command::Load(path));
+    }
+
+    let mut reader = LineReader::new().expect("Cannot read from the terminal!");
+    reader.set_names(session.environment.stack().globals());
+    while let Some(input) = reader.read() {
+        if input == "exit" { break }
+        session.run(input);
+        reader.set_names(session.environment.stack().globals());
+    }
+
+    if let Err(err) = reader.save_history() {
+        eprintln!("{}", format!("Cannot save the history: {}", err).bright_red());
+    }
+}
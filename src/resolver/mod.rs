@@ -0,0 +1,433 @@
+pub mod module;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use anyhow::bail;
+use crate::error::ResolveError::{ImportCycle, ModuleNotFound, NestedImport, NotExported, UnknownIdentifier};
+use crate::evaluator::builtins::builtin;
+use crate::evaluator::environment::{Environment, Variable};
+use crate::lexer::token::Span;
+use crate::optimizer::Optimizer;
+use crate::parser::ast::expression::{Expression, FormatPart};
+use crate::parser::ast::statement::Statement;
+use crate::resolver::module::{qualify, Export, Module};
+
+/// Position of a variable relative to the scope it is used in
+#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct Slot {
+    // Amount of functions between the usage and the declaration, variables of enclosing functions
+    // are found by following the scopes the functions were declared in
+    pub hops: usize,
+    // Amount of scopes between the usage, or the declaration of the last function, and the declaration
+    pub depth: usize,
+    // Position of the variable inside the scope it is declared in
+    pub index: usize,
+}
+
+#[derive(Default, Clone)]
+struct Scope {
+    slots: HashMap<String, usize>,
+    len: usize,
+}
+
+impl Scope {
+    fn declare(&mut self, name: &str) {
+        self.slots.insert(name.to_string(), self.len);
+        self.len += 1;
+    }
+}
+
+/// Binds every identifier of a program to the slot of its declaration before the program gets evaluated.
+/// Functions can see their own variables, the variables around their declaration and the global scope, but not the variables of their caller.
+/// Imported modules get loaded and resolved as well, their globals are placed in the global scope when the import runs.
+pub struct Resolver {
+    globals: Scope,
+    // Global scope including all top level declarations of the program, functions may refer to them before they are declared
+    hoisted: Scope,
+    // Scopes of the function or top level block which is currently being resolved
+    scopes: Vec<Scope>,
+    // Scopes of every function around the one which is being resolved, as they were when the next function got declared
+    enclosing: Vec<Vec<Scope>>,
+    function_depth: usize,
+
+    // Directory which imported paths are relative to
+    directory: PathBuf,
+    // Namespace of the module which is being resolved, its globals get qualified names
+    namespace: Option<String>,
+    // Qualified names of the globals of the module, by the names they are declared with
+    qualified: HashMap<String, String>,
+    // Whether the declaration which is being resolved is marked with `pub`
+    public: bool,
+    exports: Vec<Export>,
+    // Modules which already ran or get loaded by the program, by their canonical path
+    modules: HashMap<PathBuf, Arc<Module>>,
+    // Modules which are currently being resolved, to detect import cycles
+    loading: Vec<PathBuf>,
+    // Whether loaded modules get optimized like the program which imports them
+    optimize: bool,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self {
+            globals: Scope::default(),
+            hoisted: Scope::default(),
+            scopes: vec![],
+            enclosing: vec![],
+            function_depth: 0,
+            directory: PathBuf::from("."),
+            namespace: None,
+            qualified: HashMap::new(),
+            public: false,
+            exports: vec![],
+            modules: HashMap::new(),
+            loading: vec![],
+            optimize: false,
+        }
+    }
+}
+
+impl Resolver {
+    /// Creates a resolver that knows the global variables and modules of the environment
+    pub fn from_environment(environment: &Environment) -> Self {
+        let mut globals = Scope::default();
+        for name in environment.stack().globals() {
+            globals.declare(name);
+        }
+
+        Self {
+            globals,
+            modules: environment.modules().map(|module| (module.path.clone(), module.clone())).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Resolves imported paths relative to the given directory instead of the working directory
+    pub fn in_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = directory.into();
+        self
+    }
+
+    /// Optimizes the modules before they get resolved, see `Optimizer`
+    pub fn optimized(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    pub fn resolve(&mut self, statements: &mut [Statement]) -> anyhow::Result<()> {
+        self.hoisted = self.globals.clone();
+        for statement in statements.iter() {
+            self.hoist(statement, false)?;
+        }
+
+        for statement in statements {
+            self.resolve_statement(statement)?;
+        }
+
+        Ok(())
+    }
+
+    fn hoist(&mut self, statement: &Statement, public: bool) -> anyhow::Result<()> {
+        let names = match statement {
+            Statement::Let { name, .. } |
+            Statement::Function { name, .. } => vec![name.as_str()],
+            Statement::Destructure { pattern, .. } => pattern.names(),
+            Statement::Export { statement } => return self.hoist(statement, true),
+            Statement::Import { path, names, span, .. } => {
+                let module = self.load(path, *span, self.hoisted.len)?;
+                let mut hoisted = std::mem::take(&mut self.hoisted);
+                let result = self.bind_module(&mut hoisted, &module, names.as_deref().unwrap_or_default(), *span);
+                self.hoisted = hoisted;
+                return result
+            }
+            _ => vec![],
+        };
+
+        for name in names {
+            self.hoisted.declare(name);
+            if let Some(namespace) = &self.namespace {
+                self.qualified.insert(name.to_string(), qualify(namespace, name, public));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_statement(&mut self, statement: &mut Statement) -> anyhow::Result<()> {
+        match statement {
+            Statement::Let { name, value, .. } => {
+                self.resolve_expression(value)?;
+                self.declare(name);
+            }
+            Statement::Destructure { pattern, value, .. } => {
+                self.resolve_expression(value)?;
+                for name in pattern.names_mut() {
+                    self.declare(name);
+                }
+            }
+            Statement::Return { value } |
+            Statement::Expression { value } => self.resolve_expression(value)?,
+            Statement::Import { path, names, span, module } => {
+                if self.function_depth > 0 || !self.scopes.is_empty() { bail!(NestedImport(*span)) }
+
+                let loaded = self.load(path, *span, self.globals.len)?;
+                let mut globals = std::mem::take(&mut self.globals);
+                let result = self.bind_module(&mut globals, &loaded, names.as_deref().unwrap_or_default(), *span);
+                self.globals = globals;
+                result?;
+
+                for (_, binding) in names.iter_mut().flatten() {
+                    self.rename(binding);
+                }
+                *module = Some(loaded);
+            }
+            Statement::Export { statement } => {
+                self.public = true;
+                let result = self.resolve_statement(statement);
+                self.public = false;
+                result?;
+            }
+            Statement::Function { name, parameter, body, .. } => {
+                self.declare(name);
+
+                let mut scope = Scope::default();
+                for (name, _) in parameter.iter() {
+                    scope.declare(name);
+                }
+
+                let outer = std::mem::replace(&mut self.scopes, vec![scope]);
+                self.enclosing.push(outer);
+                self.function_depth += 1;
+                let result = self.resolve_expression(Arc::make_mut(body));
+                self.function_depth -= 1;
+                self.scopes = self.enclosing.pop().unwrap();
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_expression(&mut self, expression: &mut Expression) -> anyhow::Result<()> {
+        match expression {
+            Expression::Identifier { name, span, slot } => {
+                *slot = self.lookup(name, *span)?;
+                self.qualify(name, *slot);
+            }
+            Expression::Integer { .. } |
+            Expression::Float { .. } |
+            Expression::String { .. } |
+            Expression::Boolean { .. } |
+            Expression::BigInteger { .. } |
+            Expression::Decimal { .. } => {}
+            Expression::Prefix { value, .. } |
+            Expression::Error { value } => self.resolve_expression(value)?,
+            Expression::Infix { left, right, .. } => {
+                self.resolve_expression(left)?;
+                self.resolve_expression(right)?;
+            }
+            Expression::If { condition, consequence, alternative } => {
+                self.resolve_expression(condition)?;
+                self.resolve_scope(consequence)?;
+                if let Some(alternative) = alternative {
+                    self.resolve_scope(alternative)?;
+                }
+            }
+            Expression::While { condition, consequence } => {
+                self.resolve_expression(condition)?;
+                self.resolve_scope(consequence)?;
+            }
+            Expression::Call { name, arguments, span, slot } => {
+                *slot = match self.lookup(name, *span) {
+                    Ok(found) => {
+                        self.qualify(name, found);
+                        found
+                    }
+                    // Builtins are found by name when they get called
+                    Err(_) if builtin(name).is_some() => None,
+                    Err(err) => return Err(err),
+                };
+                for argument in arguments {
+                    self.resolve_expression(argument)?;
+                }
+            }
+            Expression::Assign { assign_to, value } => {
+                self.resolve_expression(assign_to)?;
+                self.resolve_expression(value)?;
+            }
+            Expression::Array { values } => {
+                for value in values {
+                    self.resolve_expression(value)?;
+                }
+            }
+            Expression::Tuple { values } => {
+                for value in values {
+                    self.resolve_expression(value)?;
+                }
+            }
+            Expression::Map { entries } => {
+                for (key, value) in entries {
+                    self.resolve_expression(key)?;
+                    self.resolve_expression(value)?;
+                }
+            }
+            Expression::Block { statements } => {
+                for statement in statements {
+                    self.resolve_statement(statement)?;
+                }
+            }
+            Expression::Access { source, index } => {
+                self.resolve_expression(source)?;
+                self.resolve_expression(index)?;
+            }
+            Expression::Format { parts } => {
+                for part in parts {
+                    if let FormatPart::Value { value, .. } = part {
+                        self.resolve_expression(value)?;
+                    }
+                }
+            }
+            Expression::Slice { source, start, end } => {
+                self.resolve_expression(source)?;
+                for bound in [start, end].into_iter().flatten() {
+                    self.resolve_expression(bound)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_scope(&mut self, expression: &mut Expression) -> anyhow::Result<()> {
+        self.scopes.push(Scope::default());
+        let result = self.resolve_expression(expression);
+        self.scopes.pop();
+        result
+    }
+
+    /// Declares a name in the current scope, globals of modules get renamed to their qualified names
+    fn declare(&mut self, name: &mut String) {
+        let Some(scope) = self.scopes.last_mut() else {
+            let index = self.globals.len;
+            self.globals.declare(name);
+            if self.public && self.namespace.is_some() {
+                let variable = Variable { name: self.qualified[name.as_str()].clone(), index };
+                self.exports.push(Export { name: name.clone(), variable });
+            }
+            self.rename(name);
+            return
+        };
+        scope.declare(name);
+    }
+
+    fn rename(&self, name: &mut String) {
+        if let Some(qualified) = self.qualified.get(name.as_str()) {
+            *name = qualified.clone();
+        }
+    }
+
+    /// Renames a usage of a global of the module which is being resolved
+    fn qualify(&self, name: &mut String, slot: Option<Slot>) {
+        if slot.is_some_and(|slot| slot.hops == 0 && slot.depth == self.scopes.len()) {
+            self.rename(name);
+        }
+    }
+
+    /// Loads and resolves an imported module, unless it is already loaded. Its globals start at the given index.
+    fn load(&mut self, path: &str, span: Span, base: usize) -> anyhow::Result<Arc<Module>> {
+        let located = module::locate(&self.directory, path).ok_or(ModuleNotFound(path.to_string(), span))?;
+        if let Some(module) = self.modules.get(&located) {
+            return Ok(module.clone())
+        }
+        if let Some(start) = self.loading.iter().position(|loading| loading == &located) {
+            let cycle = self.loading[start..].iter().chain([&located])
+                .map(|path| path.display().to_string())
+                .collect::<Vec<String>>();
+            bail!(ImportCycle(cycle.join(" -> ")))
+        }
+
+        let mut statements = module::parse(&located)?;
+        if self.optimize {
+            statements = Optimizer::default().optimize(statements);
+        }
+        let namespace = located.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+
+        let mut resolver = Resolver {
+            globals: Scope { slots: HashMap::new(), len: base },
+            directory: located.parent().map(PathBuf::from).unwrap_or_default(),
+            namespace: Some(namespace.clone()),
+            modules: std::mem::take(&mut self.modules),
+            loading: std::mem::take(&mut self.loading),
+            optimize: self.optimize,
+            ..Resolver::default()
+        };
+        resolver.loading.push(located.clone());
+        let result = resolver.resolve(&mut statements);
+        resolver.loading.pop();
+        self.modules = std::mem::take(&mut resolver.modules);
+        self.loading = std::mem::take(&mut resolver.loading);
+        result?;
+
+        let module = Arc::new(Module {
+            path: located.clone(),
+            namespace,
+            statements,
+            base,
+            size: resolver.globals.len - base,
+            exports: resolver.exports,
+        });
+        self.modules.insert(located, module.clone());
+        Ok(module)
+    }
+
+    /// Makes the globals of a module and the imported names known in the scope. The module only takes up space
+    /// when it runs for the first time, which is the import its globals start at.
+    fn bind_module(&mut self, scope: &mut Scope, module: &Module, names: &[(String, String)], span: Span) -> anyhow::Result<()> {
+        if module.base == scope.len {
+            scope.len += module.size;
+        }
+        for export in &module.exports {
+            scope.slots.insert(format!("{}.{}", module.namespace, export.name), export.variable.index);
+        }
+
+        for (name, binding) in names {
+            if module.export(name).is_none() {
+                bail!(NotExported(name.clone(), module.path.display().to_string(), span))
+            }
+            scope.declare(binding);
+            if let Some(namespace) = &self.namespace {
+                self.qualified.insert(binding.clone(), qualify(namespace, binding, false));
+            }
+        }
+        Ok(())
+    }
+
+    /// Slot of the declaration of the name, the variables of enclosing functions are counted from the scope
+    /// the function was declared in, since that is where its call finds them
+    fn lookup(&self, name: &str, span: Span) -> anyhow::Result<Option<Slot>> {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if let Some(&index) = scope.slots.get(name) {
+                return Ok(Some(Slot { hops: 0, depth, index }));
+            }
+        }
+        for (hops, scopes) in self.enclosing.iter().rev().enumerate() {
+            for (depth, scope) in scopes.iter().rev().enumerate() {
+                if let Some(&index) = scope.slots.get(name) {
+                    return Ok(Some(Slot { hops: hops + 1, depth, index }));
+                }
+            }
+        }
+
+        let depth = self.scopes.len();
+        if let Some(&index) = self.globals.slots.get(name) {
+            return Ok(Some(Slot { hops: 0, depth, index }));
+        }
+        if self.function_depth > 0 {
+            if let Some(&index) = self.hoisted.slots.get(name) {
+                return Ok(Some(Slot { hops: 0, depth, index }));
+            }
+        }
+
+        bail!(UnknownIdentifier(name.to_string(), span))
+    }
+}
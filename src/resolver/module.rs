@@ -0,0 +1,63 @@
+use std::path::{Path, PathBuf};
+use anyhow::Context;
+use crate::evaluator::environment::Variable;
+use crate::lexer::Lexer;
+use crate::parser::ast::statement::Statement;
+use crate::parser::Parser;
+
+// Extension which gets added to imported paths without one
+const MODULE_EXTENSION: &str = "p";
+
+/// File which got imported by a program. Its globals live in the global scope under qualified names,
+/// `namespace.name` for exported globals and `namespace::name` for private ones, which cannot be written in the source.
+#[derive(Eq, PartialEq)]
+#[derive(Debug)]
+pub struct Module {
+    // Canonical path of the file, modules are cached by it
+    pub path: PathBuf,
+    pub namespace: String,
+    pub statements: Vec<Statement>,
+    // Index of the first global of the module and the amount of globals it declares, including the modules it imports
+    pub base: usize,
+    pub size: usize,
+    pub exports: Vec<Export>,
+}
+
+/// Global which is marked with `pub` inside a module
+#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub name: String,
+    pub variable: Variable,
+}
+
+impl Module {
+    pub fn export(&self, name: &str) -> Option<&Export> {
+        self.exports.iter().find(|export| export.name == name)
+    }
+}
+
+/// Name of a global of the module with the given namespace, like it is stored on the stack
+pub fn qualify(namespace: &str, name: &str, public: bool) -> String {
+    if public {
+        format!("{}.{}", namespace, name)
+    } else {
+        format!("{}::{}", namespace, name)
+    }
+}
+
+/// Canonical path of an imported file, relative paths start at the directory of the importing file
+pub fn locate(directory: &Path, path: &str) -> Option<PathBuf> {
+    let mut path = directory.join(path);
+    if path.extension().is_none() {
+        path.set_extension(MODULE_EXTENSION);
+    }
+    path.canonicalize().ok()
+}
+
+/// Lexes and parses the file of a module
+pub fn parse(path: &Path) -> anyhow::Result<Vec<Statement>> {
+    let tokens = Lexer::from_path(path)?.lex_spanned();
+    Parser::from_spanned_tokens(tokens).parse()
+        .with_context(|| format!("Cannot parse the module {}", path.display()))
+}
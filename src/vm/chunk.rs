@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use crate::evaluator::format::FormatSpec;
+use crate::evaluator::object::{Function, Object};
+use crate::lexer::token::{Span, Token};
+use crate::parser::ast::pattern::Pattern;
+use crate::resolver::module::Module;
+use crate::resolver::Slot;
+
+/// Operation of the virtual machine. Operands are indices into the tables of the chunk,
+/// values are passed on the operand stack of the machine.
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Constant(usize),
+    // Literal which could not be converted while compiling, fails with the same error as the evaluator
+    Literal { value: usize, typee: Token },
+    // Reference to a variable
+    Load { name: usize, slot: Option<Slot> },
+    // Copy of the value of a variable
+    LoadValue { name: usize, slot: Option<Slot> },
+    // Ownership which is stored inside a variable
+    LoadStored { name: usize, slot: Option<Slot> },
+    LoadFunction { name: usize, slot: Option<Slot>, arguments: usize },
+    // Replaces the top value with a copy of the object it refers to
+    Value,
+    Prefix(Token),
+    Infix(Token),
+    // Replaces the left operand of `&&` or `||` with the result and jumps if it decides it
+    ShortCircuit { operation: Token, end: usize },
+    Let { name: usize, typee: Token, reference: bool },
+    Destructure { pattern: Pattern, typee: Token },
+    Function { name: usize, function: usize },
+    // Runs the module unless it already ran and binds the imported names
+    Import { module: Arc<Module>, names: Option<Vec<(String, String)>> },
+    Call { name: usize, arguments: usize },
+    Error,
+    // Turns the target of an assignment into its location
+    Target,
+    // Turns the collection and index of an access into the location an assignment changes
+    TargetAccess,
+    Assign { reference: bool },
+    Allocate { reference: bool },
+    Array(usize),
+    Map(usize),
+    // Replaces the top value with the ownership it gets stored as
+    Bind { reference: bool },
+    Tuple(usize),
+    // Bounds which are missing in the source are not on the stack
+    Slice { start: bool, end: bool },
+    // Pieces of an interpolated string stored in the chunk, the formatted values are on the stack
+    Format { pieces: usize, values: usize },
+    // Replaces the top value with the array or map it has to be
+    CheckCollection,
+    Access,
+    Return,
+    ClearReturn,
+    Jump(usize),
+    JumpIfFalse(usize),
+    JumpIfReturning(usize),
+    CreateScope,
+    DropScope,
+    Pop,
+    // Point at which the garbage collector may run
+    SafePoint,
+}
+
+/// Literal text or the spec of a formatted value inside an interpolated string
+#[derive(Debug, Clone)]
+pub enum Piece {
+    Literal(String),
+    Value(FormatSpec),
+}
+
+/// Compiled code of a top level statement or a function body
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    // Source position of every instruction, unknown positions are zero
+    pub spans: Vec<Span>,
+    pub constants: Vec<Object>,
+    pub names: Vec<String>,
+    pub formats: Vec<Vec<Piece>>,
+    pub functions: Vec<Arc<Function>>,
+    // Whether the chunk is a top level expression statement, whose value gets passed to the output
+    pub expression: bool,
+}
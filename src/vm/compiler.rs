@@ -0,0 +1,301 @@
+use std::sync::Arc;
+use crate::evaluator::object::{Function, Object};
+use crate::lexer::token::{Span, Token};
+use crate::parser::ast::expression::{convert_number, is_reference, Expression, FormatPart};
+use crate::parser::ast::statement::Statement;
+use crate::vm::chunk::{Chunk, Instruction, Piece};
+
+/// Translates statements into chunks of bytecode for the virtual machine
+#[derive(Default)]
+pub struct Compiler {
+    chunk: Chunk,
+    // Position of the last identifier or call, instructions are attributed to it
+    span: Span,
+}
+
+impl Compiler {
+    /// Compiles every top level statement into its own chunk, so they run one after another like in the evaluator
+    pub fn compile(statements: &[Statement]) -> Vec<Chunk> {
+        statements.iter().map(|statement| {
+            let mut compiler = Compiler::default();
+            compiler.statement(statement);
+            compiler.chunk.expression = matches!(statement, Statement::Expression { .. });
+            compiler.chunk
+        }).collect()
+    }
+
+    pub fn compile_function(body: &Expression) -> Chunk {
+        let mut compiler = Compiler::default();
+        compiler.expression(body);
+        compiler.chunk
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let { name, typee, value } => {
+                self.value(value);
+                let instruction = Instruction::Let { name: self.name(name), typee: typee.clone(), reference: is_reference(value) };
+                self.emit(instruction);
+            }
+            Statement::Destructure { pattern, typee, value } => {
+                self.value(value);
+                self.emit(Instruction::Destructure { pattern: pattern.clone(), typee: typee.clone() });
+            }
+            Statement::Return { value } => {
+                self.expression(value);
+                self.emit(Instruction::Return);
+            }
+            Statement::Expression { value } => {
+                self.expression(value);
+            }
+            Statement::Function { name, parameter, typee, body } => {
+                self.chunk.functions.push(Arc::new(Function::new(name.clone(), parameter.clone(), typee.clone(), body.clone())));
+                let instruction = Instruction::Function { name: self.name(name), function: self.chunk.functions.len() - 1 };
+                self.emit(instruction);
+            }
+            Statement::Import { names, module, .. } => {
+                let module = module.clone().expect("Imports have to be resolved before they are compiled!");
+                self.emit(Instruction::Import { module, names: names.clone() });
+            }
+            Statement::Export { statement } => self.statement(statement),
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Identifier { name, span, slot } => {
+                self.span = *span;
+                let instruction = Instruction::Load { name: self.name(name), slot: *slot };
+                self.emit(instruction);
+            }
+            Expression::Integer { value } => self.number(value, Token::IntegerType),
+            Expression::Float { value } => self.number(value, Token::FloatType),
+            Expression::BigInteger { value } => self.number(value, Token::BigIntegerType),
+            Expression::Decimal { value } => self.number(value, Token::DecimalType),
+            Expression::String { value } => self.constant(Object::String(value.clone())),
+            Expression::Boolean { value } => self.constant(Object::Boolean(value == "true")),
+            Expression::Prefix { prefix, value } => {
+                self.value(value);
+                self.emit(Instruction::Prefix(prefix.clone()));
+            }
+            Expression::Infix { left, operation, right } => {
+                // The left side is read before the right side can change it
+                self.read(left);
+                let end = matches!(operation, Token::And | Token::Or)
+                    .then(|| self.emit(Instruction::ShortCircuit { operation: operation.clone(), end: 0 }));
+                self.read(right);
+                self.emit(Instruction::Infix(operation.clone()));
+                if let Some(end) = end { self.patch(end) }
+            }
+            Expression::If { condition, consequence, alternative } => {
+                self.read(condition);
+                let otherwise = self.emit(Instruction::JumpIfFalse(0));
+                self.scoped(consequence);
+                let end = self.emit(Instruction::Jump(0));
+                self.patch(otherwise);
+                match alternative {
+                    Some(alternative) => self.scoped(alternative),
+                    None => self.constant(Object::Null),
+                }
+                self.patch(end);
+            }
+            Expression::While { condition, consequence } => {
+                self.constant(Object::Null);
+                self.read(condition);
+                let mut exits = vec![self.emit(Instruction::JumpIfFalse(0))];
+
+                let start = self.chunk.code.len();
+                self.emit(Instruction::Pop);
+                self.scoped(consequence);
+                exits.push(self.emit(Instruction::JumpIfReturning(0)));
+                self.read(condition);
+                exits.push(self.emit(Instruction::JumpIfFalse(0)));
+                self.emit(Instruction::Jump(start));
+
+                for exit in exits {
+                    self.patch(exit);
+                }
+            }
+            Expression::Call { name, arguments, span, slot } => {
+                self.span = *span;
+                let instruction = Instruction::LoadFunction { name: self.name(name), slot: *slot, arguments: arguments.len() };
+                self.emit(instruction);
+                for argument in arguments {
+                    self.read(argument);
+                }
+                // The frame of the call points at its name, not at the last argument
+                self.span = *span;
+                let instruction = Instruction::Call { name: self.name(name), arguments: arguments.len() };
+                self.emit(instruction);
+            }
+            Expression::Error { value } => {
+                self.read(value);
+                self.emit(Instruction::Error);
+            }
+            Expression::Assign { assign_to, value } => {
+                match assign_to.as_ref() {
+                    // Entries of maps can be assigned before they exist
+                    Expression::Access { source, index } => {
+                        self.collection(source);
+                        self.read(index);
+                        self.emit(Instruction::TargetAccess);
+                    }
+                    _ => {
+                        self.value(assign_to);
+                        self.emit(Instruction::Target);
+                    }
+                }
+                self.value(value);
+                self.emit(Instruction::Assign { reference: is_reference(value) });
+            }
+            Expression::Array { values } => {
+                for value in values {
+                    self.value(value);
+                    self.emit(Instruction::Allocate { reference: is_reference(value) });
+                }
+                self.emit(Instruction::Array(values.len()));
+            }
+            Expression::Tuple { values } => {
+                for value in values {
+                    self.value(value);
+                    self.emit(Instruction::Bind { reference: is_reference(value) });
+                }
+                self.emit(Instruction::Tuple(values.len()));
+            }
+            Expression::Map { entries } => {
+                for (key, value) in entries {
+                    self.read(key);
+                    self.value(value);
+                    self.emit(Instruction::Allocate { reference: is_reference(value) });
+                }
+                self.emit(Instruction::Map(entries.len()));
+            }
+            Expression::Block { statements } => {
+                self.constant(Object::Null);
+                let mut exits = vec![];
+                for statement in statements {
+                    self.emit(Instruction::Pop);
+                    self.emit(Instruction::SafePoint);
+                    self.statement(statement);
+                    exits.push(self.emit(Instruction::JumpIfReturning(0)));
+                }
+                for exit in exits {
+                    self.patch(exit);
+                }
+            }
+            Expression::Access { source, index } => {
+                self.collection(source);
+                self.read(index);
+                self.emit(Instruction::Access);
+            }
+            Expression::Format { parts } => {
+                let mut pieces = vec![];
+                for part in parts {
+                    match part {
+                        FormatPart::Literal(literal) => pieces.push(Piece::Literal(literal.clone())),
+                        FormatPart::Value { value, spec } => {
+                            self.read(value);
+                            pieces.push(Piece::Value(spec.clone()));
+                        }
+                    }
+                }
+                let values = pieces.iter().filter(|piece| matches!(piece, Piece::Value(_))).count();
+                self.chunk.formats.push(pieces);
+                self.emit(Instruction::Format { pieces: self.chunk.formats.len() - 1, values });
+            }
+            Expression::Slice { source, start, end } => {
+                self.collection(source);
+                for bound in [start, end].into_iter().flatten() {
+                    self.read(bound);
+                }
+                self.emit(Instruction::Slice { start: start.is_some(), end: end.is_some() });
+            }
+        }
+    }
+
+    /// Compiles an expression whose `ret` flag gets ignored by the expression using it
+    fn value(&mut self, expression: &Expression) {
+        self.expression(expression);
+        if matches!(expression, Expression::If { .. } | Expression::While { .. } | Expression::Block { .. }) {
+            self.emit(Instruction::ClearReturn);
+        }
+    }
+
+    /// Compiles an expression whose value gets copied right away, variables are read without referring to them
+    fn read(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Identifier { name, span, slot } => {
+                self.span = *span;
+                let instruction = Instruction::LoadValue { name: self.name(name), slot: *slot };
+                self.emit(instruction);
+            }
+            _ => {
+                self.value(expression);
+                self.emit(Instruction::Value);
+            }
+        }
+    }
+
+    /// Compiles the source of an access, which has to be an array or a map
+    fn collection(&mut self, source: &Expression) {
+        match source {
+            Expression::Identifier { name, slot, .. } => {
+                let instruction = Instruction::LoadStored { name: self.name(name), slot: *slot };
+                self.emit(instruction);
+            }
+            _ => self.value(source),
+        }
+        self.emit(Instruction::CheckCollection);
+    }
+
+    /// Compiles a branch or loop body inside its own scope
+    fn scoped(&mut self, expression: &Expression) {
+        self.emit(Instruction::CreateScope);
+        self.expression(expression);
+        self.emit(Instruction::DropScope);
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.chunk.code.push(instruction);
+        self.chunk.spans.push(self.span);
+        self.chunk.code.len() - 1
+    }
+
+    /// Points the jump at the given position to the next instruction
+    fn patch(&mut self, jump: usize) {
+        let target = self.chunk.code.len();
+        match &mut self.chunk.code[jump] {
+            Instruction::Jump(position) |
+            Instruction::JumpIfFalse(position) |
+            Instruction::JumpIfReturning(position) |
+            Instruction::ShortCircuit { end: position, .. } => *position = target,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Number literals which cannot be converted fail when they are reached, like in the evaluator
+    fn number(&mut self, value: &str, typee: Token) {
+        match convert_number(value, &typee) {
+            Ok(object) => self.constant(object),
+            Err(_) => {
+                let instruction = Instruction::Literal { value: self.name(value), typee };
+                self.emit(instruction);
+            }
+        }
+    }
+
+    fn constant(&mut self, object: Object) {
+        self.chunk.constants.push(object);
+        self.emit(Instruction::Constant(self.chunk.constants.len() - 1));
+    }
+
+    fn name(&mut self, name: &str) -> usize {
+        match self.chunk.names.iter().position(|x| x == name) {
+            Some(index) => index,
+            None => {
+                self.chunk.names.push(name.to_string());
+                self.chunk.names.len() - 1
+            }
+        }
+    }
+}
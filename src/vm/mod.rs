@@ -0,0 +1,274 @@
+pub mod chunk;
+pub mod compiler;
+
+use std::vec::IntoIter;
+use crate::evaluator::environment::{Environment, Location};
+use crate::evaluator::Output;
+use crate::evaluator::object::{Object, OwnerShip};
+use crate::parser::ast::expression::{access, access_location, access_source, allocate, assign, bind, slice, assign_location, call_function, convert_number, create_map, evaluate_condition, evaluate_infix_expression, evaluate_prefix_expression, locate_function, locate_value, locate_variable, read_variable, short_circuit, target_value};
+use crate::parser::ast::statement::{declare_pattern, declare_variable, import};
+use crate::vm::chunk::{Chunk, Instruction, Piece};
+use crate::vm::compiler::Compiler;
+
+/// Stack based virtual machine which runs compiled chunks with the same semantics as the `Evaluator`
+pub struct VirtualMachine {
+    chunks: IntoIter<Chunk>,
+    // Values the instructions work on, shared by the chunks of function calls and imported modules
+    operands: Vec<OwnerShip>,
+}
+
+impl VirtualMachine {
+    pub fn new(chunks: IntoIter<Chunk>) -> Self {
+        Self { chunks, operands: vec![] }
+    }
+}
+
+impl VirtualMachine {
+    pub fn evaluate(&mut self, environment: &mut Environment) -> anyhow::Result<()> {
+        self.evaluate_with(environment, &mut |_, _| {})
+    }
+
+    pub fn evaluate_with(&mut self, environment: &mut Environment, output: Output) -> anyhow::Result<()> {
+        for chunk in self.chunks.by_ref() {
+            self.operands.clear();
+            environment.collect_garbage_if_needed();
+            let (ownership, _) = run(&chunk, &mut self.operands, environment)?;
+            if chunk.expression {
+                output(&ownership, environment);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs a chunk and returns its value together with the `ret` flag.
+/// Scopes and operands it left behind are dropped when it fails.
+pub fn run(chunk: &Chunk, operands: &mut Vec<OwnerShip>, environment: &mut Environment) -> anyhow::Result<(OwnerShip, bool)> {
+    let (scopes, height) = (environment.stack().scopes(), operands.len());
+    let result = execute(chunk, operands, environment);
+    if result.is_err() {
+        environment.stack_mut().unwind(scopes);
+        operands.truncate(height);
+    }
+    result
+}
+
+fn execute(chunk: &Chunk, operands: &mut Vec<OwnerShip>, environment: &mut Environment) -> anyhow::Result<(OwnerShip, bool)> {
+    let mut returning = false;
+    // Locations and old values of the assignments whose values are being evaluated
+    let mut targets: Vec<(Location, Option<Object>)> = vec![];
+    let mut ip = 0;
+
+    while let Some(instruction) = chunk.code.get(ip) {
+        ip += 1;
+
+        match instruction {
+            Instruction::Constant(index) => {
+                operands.push(OwnerShip::Instance(chunk.constants[*index].clone()));
+            }
+            Instruction::Literal { value, typee } => {
+                let object = convert_number(&chunk.names[*value], typee)?;
+                operands.push(OwnerShip::Instance(object));
+            }
+            Instruction::Load { name, slot } => {
+                let ownership = locate_variable(&chunk.names[*name], *slot, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::LoadValue { name, slot } => {
+                let object = read_variable(&chunk.names[*name], *slot, environment)?;
+                operands.push(OwnerShip::Instance(object));
+            }
+            Instruction::LoadStored { name, slot } => {
+                let ownership = locate_value(&chunk.names[*name], *slot, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::LoadFunction { name, slot, arguments } => {
+                let function = locate_function(&chunk.names[*name], *slot, *arguments, environment)?;
+                operands.push(function);
+            }
+            Instruction::Value => {
+                let ownership = operands.last_mut().expect("Virtual machine ran out of operands!");
+                if !matches!(ownership, OwnerShip::Instance(_)) {
+                    *ownership = OwnerShip::Instance(ownership.value(environment)?);
+                }
+            }
+            Instruction::Prefix(prefix) => {
+                let ownership = pop(operands);
+                let ownership = evaluate_prefix_expression(prefix, ownership, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Infix(operation) => {
+                let right_obj = pop(operands).value(environment)?;
+                let left_obj = pop(operands).value(environment)?;
+                let object = evaluate_infix_expression(left_obj, right_obj, operation)?;
+                operands.push(OwnerShip::Instance(object));
+            }
+            Instruction::ShortCircuit { operation, end } => {
+                let left_obj = pop(operands).value(environment)?;
+                match short_circuit(&left_obj, operation)? {
+                    Some(object) => {
+                        operands.push(OwnerShip::Instance(object));
+                        ip = *end;
+                    }
+                    None => operands.push(OwnerShip::Instance(left_obj)),
+                }
+            }
+            Instruction::Let { name, typee, reference } => {
+                let ownership = pop(operands);
+                let ownership = declare_variable(&chunk.names[*name], typee, *reference, ownership, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Destructure { pattern, typee } => {
+                let ownership = pop(operands);
+                let ownership = declare_pattern(pattern, typee, ownership, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Function { name, function } => {
+                let function = Object::Function(chunk.functions[*function].clone());
+                environment.stack_mut().add(chunk.names[*name].clone(), OwnerShip::Instance(function));
+                operands.push(OwnerShip::Instance(Object::Null));
+            }
+            Instruction::Import { module, names } => {
+                let (ownership, _) = import(module, names, environment, |module, environment| {
+                    for chunk in Compiler::compile(&module.statements) {
+                        environment.collect_garbage_if_needed_with(operands);
+                        run(&chunk, operands, environment)?;
+                    }
+                    Ok(())
+                })?;
+                operands.push(ownership);
+            }
+            Instruction::Call { name, arguments } => {
+                let mut values = (0..*arguments).map(|_| pop(operands).value(environment)).collect::<anyhow::Result<Vec<Object>>>()?;
+                values.reverse();
+                let function = pop(operands);
+
+                let result = call_function(&chunk.names[*name], chunk.spans[ip - 1], &function, values, environment, |function, environment| {
+                    let code = function.code.get_or_init(|| Compiler::compile_function(&function.body));
+                    Ok(run(code, operands, environment)?.0)
+                })?;
+                operands.push(OwnerShip::Instance(result));
+            }
+            Instruction::Error => {
+                let object = pop(operands).value(environment)?;
+                operands.push(OwnerShip::Instance(Object::Error(Box::new(object))));
+            }
+            Instruction::Target => {
+                let target = pop(operands);
+                let location = assign_location(&target, environment)?;
+                targets.push((location.clone(), target_value(&location, environment)?));
+                // The target stays on the stack while the value gets evaluated
+                operands.push(target);
+            }
+            Instruction::TargetAccess => {
+                let index_obj = pop(operands).value(environment)?;
+                let OwnerShip::Instance(collection) = pop(operands) else { unreachable!() };
+                let location = access_location(&collection, index_obj, environment)?;
+                targets.push((location.clone(), target_value(&location, environment)?));
+                operands.push(OwnerShip::Instance(collection));
+            }
+            Instruction::Assign { reference } => {
+                let ownership = pop(operands);
+                pop(operands);
+                let (location, old) = targets.pop().expect("Virtual machine ran out of assignment targets!");
+
+                let ownership = assign(&location, old, *reference, ownership, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Allocate { reference } => {
+                let ownership = pop(operands);
+                let address = allocate(*reference, ownership, environment)?;
+                operands.push(OwnerShip::Heap(address));
+            }
+            Instruction::Array(length) => {
+                let mut values = (0..*length).map(|_| pop(operands)).collect::<Vec<OwnerShip>>();
+                values.reverse();
+                let address = environment.heap_mut().set(OwnerShip::Instance(Object::Array(values)));
+                operands.push(OwnerShip::Heap(address));
+            }
+            Instruction::Map(length) => {
+                let mut pairs = (0..*length).map(|_| {
+                    let value = pop(operands);
+                    let OwnerShip::Instance(key) = pop(operands) else { unreachable!() };
+                    (key, value)
+                }).collect::<Vec<(Object, OwnerShip)>>();
+                pairs.reverse();
+                let map = create_map(pairs, environment)?;
+                operands.push(OwnerShip::Instance(map));
+            }
+            Instruction::Bind { reference } => {
+                let ownership = pop(operands);
+                let ownership = bind(*reference, ownership, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Tuple(length) => {
+                let mut values = (0..*length).map(|_| pop(operands)).collect::<Vec<OwnerShip>>();
+                values.reverse();
+                operands.push(OwnerShip::Instance(Object::Tuple(values)));
+            }
+            Instruction::CheckCollection => {
+                let ownership = pop(operands);
+                let collection = access_source(&ownership, environment)?;
+                operands.push(OwnerShip::Instance(collection));
+            }
+            Instruction::Access => {
+                let index_obj = pop(operands).value(environment)?;
+                let OwnerShip::Instance(collection) = pop(operands) else { unreachable!() };
+                let ownership = access(&collection, index_obj, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Slice { start, end } => {
+                let end = if *end { Some(pop(operands).value(environment)?) } else { None };
+                let start = if *start { Some(pop(operands).value(environment)?) } else { None };
+                let OwnerShip::Instance(collection) = pop(operands) else { unreachable!() };
+                let ownership = slice(&collection, start, end, environment)?;
+                operands.push(ownership);
+            }
+            Instruction::Format { pieces, values } => {
+                let mut objects = (0..*values).map(|_| pop(operands).value(environment)).collect::<anyhow::Result<Vec<Object>>>()?;
+                objects.reverse();
+                let string = format(&chunk.formats[*pieces], objects, environment)?;
+                operands.push(OwnerShip::Instance(string));
+            }
+            Instruction::Return => returning = true,
+            Instruction::ClearReturn => returning = false,
+            Instruction::Jump(position) => ip = *position,
+            Instruction::JumpIfFalse(position) => {
+                let condition = pop(operands).value(environment)?;
+                if !evaluate_condition(condition)? { ip = *position }
+            }
+            Instruction::JumpIfReturning(position) => {
+                if returning { ip = *position }
+            }
+            Instruction::CreateScope => environment.stack_mut().create_scope(),
+            Instruction::DropScope => environment.stack_mut().drop_scope(),
+            Instruction::Pop => {
+                pop(operands);
+            }
+            Instruction::SafePoint => environment.collect_garbage_if_needed_with(operands),
+        }
+    }
+
+    Ok((pop(operands), returning))
+}
+
+fn pop(operands: &mut Vec<OwnerShip>) -> OwnerShip {
+    operands.pop().expect("Virtual machine ran out of operands!")
+}
+
+/// Joins the literal pieces of an interpolated string with the formatted values
+fn format(pieces: &[Piece], values: Vec<Object>, environment: &Environment) -> anyhow::Result<Object> {
+    let mut values = values.into_iter();
+    let mut string = String::new();
+
+    for piece in pieces {
+        match piece {
+            Piece::Literal(literal) => string.push_str(literal),
+            Piece::Value(spec) => {
+                let value = values.next().unwrap().resolve(environment)?;
+                string.push_str(&spec.apply(&value)?);
+            }
+        }
+    }
+    Ok(Object::String(string))
+}
@@ -0,0 +1,91 @@
+use interpreter::analysis::{analyze, Analysis, SymbolKind};
+use interpreter::error::TypeError;
+use interpreter::lexer::token::Span;
+use interpreter::lexer::Lexer;
+use interpreter::parser::Parser;
+
+fn analysis(program: &str) -> Analysis {
+    let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let statements = parser.parse().unwrap();
+    analyze(&statements, parser.layout().clone())
+}
+
+#[test]
+fn test_analyze_symbols() {
+    let analysis = analysis("let a: int = 1;
+function add(x: int, y: int): int {
+    let sum: int = x + y + a;
+    sum
+}
+let (b, c): (int, string) = (add(a, 2), \"c\");");
+
+    let symbols = analysis.symbols.iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.kind, symbol.span, symbol.parent))
+        .collect::<Vec<_>>();
+    assert_eq!(symbols, [
+        ("a", SymbolKind::Variable, Span::new(1, 5), None),
+        ("add", SymbolKind::Function, Span::new(2, 10), None),
+        ("x", SymbolKind::Parameter, Span::new(2, 14), Some(1)),
+        ("y", SymbolKind::Parameter, Span::new(2, 22), Some(1)),
+        ("sum", SymbolKind::Variable, Span::new(3, 9), Some(1)),
+        ("b", SymbolKind::Variable, Span::new(6, 6), None),
+        ("c", SymbolKind::Variable, Span::new(6, 9), None),
+    ]);
+    assert_eq!(analysis.symbols[1].to_string(), "function add(x: int, y: int): int");
+    assert_eq!(analysis.symbols[6].to_string(), "let c: string");
+    assert!(analysis.diagnostics.is_empty(), "Found {:?}!", analysis.diagnostics);
+
+    // Uses of a, the call of add and the parameter x
+    assert_eq!(analysis.symbol_at(Span::new(3, 28)).map(|symbol| symbol.span), Some(Span::new(1, 5)));
+    assert_eq!(analysis.symbol_at(Span::new(6, 31)).map(|symbol| symbol.span), Some(Span::new(2, 10)));
+    assert_eq!(analysis.symbol_at(Span::new(3, 20)).map(|symbol| symbol.span), Some(Span::new(2, 14)));
+}
+
+#[test]
+fn test_visible_symbols() {
+    let analysis = analysis("let a: int = 1;
+function f(x: int): int {
+    let inner: int = g();
+    inner
+}
+function g(): int { 2 }
+let b: int = 3;");
+
+    let names = |line, column| {
+        let mut names = analysis.visible(Span::new(line, column)).iter().map(|symbol| symbol.name.as_str()).collect::<Vec<&str>>();
+        names.sort();
+        names
+    };
+    assert_eq!(names(3, 5), ["a", "f", "g", "x"]);
+    assert_eq!(names(4, 5), ["a", "f", "g", "inner", "x"]);
+    assert_eq!(names(7, 1), ["a", "f", "g"]);
+}
+
+#[test]
+fn test_type_diagnostics() {
+    let analysis = analysis("let a: int = \"text\";
+function f(x: int): string {
+    if (x > 0) { ret \"positive\"; }
+    x
+}
+let b: string = f(true);
+let c: string = f(1, 2);
+let d: bool = 1 < 2 && !false;
+a = 1.5;
+let e: int = len(\"a\", \"b\");
+let g: int = if (d) { 1 } else { 2 };");
+
+    let diagnostics = analysis.diagnostics.iter()
+        .map(|diagnostic| (diagnostic.span.line, diagnostic.error.to_string()))
+        .collect::<Vec<(usize, String)>>();
+    assert_eq!(diagnostics, [
+        (1, "Expected a value of type int but found string!".to_string()),
+        (2, "Expected a value of type string but found int!".to_string()),
+        (6, "Expected a value of type int but found bool!".to_string()),
+        (7, "Function f expects 1 arguments but found 2!".to_string()),
+        (9, "Expected a value of type int but found float!".to_string()),
+        (10, "Function len expects 1 arguments but found 2!".to_string()),
+    ]);
+    assert!(matches!(analysis.diagnostics[3].error, TypeError::InvalidAmountOfArguments(..)));
+}
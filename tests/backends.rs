@@ -0,0 +1,138 @@
+use std::fs;
+use std::time::{Duration, Instant};
+use interpreter::evaluator::environment::Environment;
+use interpreter::evaluator::printer::print;
+use interpreter::evaluator::{execute, execute_with, Backend};
+use interpreter::lexer::Lexer;
+use interpreter::parser::Parser;
+use interpreter::resolver::Resolver;
+
+const CORPUS_PATH: &str = "res/tests/corpus";
+
+fn evaluate(program: &str, backend: Backend, environment: &mut Environment) -> anyhow::Result<()> {
+    let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+    let mut ast = Parser::from_spanned_tokens(tokens).parse()?;
+    Resolver::from_environment(environment).resolve(&mut ast)?;
+    execute(ast, backend, environment)
+}
+
+/// Outcome of a program together with the values of all global variables
+fn run(program: &str, backend: Backend) -> String {
+    let mut environment = Environment::default();
+    let mut outcome = match evaluate(program, backend, &mut environment) {
+        Ok(()) => "Ok".to_string(),
+        Err(err) => err.to_string(),
+    };
+
+    for name in environment.stack().globals() {
+        outcome.push_str(&format!("\n{} = {}", name, print(environment.get(name).unwrap(), &environment).unwrap()));
+    }
+    outcome
+}
+
+#[test]
+fn test_backends_corpus() {
+    let mut paths = fs::read_dir(CORPUS_PATH).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty(), "Test corpus is empty!");
+
+    for path in paths {
+        let program = fs::read_to_string(&path).unwrap();
+        let tree = run(&program, Backend::Tree);
+        let bytecode = run(&program, Backend::Bytecode);
+
+        assert_eq!(tree, bytecode, "Backends disagree on {}!", path.display());
+    }
+}
+
+#[test]
+fn test_bytecode_results() {
+    let outcome = run(&fs::read_to_string(format!("{CORPUS_PATH}/fibonacci.p")).unwrap(), Backend::Bytecode);
+    assert!(outcome.contains("recursive = 610"), "{outcome}");
+    assert!(outcome.contains("looped = 12586269025"), "{outcome}");
+
+    let outcome = run(&fs::read_to_string(format!("{CORPUS_PATH}/control_flow.p")).unwrap(), Backend::Bytecode);
+    assert!(outcome.contains("position = 2"), "Return did not leave the loop: {outcome}");
+    assert!(outcome.contains("doubled = 42"), "{outcome}");
+
+    // Collections which are only on the operand stack survive the collections inside of the calls
+    let outcome = run(&fs::read_to_string(format!("{CORPUS_PATH}/operands.p")).unwrap(), Backend::Bytecode);
+    assert!(outcome.contains("picked = 3"), "{outcome}");
+    assert!(outcome.contains("summed = 13"), "{outcome}");
+}
+
+#[test]
+fn test_bytecode_stack_trace() {
+    let outcome = run(&fs::read_to_string(format!("{CORPUS_PATH}/index_error.p")).unwrap(), Backend::Bytecode);
+
+    assert!(outcome.starts_with("Index 3 is out of range!\nat inner (line 6)\nat outer (line 9)"), "{outcome}");
+    assert!(!outcome.contains("after"), "Evaluation continued after the error!");
+}
+
+#[test]
+fn test_bytecode_unwinds_scopes() {
+    let mut environment = Environment::default();
+
+    assert!(evaluate("let i: int = 0; while (i < 3) { let inner: int = i; if (i == 1) { inner + true } else { i = i + 1 } }", Backend::Bytecode, &mut environment).is_err());
+    assert!(environment.call_stack().frames().is_empty());
+
+    evaluate("let after: int = i;", Backend::Bytecode, &mut environment).unwrap();
+    assert_eq!(environment.stack().globals().collect::<Vec<_>>(), vec!["i", "after"]);
+}
+
+
+#[test]
+fn test_backends_output() {
+    let program = "let a: array = [1, [2, 3]];
+a;
+function scale(value: float, factor: int): float { ret value * factor; }
+scale;
+let m: map<string, array> = {\"xs\": [1], \"ys\": []};
+m;
+(\"text\", err(\"failed\"), 1.5);
+a[0] = 4;
+if (false) { 1 };";
+
+    for backend in [Backend::Tree, Backend::Bytecode] {
+        let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+        let mut ast = Parser::from_spanned_tokens(tokens).parse().unwrap();
+        let mut environment = Environment::default();
+        Resolver::from_environment(&environment).resolve(&mut ast).unwrap();
+
+        let mut printed = vec![];
+        execute_with(ast, backend, &mut environment, &mut |ownership, environment| printed.push(print(ownership, environment).unwrap())).unwrap();
+
+        assert_eq!(printed, [
+            "[1, [2, 3]]",
+            "function scale(value: float, factor: int): float",
+            "{\"xs\": [1], \"ys\": []}",
+            "(\"text\", err(\"failed\"), 1.5)",
+            "4",
+            "null",
+        ], "{:?} printed the wrong values!", backend);
+    }
+}
+
+/// Time a single run of the loop of the benchmark in the corpus takes
+fn benchmark(program: &str, backend: Backend) -> Duration {
+    let start = Instant::now();
+    let outcome = run(program, backend);
+    let elapsed = start.elapsed();
+
+    assert!(outcome.contains("summed = 89995"), "{outcome}");
+    elapsed
+}
+
+#[test]
+fn test_bytecode_benchmark() {
+    let program = fs::read_to_string(format!("{CORPUS_PATH}/benchmark.p")).unwrap();
+    // The backends take turns, so both are slowed down alike by whatever else runs meanwhile
+    let (mut tree, mut bytecode) = (Duration::MAX, Duration::MAX);
+    for _ in 0..7 {
+        tree = tree.min(benchmark(&program, Backend::Tree));
+        bytecode = bytecode.min(benchmark(&program, Backend::Bytecode));
+    }
+
+    println!("Benchmark took {tree:?} with the evaluator and {bytecode:?} with the virtual machine");
+    assert!(bytecode < tree, "Virtual machine took {bytecode:?} but the evaluator only {tree:?}!");
+}
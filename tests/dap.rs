@@ -0,0 +1,192 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use serde_json::{json, Value};
+
+const PROGRAM: &str = "function square(x: int): int {
+    let result: int = x * x;
+    result
+}
+let values: array = [1, [2, 3]];
+let total: int = square(4);
+total = total + 1;
+";
+
+/// Editor which talks to the debug adapter binary over its standard input and output
+struct Client {
+    process: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    seq: i64,
+    // Events which arrived while waiting for a response
+    events: Vec<Value>,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+            .arg("dap")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = process.stdin.take().unwrap();
+        let output = BufReader::new(process.stdout.take().unwrap());
+        Self { process, input, output, seq: 0, events: vec![] }
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.output.read_line(&mut header).unwrap();
+            let header = header.trim();
+            if header.is_empty() { break }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Sends the request and returns its response
+    fn send(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+        let body = json!({ "seq": self.seq, "type": "request", "command": command, "arguments": arguments }).to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.input.flush().unwrap();
+
+        loop {
+            let message = self.receive();
+            if message["type"] == "response" && message["request_seq"] == json!(self.seq) {
+                assert_eq!(message["command"], command);
+                return message
+            }
+            self.events.push(message);
+        }
+    }
+
+    /// Sends the request and returns the body of its successful response
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        let response = self.send(command, arguments);
+        assert_eq!(response["success"], json!(true), "Request {} failed with {}!", command, response);
+        response["body"].clone()
+    }
+
+    /// Body of the first event with the name which was not awaited yet
+    fn event(&mut self, name: &str) -> Value {
+        loop {
+            if let Some(index) = self.events.iter().position(|event| event["event"] == name) {
+                return self.events.remove(index)["body"].clone()
+            }
+            let message = self.receive();
+            self.events.push(message);
+        }
+    }
+
+    /// Waits until the program stops and returns why with the line of its innermost frame
+    fn stopped(&mut self) -> (String, u64) {
+        let reason = self.event("stopped")["reason"].as_str().unwrap().to_string();
+        let trace = self.request("stackTrace", json!({ "threadId": 1 }));
+        (reason, trace["stackFrames"][0]["line"].as_u64().unwrap())
+    }
+
+    /// Names with values of the variables of the reference
+    fn variables(&mut self, reference: &Value) -> Vec<(String, String, i64)> {
+        let body = self.request("variables", json!({ "variablesReference": reference }));
+        body["variables"].as_array().unwrap().iter()
+            .map(|variable| (
+                variable["name"].as_str().unwrap().to_string(),
+                variable["value"].as_str().unwrap().to_string(),
+                variable["variablesReference"].as_i64().unwrap(),
+            ))
+            .collect()
+    }
+}
+
+fn names(variables: &[(String, String, i64)]) -> Vec<String> {
+    variables.iter().map(|(name, value, _)| format!("{} = {}", name, value)).collect()
+}
+
+#[test]
+fn test_debug_session() {
+    let path = std::env::temp_dir().join(format!("dap_session_{}.p", std::process::id()));
+    std::fs::write(&path, PROGRAM).unwrap();
+    let mut client = Client::start();
+
+    let capabilities = client.request("initialize", json!({ "adapterID": "p_lang", "linesStartAt1": true }));
+    assert_eq!(capabilities["supportsConfigurationDoneRequest"], json!(true));
+    assert_eq!(client.send("stackTrace", json!({ "threadId": 1 }))["success"], json!(false));
+    client.request("launch", json!({ "program": path }));
+    client.event("initialized");
+
+    let breakpoints = client.request("setBreakpoints", json!({ "source": { "path": path }, "breakpoints": [{ "line": 2 }, { "line": 4 }] }));
+    let verified = breakpoints["breakpoints"].as_array().unwrap().iter().map(|breakpoint| breakpoint["verified"].clone()).collect::<Vec<Value>>();
+    assert_eq!(verified, [json!(true), json!(false)]);
+    client.request("configurationDone", json!({}));
+
+    assert_eq!(client.stopped(), ("breakpoint".to_string(), 2));
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+    let frames = trace["stackFrames"].as_array().unwrap().iter()
+        .map(|frame| format!("{} {}", frame["name"].as_str().unwrap(), frame["line"]))
+        .collect::<Vec<String>>();
+    assert_eq!(frames, ["square 2", "<program> 6"]);
+    assert_eq!(trace["stackFrames"][0]["source"]["path"], json!(path.canonicalize().unwrap()));
+
+    let scopes = client.request("scopes", json!({ "frameId": trace["stackFrames"][0]["id"] }));
+    let scopes = scopes["scopes"].as_array().unwrap().clone();
+    assert_eq!(scopes.iter().map(|scope| scope["name"].clone()).collect::<Vec<Value>>(), [json!("Locals"), json!("Globals")]);
+    assert_eq!(names(&client.variables(&scopes[0]["variablesReference"])), ["x = 4"]);
+
+    let globals = client.variables(&scopes[1]["variablesReference"]);
+    assert_eq!(names(&globals), ["values = [1, [2, 3]]"]);
+    let elements = client.variables(&json!(globals[0].2));
+    assert_eq!(names(&elements), ["[0] = 1", "[1] = [2, 3]"]);
+    assert_eq!(elements[0].2, 0, "Integers cannot be expanded!");
+    assert_eq!(names(&client.variables(&json!(elements[1].2))), ["[0] = 2", "[1] = 3"]);
+
+    let result = client.request("evaluate", json!({ "expression": "x * 10", "frameId": 2 }));
+    assert_eq!(result, json!({ "result": "40", "type": "int", "variablesReference": 0 }));
+    let response = client.send("evaluate", json!({ "expression": "unknown" }));
+    assert_eq!((response["success"].clone(), response["message"].clone()), (json!(false), json!("Found unknown identifier unknown!")));
+
+    client.request("next", json!({ "threadId": 1 }));
+    assert_eq!(client.stopped(), ("step".to_string(), 3));
+    client.request("stepOut", json!({ "threadId": 1 }));
+    assert_eq!(client.stopped(), ("step".to_string(), 7));
+    let result = client.request("evaluate", json!({ "expression": "values" }));
+    assert_eq!((result["result"].clone(), result.get("value")), (json!("[1, [2, 3]]"), None));
+    assert_eq!(names(&client.variables(&result["variablesReference"])), ["[0] = 1", "[1] = [2, 3]"]);
+    assert_eq!(client.request("evaluate", json!({ "expression": "total" }))["result"], json!("16"));
+
+    client.request("continue", json!({ "threadId": 1 }));
+    assert_eq!(client.event("exited")["exitCode"], json!(0));
+    client.event("terminated");
+    client.request("disconnect", json!({}));
+    assert!(client.process.wait().unwrap().success());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_disconnect_while_paused() {
+    let path = std::env::temp_dir().join(format!("dap_disconnect_{}.p", std::process::id()));
+    std::fs::write(&path, PROGRAM).unwrap();
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "adapterID": "p_lang" }));
+    client.request("launch", json!({ "program": path, "stopOnEntry": true }));
+    client.event("initialized");
+    client.request("configurationDone", json!({}));
+    assert_eq!(client.stopped(), ("entry".to_string(), 1));
+
+    client.request("stepIn", json!({ "threadId": 1 }));
+    assert_eq!(client.stopped(), ("step".to_string(), 5));
+    assert_eq!(client.send("jump", json!({}))["message"], json!("Found unknown request jump!"));
+    client.request("disconnect", json!({}));
+    let mut rest = String::new();
+    client.output.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "", "A disconnected program has to end quietly!");
+    assert!(client.process.wait().unwrap().success());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,190 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use interpreter::debugger::command::Command as DebugCommand;
+use interpreter::debugger::{Debugger, Frontend, Pause, Reason, Step};
+use interpreter::evaluator::environment::Environment;
+use interpreter::evaluator::printer::print;
+use interpreter::lexer::Lexer;
+use interpreter::parser::Parser;
+use interpreter::resolver::Resolver;
+
+const PROGRAM: &str = "function square(x: int): int {
+    let result: int = x * x;
+    result
+}
+let values: array = [1, 2, 3];
+let total: int = 0;
+let i: int = 0;
+while (i < len(values)) {
+    total = total + square(values[i]);
+    i = i + 1;
+}
+total;";
+
+/// What the program looked like when it paused
+#[derive(Debug)]
+struct Paused {
+    reason: Reason,
+    line: usize,
+    frames: Vec<String>,
+    locals: Vec<String>,
+}
+
+/// Frontend which answers the pauses with the given steps and records them
+struct Script {
+    steps: VecDeque<Step>,
+    pauses: Rc<RefCell<Vec<Paused>>>,
+}
+
+impl Frontend for Script {
+    fn paused(&mut self, pause: &mut Pause) -> anyhow::Result<Step> {
+        let frames = pause.frames();
+        let locals = pause.locals(frames[0].call).iter()
+            .map(|(name, value)| format!("{} = {}", name, print(value, pause.environment).unwrap()))
+            .collect();
+
+        self.pauses.borrow_mut().push(Paused {
+            reason: pause.reason,
+            line: pause.span.line,
+            frames: frames.iter().map(|frame| format!("{} {}", frame.name, frame.span.line)).collect(),
+            locals,
+        });
+        Ok(self.steps.pop_front().unwrap_or(Step::Continue))
+    }
+}
+
+/// Debugs the program with the steps and returns where it paused
+fn debug(program: &str, debugger: impl FnOnce(Script) -> Debugger<Script>, steps: &[Step]) -> Vec<Paused> {
+    let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let mut statements = parser.parse().unwrap();
+    Resolver::default().resolve(&mut statements).unwrap();
+
+    let pauses = Rc::new(RefCell::new(vec![]));
+    let script = Script { steps: steps.iter().copied().collect(), pauses: pauses.clone() };
+    debugger(script).run(&statements, parser.layout().clone(), &mut Environment::default()).unwrap();
+    pauses.take()
+}
+
+fn lines(pauses: &[Paused]) -> Vec<usize> {
+    pauses.iter().map(|pause| pause.line).collect()
+}
+
+#[test]
+fn test_breakpoints() {
+    let pauses = debug(PROGRAM, |script| Debugger::new(script).with_breakpoints([2]).without_entry(), &[]);
+    assert_eq!(lines(&pauses), [2, 2, 2]);
+    assert!(pauses.iter().all(|pause| pause.reason == Reason::Breakpoint));
+    assert_eq!(pauses[1].frames, ["square 2", "<program> 9"]);
+    assert_eq!(pauses[1].locals, ["x = 2"]);
+}
+
+#[test]
+fn test_stepping() {
+    let pauses = debug(PROGRAM, Debugger::new, &[Step::Over, Step::Over, Step::Over, Step::Over, Step::Over, Step::Into, Step::Into, Step::Out, Step::Over]);
+    assert_eq!(lines(&pauses), [1, 5, 6, 7, 8, 9, 2, 3, 10, 9]);
+    assert_eq!(pauses[0].reason, Reason::Entry);
+    assert_eq!(pauses[1].reason, Reason::Step);
+    assert_eq!(pauses[7].locals, ["x = 1", "result = 1"]);
+    assert_eq!(pauses[8].frames, ["<program> 10"]);
+    assert_eq!(pauses[9].locals, ["values = [1, 2, 3]", "total = 1", "i = 1"]);
+}
+
+#[test]
+fn test_step_over_calls() {
+    let pauses = debug(PROGRAM, |script| Debugger::new(script).with_breakpoints([9]).without_entry(), &[Step::Over, Step::Over]);
+    assert_eq!(lines(&pauses), [9, 10, 9, 9]);
+    assert_eq!(pauses[1].reason, Reason::Step);
+    assert_eq!(pauses[2].reason, Reason::Breakpoint);
+}
+
+#[test]
+fn test_watches() {
+    struct Watcher(Rc<RefCell<Vec<String>>>);
+    impl Frontend for Watcher {
+        fn paused(&mut self, pause: &mut Pause) -> anyhow::Result<Step> {
+            if pause.watches.is_empty() {
+                pause.watches.extend(["total * 10".to_string(), "square(i + 1)".to_string(), "unknown".to_string()]);
+            }
+            for (expression, value) in pause.watched() {
+                self.0.borrow_mut().push(format!("{} = {}", expression, value.unwrap_or_else(|err| err.to_string())));
+            }
+            assert!(pause.evaluate("let a: int = 1;").is_err(), "Only expressions can be evaluated!");
+            Ok(Step::Continue)
+        }
+    }
+
+    let tokens = Lexer::from_string(PROGRAM.to_string()).lex_spanned();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let mut statements = parser.parse().unwrap();
+    Resolver::default().resolve(&mut statements).unwrap();
+
+    let watched = Rc::new(RefCell::new(vec![]));
+    let debugger = Debugger::new(Watcher(watched.clone())).with_breakpoints([10]).without_entry();
+    debugger.run(&statements, parser.layout().clone(), &mut Environment::default()).unwrap();
+    assert_eq!(watched.take()[3..6], [
+        "total * 10 = 50".to_string(),
+        "square(i + 1) = 4".to_string(),
+        "unknown = Found unknown identifier unknown!".to_string(),
+    ]);
+}
+
+#[test]
+fn test_terminate() {
+    struct Quit;
+    impl Frontend for Quit {
+        fn paused(&mut self, _: &mut Pause) -> anyhow::Result<Step> {
+            anyhow::bail!(interpreter::error::DebugError::Terminated)
+        }
+    }
+
+    let tokens = Lexer::from_string("function f(): int {\n    1\n}\nf();".to_string()).lex_spanned();
+    let mut parser = Parser::from_spanned_tokens(tokens);
+    let mut statements = parser.parse().unwrap();
+    Resolver::default().resolve(&mut statements).unwrap();
+    let debugger = Debugger::new(Quit).with_breakpoints([2]).without_entry();
+    assert!(debugger.run(&statements, parser.layout().clone(), &mut Environment::default()).is_ok());
+}
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(DebugCommand::parse("b 12").unwrap(), DebugCommand::Break(12));
+    assert_eq!(DebugCommand::parse("  next ").unwrap(), DebugCommand::Next);
+    assert_eq!(DebugCommand::parse("print a + 1").unwrap(), DebugCommand::Print("a + 1".to_string()));
+    assert_eq!(DebugCommand::parse("break x").unwrap_err().to_string(), "Expected a line number but found x!");
+    assert_eq!(DebugCommand::parse("watch").unwrap_err().to_string(), "Command watch expects an expression!");
+    assert!(DebugCommand::parse("jump 3").is_err());
+}
+
+#[test]
+fn test_debug_command() {
+    let path = std::env::temp_dir().join(format!("debug_command_{}.p", std::process::id()));
+    std::fs::write(&path, PROGRAM).unwrap();
+
+    let mut process = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+        .arg("debug").arg("--break").arg("2").arg("--run").arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    process.stdin.take().unwrap().write_all(b"stack\nprint x * 10\ndelete 2\nwatch total\nnext\n\nquit\n").unwrap();
+    let output = process.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for expected in [
+        "Reached the breakpoint at line 2\n    2 |     let result: int = x * x;",
+        "#0 square at line 2\n#1 <program> at line 9",
+        "(debug) 10\n",
+        "Removed the breakpoint at line 2!",
+        "Stepped to line 3\n    3 |     result\ntotal = 0",
+        "Stepped to line 10\n   10 |     i = i + 1;\ntotal = 1",
+        "Debugging finished!",
+    ] {
+        assert!(stdout.contains(expected), "Missing {:?} in {}!", expected, stdout);
+    }
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,655 @@
+use interpreter::error::{EvalError, StackTrace};
+use interpreter::evaluator::environment::{Environment, Heap};
+use interpreter::evaluator::object::{Object, OwnerShip};
+use interpreter::evaluator::printer::print;
+use interpreter::evaluator::Evaluator;
+use interpreter::lexer::Lexer;
+use interpreter::lexer::token::Token;
+use interpreter::parser::Parser;
+use interpreter::resolver::Resolver;
+
+fn evaluate(program: &str, environment: &mut Environment) -> anyhow::Result<()> {
+    let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+    let mut ast = Parser::from_spanned_tokens(tokens).parse()?;
+    Resolver::from_environment(environment).resolve(&mut ast)?;
+    Evaluator::new(ast.into_iter()).evaluate(environment)
+}
+
+#[test]
+fn test_stack_trace() {
+    let program = "function inner(n: int): int {
+    let a: array = [1];
+    a[n]
+}
+function outer(n: int): int {
+    inner(n + 1)
+}
+outer(2);";
+
+    let mut environment = Environment::default();
+    let err = evaluate(program, &mut environment).unwrap_err();
+
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::IndexOutOfRange(3))), "Wrong error was raised!");
+
+    let trace = err.downcast_ref::<StackTrace>().expect("Runtime error has no stack trace!");
+    assert_eq!(trace.frames.len(), 2);
+    assert_eq!(trace.frames[0].name, "inner");
+    assert_eq!(trace.frames[0].span.line, 6);
+    assert_eq!(trace.frames[1].name, "outer");
+    assert_eq!(trace.frames[1].span.line, 8);
+    assert_eq!(trace.calls(), "at inner (line 6)\nat outer (line 8)");
+    // Embedders which only display the error see the message together with the calls
+    assert_eq!(err.to_string(), format!("{}\nat inner (line 6)\nat outer (line 8)", EvalError::IndexOutOfRange(3)));
+    assert_eq!(err.root_cause().to_string(), EvalError::IndexOutOfRange(3).to_string());
+
+    assert!(environment.call_stack().frames().is_empty(), "Call stack was not unwound!");
+}
+
+#[test]
+fn test_stack_trace_arguments() {
+    let program = "function fail(n: int): int { n + true }
+fail(7);";
+
+    let err = evaluate(program, &mut Environment::default()).unwrap_err();
+    let trace = err.downcast_ref::<StackTrace>().unwrap();
+
+    assert_eq!(format!("{:?}", trace.frames[0].arguments), "[Integer(7)]");
+}
+
+#[test]
+fn test_garbage_collection() {
+    let program = "let keep: array = [[1], [2]];
+[3, 4];
+[5];";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    assert_eq!(environment.heap().statistics().live, 12);
+
+    let freed = environment.collect_garbage();
+    let statistics = environment.heap().statistics();
+
+    assert_eq!(freed, 5, "Unreachable arrays were not freed!");
+    assert_eq!(statistics.live, 7);
+    assert_eq!(statistics.freed, 5);
+    assert_eq!(statistics.collections, 1);
+
+    evaluate("let check: int = keep[1][0];", &mut environment).unwrap();
+    let check = environment.get("check").unwrap().value(&environment).unwrap();
+    assert_eq!(format!("{:?}", check), "Integer(2)");
+}
+
+#[test]
+fn test_garbage_collection_pressure() {
+    let program = "let i: int = 0;
+while (i < 2000) {
+    [i, i + 1];
+    i = i + 1;
+}";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+    let statistics = environment.heap().statistics();
+
+    assert_eq!(statistics.allocated, 6000);
+    assert!(statistics.collections > 0, "Allocation pressure did not trigger a collection!");
+    assert!(statistics.live < 2048, "Heap keeps growing without bound!");
+}
+
+#[test]
+fn test_return_inside_while() {
+    let program = "function search(limit: int): int {
+    let i: int = 0;
+    while (i < 100) {
+        if (i * i > limit) { ret i; }
+        i = i + 1;
+    }
+    ret 0;
+}
+let root: int = search(10);
+let missing: int = search(100000);";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| format!("{:?}", environment.get(name).unwrap().value(&environment).unwrap());
+    assert_eq!(value("root"), "Integer(4)");
+    assert_eq!(value("missing"), "Integer(0)");
+}
+
+#[test]
+fn test_heap_addresses() {
+    let mut heap = Heap::default();
+
+    let first = heap.set(OwnerShip::Instance(Object::Integer(1)));
+    heap.deallocate(first);
+    let second = heap.set(OwnerShip::Instance(Object::Integer(2)));
+
+    assert_ne!(first, second, "Reused slot got the same address!");
+    assert!(heap.get(first).is_none(), "Stale address still points to a value!");
+    assert!(matches!(heap.get(second), Some(OwnerShip::Instance(Object::Integer(2)))));
+}
+
+#[test]
+fn test_heap_assignment() {
+    let mut environment = Environment::default();
+    evaluate("let a: array = [1, 2]; a[1] = 5; let b: int = a[1];", &mut environment).unwrap();
+
+    let b = environment.get("b").unwrap().value(&environment).unwrap();
+    assert_eq!(format!("{:?}", b), "Integer(5)");
+}
+
+#[test]
+fn test_binding_copies_values() {
+    let program = "let a: int = 1;
+let b: int = a;
+let r: int = &a;
+a = 2;
+let arr: array = [1];
+let shared: array = arr;
+shared[0] = 5;";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| format!("{:?}", environment.get(name).unwrap().value(&environment).unwrap());
+    assert_eq!(value("b"), "Integer(1)", "Let binding did not copy the value!");
+    assert_eq!(value("r"), "Integer(2)", "Explicit reference does not follow its variable!");
+
+    evaluate("let first: int = arr[0];", &mut environment).unwrap();
+    let first = environment.get("first").unwrap().value(&environment).unwrap();
+    assert_eq!(format!("{:?}", first), "Integer(5)", "Arrays are not shared!");
+}
+
+#[test]
+fn test_checked_arithmetic() {
+    let max = i128::MAX;
+    let cases = [
+        (format!("{max} + 1;"), "Integer overflow while applying Add on 170141183460469231731687303715884105727 and 1!"),
+        (format!("-{max} - 2;"), "Integer overflow while applying Subtract on -170141183460469231731687303715884105727 and 2!"),
+        (format!("{max} * 2;"), "Integer overflow while applying Multiply on 170141183460469231731687303715884105727 and 2!"),
+        ("let a: int = 0; 5 / a;".to_string(), "Cannot divide 5 by zero!"),
+        ("5 % 0;".to_string(), "Cannot calculate 5 modulo zero!"),
+        (format!("let a: int = -{max} - 1; -a;"), "Integer overflow while negating -170141183460469231731687303715884105728!"),
+        (format!("let a: int = -{max} - 1; a / -1;"), "Integer overflow while applying Divide on -170141183460469231731687303715884105728 and -1!"),
+    ];
+
+    for (program, message) in cases {
+        let err = evaluate(&program, &mut Environment::default()).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+
+    assert!(evaluate("5.0 / 0;", &mut Environment::default()).is_ok(), "Float division follows IEEE 754!");
+}
+
+#[test]
+fn test_wrapping_and_saturating_builtins() {
+    let program = format!("let a: int = wrapping_add({max}, 2);
+let b: int = saturating_add({max}, 2);
+let c: int = saturating_sub(0 - {max}, 5);
+let d: int = wrapping_sub(0, 1);
+let minimum: int = 0 - {max} - 1;
+let e: int = wrapping_div(minimum, -1);
+let f: int = saturating_div(minimum, -1);
+let g: int = wrapping_neg(minimum);
+let h: int = saturating_neg(minimum);
+let i: int = wrapping_pow(2, 128);
+let j: int = saturating_pow(-2, 127);
+let k: int = saturating_pow(-2, 128);
+let l: int = wrapping_div(7, 2);", max = i128::MAX);
+
+    let mut environment = Environment::default();
+    evaluate(&program, &mut environment).unwrap();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    assert!(matches!(value("a"), Object::Integer(val) if val == i128::MIN + 1));
+    assert!(matches!(value("b"), Object::Integer(i128::MAX)));
+    assert!(matches!(value("c"), Object::Integer(i128::MIN)));
+    assert!(matches!(value("d"), Object::Integer(-1)));
+    assert!(matches!(value("e"), Object::Integer(i128::MIN)));
+    assert!(matches!(value("f"), Object::Integer(i128::MAX)));
+    assert!(matches!(value("g"), Object::Integer(i128::MIN)));
+    assert!(matches!(value("h"), Object::Integer(i128::MAX)));
+    assert!(matches!(value("i"), Object::Integer(0)));
+    assert!(matches!(value("j"), Object::Integer(i128::MIN)));
+    assert!(matches!(value("k"), Object::Integer(i128::MAX)));
+    assert!(matches!(value("l"), Object::Integer(3)));
+
+    let cases = [
+        ("wrapping_div(1, 0);", "Cannot divide 1 by zero!"),
+        ("saturating_div(1, 0);", "Cannot divide 1 by zero!"),
+        ("wrapping_pow(2, -1);", "Cannot raise to the negative power -1!"),
+    ];
+    for (program, message) in cases {
+        let err = evaluate(program, &mut Environment::default()).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+
+    let err = evaluate("wrapping_add(1);", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::InvalidAmountOfArguments(2, 1))));
+
+    let err = evaluate("wrapping_add(1, true);", &mut Environment::default()).unwrap_err();
+    assert_eq!(err.downcast_ref::<StackTrace>().unwrap().calls(), "at wrapping_add (line 1)");
+}
+
+#[test]
+fn test_big_integers_and_decimals() {
+    let program = "function factorial(n: bigint): bigint {
+    if (n < 2) { ret 1n; }
+    n * factorial(n - 1)
+}
+let big: bigint = factorial(35n);
+let sum: decimal = 0.1d + 0.2d;
+let exact: bool = sum == 0.3d;
+let price: decimal = 19.99d * 3;
+let negative: bigint = -5n % 3;";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| format!("{:?}", environment.get(name).unwrap().value(&environment).unwrap());
+    assert!(value("big").contains("10333147966386144929666651337523200000000"), "{}", value("big"));
+    assert_eq!(value("exact"), "Boolean(true)");
+    assert!(matches!(environment.get("price").unwrap().value(&environment).unwrap(), Object::Decimal(price) if price.to_string() == "59.97"));
+    assert!(matches!(environment.get("negative").unwrap().value(&environment).unwrap(), Object::BigInteger(value) if value == (-2).into()));
+}
+
+#[test]
+fn test_number_conversions() {
+    let program = "let a: int = to_int(12.9d);
+let b: bigint = to_bigint(\"123456789012345678901234567890123456789012\");
+let c: decimal = to_decimal(0.1);
+let d: float = to_float(5n);";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    assert!(matches!(value("a"), Object::Integer(12)));
+    assert!(matches!(value("b"), Object::BigInteger(value) if value.to_string() == "123456789012345678901234567890123456789012"));
+    assert!(matches!(value("c"), Object::Decimal(value) if value.to_string() == "0.1"));
+    assert!(matches!(value("d"), Object::Float(value) if value == 5.0));
+
+    let err = evaluate("to_int(170141183460469231731687303715884105728n);", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotConvertInto(..))));
+
+    let err = evaluate("1.5 + 1d;", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::IncompatibleTypes(..))), "Floats must not mix with decimals!");
+
+    let err = evaluate("1d / 0;", &mut Environment::default()).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Cannot divide 1 by zero!");
+}
+
+#[test]
+fn test_bitwise_and_power_operators() {
+    let program = "let x: int = 5;
+let bits: int = 6 & 3 | 8 ^ 1;
+let shifted: int = 1 + 1 << 4 >> 2;
+let inverted: int = ~x;
+let tower: int = 2 ** 3 ** 2;
+let negated: int = -2 ** 2;
+let root: float = 2.0 ** 0.5;
+let big: bigint = 2n ** 100 | 1;
+let cube: decimal = 1.5d ** 3;
+let reference: int = &x;";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    assert!(matches!(value("bits"), Object::Integer(11)));
+    assert!(matches!(value("shifted"), Object::Integer(8)));
+    assert!(matches!(value("inverted"), Object::Integer(-6)));
+    assert!(matches!(value("tower"), Object::Integer(512)), "Power has to be right associative!");
+    assert!(matches!(value("negated"), Object::Integer(-4)), "Power has to bind tighter than negation!");
+    assert!(matches!(value("root"), Object::Float(value) if value == 2f64.sqrt()));
+    assert!(matches!(value("big"), Object::BigInteger(value) if value.to_string() == "1267650600228229401496703205377"));
+    assert!(matches!(value("cube"), Object::Decimal(value) if value.to_string() == "3.375"));
+    assert!(matches!(environment.get("reference").unwrap(), OwnerShip::Reference(_)), "Prefix & still creates a reference!");
+
+    let cases = [
+        ("2 ** 127;", "Integer overflow while applying Power on 2 and 127!"),
+        ("2 ** -1;", "Cannot raise to the negative power -1!"),
+        ("1 << 128;", "Cannot shift by 128!"),
+        ("1 >> -1;", "Cannot shift by -1!"),
+        ("1 << 127;", "Integer overflow while applying ShiftLeft on 1 and 127!"),
+        ("-1 << 127;", "Ok"),
+        ("3 << 126;", "Integer overflow while applying ShiftLeft on 3 and 126!"),
+        ("1n << 4000000000n;", "Result of applying ShiftLeft with 4000000000 would have more than 1048576 bits!"),
+        ("3n ** 4000000000n;", "Result of applying Power with 4000000000 would have more than 1048576 bits!"),
+        ("1.5d ** 4000000000d;", "Result of applying Power with 4000000000 would have more than 1048576 bits!"),
+        ("1n ** 4000000000n;", "Ok"),
+    ];
+
+    for (program, message) in cases {
+        let outcome = evaluate(program, &mut Environment::default()).map_or_else(|err| err.root_cause().to_string(), |_| "Ok".to_string());
+        assert_eq!(outcome, message);
+    }
+
+    let err = evaluate("~true;", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::IllegalPrefixOperation(Token::BitNot, _))));
+
+    let err = evaluate("1.5 & 1;", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotApplyOn(..))));
+}
+
+#[test]
+fn test_short_circuit() {
+    let program = "let a: array = [3, 1, 0];
+function positives(values: int): int {
+    let i: int = 0;
+    while (i < len(a) && a[i] > 0) {
+        i = i + 1;
+    }
+    i
+}
+let count: int = positives(0);
+let skipped: bool = true || a[10] > 0;
+let guarded: bool = false && 1 / 0 == 1;";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    assert!(matches!(value("count"), Object::Integer(2)));
+    assert!(matches!(value("skipped"), Object::Boolean(true)));
+    assert!(matches!(value("guarded"), Object::Boolean(false)));
+
+    let err = evaluate("1 && true;", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotApplyOn(Token::And, _))));
+
+    let err = evaluate("true && 1;", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::IncompatibleTypes(..))), "The right side still has to be a boolean!");
+}
+
+#[test]
+fn test_maps() {
+    let program = "let ages: map<string, int> = { \"alice\": 31, \"bob\": 27 };
+ages[\"carol\"] = 40;
+ages[\"bob\"] = ages[\"bob\"] + 1;
+let alias: map<string, int> = ages;
+alias[\"dave\"] = 19;
+function oldest(people: map<string, int>): string {
+    let pairs: array = entries(people);
+    let name: string = \"\";
+    let age: int = 0;
+    let i: int = 0;
+    while (i < len(pairs)) {
+        if (pairs[i][1] > age) {
+            name = pairs[i][0];
+            age = pairs[i][1];
+        }
+        i = i + 1;
+    }
+    name
+}
+let name: string = oldest(ages);
+let removed: int = remove(ages, \"alice\");
+let names: array = keys(ages);
+let found: bool = contains(ages, \"dave\") && !contains(ages, \"alice\");";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+    environment.collect_garbage();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    let Object::Map(ages) = value("ages") else { panic!("Maps have to be stored as maps!") };
+    let ages = environment.entries(ages).unwrap().iter()
+        .map(|(key, value)| format!("{}: {:?}", key, value.value(&environment).unwrap()))
+        .collect::<Vec<String>>();
+    assert_eq!(ages, ["\"bob\": Integer(28)", "\"carol\": Integer(40)", "\"dave\": Integer(19)"]);
+    assert!(matches!(value("name"), Object::String(name) if name == "carol"));
+    assert!(matches!(value("removed"), Object::Integer(31)));
+    assert!(matches!(value("names"), Object::Array(names) if names.len() == 3));
+    assert!(matches!(value("found"), Object::Boolean(true)));
+
+    let cases = [
+        ("let m: map<string, int> = { \"a\": 1 }; m[\"b\"];", "Map does not contain the key \"b\"!"),
+        ("let m: map<string, int> = { \"a\": 1 }; m[1] = 2;", "Cannot use Integer(1) as a map key!"),
+        ("let m: map<float, int> = { 1.5: 1 };", "Cannot use Float(1.5) as a map key!"),
+        ("let m: map<string, int> = { \"a\": 1 }; remove(m, \"b\");", "Map does not contain the key \"b\"!"),
+    ];
+
+    for (program, message) in cases {
+        let err = evaluate(program, &mut Environment::default()).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+
+    let err = evaluate("let m: map<string, int> = { \"a\": true };", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::InvalidTypeButFound(..))));
+
+    let err = evaluate("let m: map<string, int> = { \"a\": 1 }; m[\"b\"] = 1.5;", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotAssignDifferentType(..))));
+}
+
+#[test]
+fn test_tuples_and_destructuring() {
+    let program = "let (q, r): (int, int) = divmod(17, 5);
+function bounds(a: int, b: int): (int, int) {
+    let low: int = a;
+    if (b < a) { low = b; }
+    ret (low, a + b - low);
+}
+let (low, high): (int, int) = bounds(8, 3);
+let pair: (string, (int, bool)) = (\"a\", (1, true));
+let (name, (count, flag)): (string, (int, bool)) = pair;
+let [first, ..rest]: array = [1, 2, 3];
+let [head, ..middle, tail]: array = [1, 2, 3, 4];
+rest[0] = 20;
+let second: int = pair[1][0];";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+    environment.collect_garbage();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    let integers = |name: &str| match value(name) {
+        Object::Array(values) => values.iter().map(|value| format!("{:?}", value.value(&environment).unwrap())).collect::<Vec<String>>(),
+        obj => panic!("Expected an array but found {:?}!", obj),
+    };
+    assert!(matches!(value("q"), Object::Integer(3)));
+    assert!(matches!(value("r"), Object::Integer(2)));
+    assert!(matches!(value("low"), Object::Integer(3)));
+    assert!(matches!(value("high"), Object::Integer(8)));
+    assert!(matches!(value("name"), Object::String(name) if name == "a"));
+    assert!(matches!(value("count"), Object::Integer(1)));
+    assert!(matches!(value("flag"), Object::Boolean(true)));
+    assert!(matches!(value("first"), Object::Integer(1)));
+    assert_eq!(integers("rest"), ["Integer(20)", "Integer(3)"]);
+    assert!(matches!(value("head"), Object::Integer(1)));
+    assert_eq!(integers("middle"), ["Integer(2)", "Integer(3)"]);
+    assert!(matches!(value("tail"), Object::Integer(4)));
+    assert!(matches!(value("second"), Object::Integer(1)));
+
+    let cases = [
+        "let (a, b, c): (int, int, int) = divmod(7, 2);",
+        "let [a, b]: array = [1, 2, 3];",
+        "let [a, b, ..c]: array = [1];",
+    ];
+
+    for program in cases {
+        let err = evaluate(program, &mut Environment::default()).unwrap_err();
+        assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotDestructure(..))), "{}", program);
+    }
+
+    let err = evaluate("let (a, b): (int, string) = divmod(7, 2);", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::InvalidType(..))));
+
+    let err = evaluate("let t: (int, string) = (1, 2);", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::InvalidTypeButFound(..))));
+
+    let err = evaluate("divmod(1, 0);", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::DivisionByZero(..))));
+}
+
+#[test]
+fn test_strings() {
+    let program = "let word: string = \"héllo\";
+let second: string = word[1];
+let size: int = len(word);
+let middle: string = substring(word, 1, 4);
+let parts: array = split(\"a,b,,c\", \",\");
+let joined: string = join(parts, \"-\");
+let letters: string = join(chars(\"abc\"), \" \");
+let trimmed: string = trim(\"  padded  \");
+let found: bool = contains(word, \"ll\") && !contains(word, \"x\");
+let prefix: bool = starts_with(word, \"hé\");
+let replaced: string = replace(\"a-b-c\", \"-\", \"+\");
+let upper: string = to_upper(word);
+let lower: string = to_lower(\"MiXeD\");
+let ordered: bool = \"apple\" < \"banana\" && \"b\" >= \"a\" && !(\"a\" > \"a\");";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    let string = |name: &str| match value(name) {
+        Object::String(value) => value,
+        obj => panic!("Expected a string but found {:?}!", obj),
+    };
+    assert_eq!(string("second"), "é");
+    assert!(matches!(value("size"), Object::Integer(5)));
+    assert_eq!(string("middle"), "éll");
+    assert!(matches!(value("parts"), Object::Array(parts) if parts.len() == 4));
+    assert_eq!(string("joined"), "a-b--c");
+    assert_eq!(string("letters"), "a b c");
+    assert_eq!(string("trimmed"), "padded");
+    assert!(matches!(value("found"), Object::Boolean(true)));
+    assert!(matches!(value("prefix"), Object::Boolean(true)));
+    assert_eq!(string("replaced"), "a+b+c");
+    assert_eq!(string("upper"), "HÉLLO");
+    assert_eq!(string("lower"), "mixed");
+    assert!(matches!(value("ordered"), Object::Boolean(true)));
+
+    let cases = [
+        ("let s: string = \"abc\"; s[3];", "Index 3 is out of range!"),
+        ("substring(\"abc\", 2, 1);", "Cannot slice 2..1 out of a sequence with length 3!"),
+        ("substring(\"abc\", 0, 4);", "Cannot slice 0..4 out of a sequence with length 3!"),
+        ("join([\"a\", 1], \",\");", "Expected type StringType but found Integer(1)!"),
+    ];
+
+    for (program, message) in cases {
+        let err = evaluate(program, &mut Environment::default()).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+
+    let err = evaluate("let s: string = \"abc\"; s[0] = \"x\";", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotAssignTo(..))));
+}
+
+#[test]
+fn test_slices_and_negative_indexing() {
+    let program = "let values: array = [1, 2, 3, 4, 5];
+let middle: array = values[1..3];
+let head: array = values[..2];
+let tail: array = values[3..];
+let copy: array = values[..];
+let last_two: array = values[-2..];
+let empty: array = values[2..2];
+let last: int = values[-1];
+values[-5] = 10;
+copy[0] = 20;
+let word: string = \"hello\"[1..-1];
+let final: string = \"hello\"[-1];
+let n: int = 2;
+let computed: array = values[n - 1..n + 1];";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let value = |name: &str| environment.get(name).unwrap().value(&environment).unwrap();
+    let integers = |name: &str| match value(name) {
+        Object::Array(values) => values.iter().map(|value| match value.value(&environment).unwrap() {
+            Object::Integer(value) => value,
+            obj => panic!("Expected an integer but found {:?}!", obj),
+        }).collect::<Vec<i128>>(),
+        obj => panic!("Expected an array but found {:?}!", obj),
+    };
+    assert_eq!(integers("values"), [10, 2, 3, 4, 5]);
+    assert_eq!(integers("middle"), [2, 3]);
+    assert_eq!(integers("head"), [1, 2]);
+    assert_eq!(integers("tail"), [4, 5]);
+    assert_eq!(integers("copy"), [20, 2, 3, 4, 5]);
+    assert_eq!(integers("last_two"), [4, 5]);
+    assert!(integers("empty").is_empty());
+    assert!(matches!(value("last"), Object::Integer(5)));
+    assert!(matches!(value("word"), Object::String(word) if word == "ell"));
+    assert!(matches!(value("final"), Object::String(last) if last == "o"));
+    assert_eq!(integers("computed"), [2, 3]);
+
+    let cases = [
+        ("let a: array = [1, 2, 3]; a[1..4];", "Cannot slice 1..4 out of a sequence with length 3!"),
+        ("let a: array = [1, 2, 3]; a[2..1];", "Cannot slice 2..1 out of a sequence with length 3!"),
+        ("let a: array = [1, 2, 3]; a[-4..];", "Cannot slice -4..3 out of a sequence with length 3!"),
+        ("let a: array = [1, 2, 3]; a[-4];", "Index -4 is out of range!"),
+        ("let a: array = [1, 2, 3]; a[1.5..];", "Expected type IntegerType but found Float(1.5)!"),
+    ];
+
+    for (program, message) in cases {
+        let err = evaluate(program, &mut Environment::default()).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+
+    let err = evaluate("let a: int = 1; a[0..1];", &mut Environment::default()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<EvalError>(), Some(EvalError::CannotArrayAccess(..))));
+}
+
+#[test]
+fn test_format_strings() {
+    let program = "let x: int = 42;
+let y: float = 3.14159;
+let name: string = \"p\";
+let point: (int, string) = (1, \"a\");
+let grid: array = [[1, 2], [3]];
+let ages: map<string, int> = { \"bob\": 27 };
+let message: string = f\"x = {x}, y = {y:.2}, name = {name:>3}|{name:-<3}|{name:^5}|\";
+let nested: string = f\"{point} {grid} {ages} {{braces}} {to_upper(name)}\";
+let padded: string = format(x, \"06\");
+let rounded: string = format(2.5d, \".0\");
+let cut: string = format(\"abcdef\", \".3\");
+let text: string = to_string(grid) + to_string(1.0) + to_string(12n) + to_string(err(x));";
+
+    let mut environment = Environment::default();
+    evaluate(program, &mut environment).unwrap();
+
+    let string = |name: &str| match environment.get(name).unwrap().value(&environment).unwrap() {
+        Object::String(value) => value,
+        obj => panic!("Expected a string but found {:?}!", obj),
+    };
+    assert_eq!(string("message"), "x = 42, y = 3.14, name =   p|p--|  p  |");
+    assert_eq!(string("nested"), "(1, \"a\") [[1, 2], [3]] {\"bob\": 27} {braces} P");
+    assert_eq!(string("padded"), "    42");
+    assert_eq!(string("rounded"), "2");
+    assert_eq!(string("cut"), "abc");
+    assert_eq!(string("text"), "[[1, 2], [3]]1.012err(42)");
+
+    let cases = [
+        ("format(1, \"x\");", "Invalid format specifier \"x\"!"),
+        ("format(1, \".2\");", "Cannot format Integer(1) with a precision!"),
+        ("f\"{undefined}\";", "Found unknown identifier undefined at line 1!"),
+        ("let a: int = 1;\nf\"a {x\";", "Found an unterminated or unbalanced format string at line 2!"),
+        ("f\"a } b\";", "Found an unterminated or unbalanced format string at line 1!"),
+    ];
+
+    for (program, message) in cases {
+        let err = evaluate(program, &mut Environment::default()).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+}
+
+#[test]
+fn test_print_cycles() {
+    let mut environment = Environment::default();
+    let address = environment.heap_mut().set(OwnerShip::Instance(Object::Null));
+    *environment.heap_mut().get_mut(address).unwrap() = OwnerShip::Instance(Object::Array(vec![
+        OwnerShip::Instance(Object::Integer(1)),
+        OwnerShip::Heap(address),
+    ]));
+
+    assert_eq!(print(&OwnerShip::Heap(address), &environment).unwrap(), "[1, ...]");
+
+    evaluate("let a: map<string, array> = {};
+a[\"self\"] = [a];", &mut environment).unwrap();
+    assert_eq!(print(environment.get("a").unwrap(), &environment).unwrap(), "{\"self\": [{...}]}");
+}
@@ -0,0 +1,176 @@
+use std::fs;
+use std::process::Command;
+use interpreter::formatter::format;
+use interpreter::lexer::Lexer;
+use interpreter::parser::ast::statement::Statement;
+use interpreter::parser::Parser;
+
+const CORPUS_PATH: &str = "res/tests/corpus";
+
+/// Syntax tree without positions, so programs with a different layout can be compared
+fn parse(program: &str) -> Vec<Statement> {
+    Parser::from_tokens(Lexer::from_string(program.to_string()).lex()).parse().unwrap()
+}
+
+#[test]
+fn test_format_layout() {
+    let program = "let   a : int=1+2*3;let b: array = [ 1,2 ] ;
+function add( x:int ,y: int ):int{ret x+y;}
+if(a>1){a=2;}else{ }
+let m: map<string, map<string, int>> = { \"k\" : {\"j\": 1} };
+let (p, [q, ..r]): (int, array) = (1, [2, 3]);
+import {  x,y } from \"lib\";
+pub   function f(): int { while (false) {} 1 }";
+
+    assert_eq!(format(program).unwrap(), "let a: int = 1 + 2 * 3;
+let b: array = [1, 2];
+function add(x: int, y: int): int {
+    ret x + y;
+}
+if (a > 1) {
+    a = 2;
+} else {}
+let m: map<string, map<string, int>> = {\"k\": {\"j\": 1}};
+let (p, [q, ..r]): (int, array) = (1, [2, 3]);
+import { x, y } from \"lib\";
+pub function f(): int {
+    while (false) {}
+    1
+}
+");
+}
+
+#[test]
+fn test_format_parentheses() {
+    let cases = [
+        ("(1 + 2) * 3", "(1 + 2) * 3"),
+        ("1 + (2 * 3)", "1 + 2 * 3"),
+        ("a - (b - c)", "a - (b - c)"),
+        ("(a - b) - c", "a - b - c"),
+        ("2 ** 3 ** 2", "2 ** 3 ** 2"),
+        ("(2 ** 3) ** 2", "(2 ** 3) ** 2"),
+        ("(-a) ** 2", "(-a) ** 2"),
+        ("-(a ** 2)", "-a ** 2"),
+        ("-(a + b) * -c", "-(a + b) * -c"),
+        ("-(-a)", "-(-a)"),
+        ("(a + b)[0]", "(a + b)[0]"),
+        ("(-a)[1..]", "(-a)[1..]"),
+        ("(a = 1) + b", "(a = 1) + b"),
+        ("!(a == b) && (c || d)", "!(a == b) && (c || d)"),
+        ("a & (b | c) << 1", "a & (b | c) << 1"),
+    ];
+
+    for (program, expected) in cases {
+        let formatted = format(program).unwrap();
+        assert_eq!(formatted.trim_end(), expected, "Formatted {:?} wrongly!", program);
+        assert_eq!(parse(&formatted), parse(program), "Formatting changed the meaning of {:?}!", program);
+    }
+}
+
+#[test]
+fn test_format_comments() {
+    let program = "// Header
+
+
+let a: int = 1;   // one
+let b: int = 2; let c: int = 3; // shared
+function f(x: int): int { // opening
+  // inside
+
+  if (x > 1) { ret x; } // after the if
+  x
+  // end of the body
+}
+// end of the file";
+
+    assert_eq!(format(program).unwrap(), "// Header
+
+let a: int = 1; // one
+let b: int = 2;
+let c: int = 3; // shared
+function f(x: int): int {
+    // opening
+    // inside
+
+    if (x > 1) {
+        ret x;
+    } // after the if
+    x
+    // end of the body
+}
+// end of the file
+");
+}
+
+#[test]
+fn test_format_moved_comments() {
+    let program = "if (c) {
+    a = 1;
+} // closing
+else {
+    a = 2;
+}
+let xs: array = [
+    1, // one
+    2
+];
+let y: int = 1;
+if (c) { 1 } else { 2 } // same line";
+
+    let expected = "if (c) {
+    a = 1;
+} // closing
+else {
+    a = 2;
+}
+// one
+let xs: array = [1, 2];
+let y: int = 1;
+if (c) {
+    1
+} else {
+    2
+} // same line
+";
+    assert_eq!(format(program).unwrap(), expected);
+    assert_eq!(format(expected).unwrap(), expected, "Formatting moved comments is not idempotent!");
+}
+
+#[test]
+fn test_format_strings() {
+    let program = "let s: string = f\"{a:>5} {{x}} {join(xs, \",\"):*^8.2} {b:0<3}\";";
+    assert_eq!(format(program).unwrap().trim_end(), program);
+}
+
+#[test]
+fn test_format_corpus() {
+    for entry in fs::read_dir(CORPUS_PATH).unwrap() {
+        let path = entry.unwrap().path();
+        let program = fs::read_to_string(&path).unwrap();
+
+        let formatted = format(&program).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted, "Formatting {} is not idempotent!", path.display());
+        assert_eq!(parse(&formatted), parse(&program), "Formatting changed the meaning of {}!", path.display());
+    }
+}
+
+#[test]
+fn test_format_invalid_program() {
+    assert!(format("let a: int = ;").is_err(), "Programs which cannot be parsed cannot be formatted!");
+}
+
+#[test]
+fn test_format_command() {
+    let path = std::env::temp_dir().join(format!("format_command_{}.p", std::process::id()));
+    fs::write(&path, "let a:int=1;").unwrap();
+    let run = |arguments: &[&str]| Command::new(env!("CARGO_BIN_EXE_interpreter")).arg("fmt").args(arguments).arg(&path).output().unwrap();
+
+    assert!(!run(&["--check"]).status.success(), "Check passed for an unformatted file!");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "let a:int=1;", "Check changed the file!");
+
+    assert!(run(&[]).status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "let a: int = 1;\n");
+    assert!(run(&["--check"]).status.success(), "Check failed for a formatted file!");
+
+    fs::remove_file(&path).unwrap();
+}
@@ -1,14 +1,14 @@
 use interpreter::lexer::{keyword_map, Lexer};
-use interpreter::lexer::token::Token;
+use interpreter::lexer::token::{Span, Token};
 
-const LEXER_TEST_PATH: &'static str = "res/tests/lexer.txt";
-const LEXER_TEST_STRING: &'static str = "abc 123 123.3 \"askdlk\" true false ; : , . ( ) { } [ ] = + - * / % ! && || == != > < >= <= function let if while int float string bool ret err null array";
+const LEXER_TEST_PATH: &str = "res/tests/lexer.txt";
+const LEXER_TEST_STRING: &str = "abc 123 123.3 \"askdlk\" true false ; : , . ( ) { } [ ] = + - * / % ! && || == != > < >= <= function let if while int float string bool ret err null array bigint decimal 12n 1.5d 3d | ^ ~ << >> ** & map .. import pub";
 
 #[test]
 fn test_keywords() {
     let keywords = keyword_map();
 
-    assert_eq!(keywords.len(), 15, "Keywords length do not match with the actual amount of keywords!")
+    assert_eq!(keywords.len(), 20, "Keywords length do not match with the actual amount of keywords!")
 }
 
 #[test]
@@ -74,10 +74,95 @@ fn test_lexer_lex() {
         Token::Return,
         Token::Error,
         Token::NullType,
-        Token::Array,
+        Token::ArrayType,
+        Token::BigIntegerType,
+        Token::DecimalType,
+        Token::BigInteger("12".to_string()),
+        Token::Decimal("1.5".to_string()),
+        Token::Decimal("3".to_string()),
+        Token::BitOr,
+        Token::BitXor,
+        Token::BitNot,
+        Token::ShiftLeft,
+        Token::ShiftRight,
+        Token::Power,
+        Token::SingleAnd,
+        Token::Map,
+        Token::DotDot,
+        Token::Import,
+        Token::Public,
     ];
 
     let mut lexer = Lexer::from_path(LEXER_TEST_PATH).unwrap();
 
     assert_eq!(lexer.lex(), tokens, "Lexer generated the wrong tokens!")
-}
\ No newline at end of file
+}
+#[test]
+fn test_lexer_spans() {
+    let mut lexer = Lexer::from_string("let a: int = 1;\n  a".to_string());
+    let spans = lexer.lex_spanned().into_iter().map(|(_, span)| span).collect::<Vec<Span>>();
+
+    assert_eq!(spans[0], Span::new(1, 1));
+    assert_eq!(spans[1], Span::new(1, 5));
+    assert_eq!(spans[6], Span::new(1, 15));
+    assert_eq!(spans[7], Span::new(2, 3));
+}
+
+#[test]
+fn test_lexer_ranges() {
+    let mut lexer = Lexer::from_string("1..3 1.5..2".to_string());
+
+    assert_eq!(lexer.lex(), vec![
+        Token::Integer("1".to_string()),
+        Token::DotDot,
+        Token::Integer("3".to_string()),
+        Token::Float("1.5".to_string()),
+        Token::DotDot,
+        Token::Integer("2".to_string()),
+    ]);
+}
+
+#[test]
+fn test_lexer_format_strings() {
+    use interpreter::lexer::token::FormatSegment;
+
+    let mut lexer = Lexer::from_string("f\"x = {x}, {{y}} {join(a, \"}\"):>8.2}\"".to_string());
+
+    assert_eq!(lexer.lex(), vec![
+        Token::FormatString(vec![
+            FormatSegment::Literal("x = ".to_string()),
+            FormatSegment::Value { source: "x".to_string(), spec: String::new() },
+            FormatSegment::Literal(", {y} ".to_string()),
+            FormatSegment::Value { source: "join(a, \"}\")".to_string(), spec: ">8.2".to_string() },
+        ]),
+    ]);
+
+    for program in ["f\"{x\"", "f\"}\""] {
+        assert_eq!(Lexer::from_string(program.to_string()).lex(), vec![Token::InvalidFormatString], "{} should be invalid!", program);
+    }
+}
+
+#[test]
+fn test_lexer_comments() {
+    use interpreter::lexer::token::Comment;
+
+    let mut lexer = Lexer::from_string("// first\nlet a: int = 4 / 2; // second\n//".to_string());
+    let (tokens, comments) = lexer.lex_with_comments();
+
+    assert_eq!(tokens.iter().map(|(token, _)| token.clone()).collect::<Vec<Token>>(), vec![
+        Token::Let,
+        Token::Identifier("a".to_string()),
+        Token::Colon,
+        Token::IntegerType,
+        Token::Assign,
+        Token::Integer("4".to_string()),
+        Token::Divide,
+        Token::Integer("2".to_string()),
+        Token::Semicolon,
+    ]);
+    assert_eq!(comments, vec![
+        Comment { text: " first".to_string(), span: Span::new(1, 1), trailing: false },
+        Comment { text: " second".to_string(), span: Span::new(2, 21), trailing: true },
+        Comment { text: "".to_string(), span: Span::new(3, 1), trailing: false },
+    ]);
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::process::Command;
+use interpreter::lexer::token::Span;
+use interpreter::linter::{lint, Config, Level, Lint};
+
+/// Lints with their positions, found with the default levels
+fn warnings(program: &str) -> Vec<(Lint, Span)> {
+    lint(program, &Config::default()).unwrap().iter().map(|warning| (warning.lint, warning.span)).collect()
+}
+
+#[test]
+fn test_lint_codes() {
+    assert_eq!(Lint::UnusedVariable.code(), "L001");
+    assert_eq!(Lint::MissingReturn.name(), "missing-return");
+    assert_eq!(Lint::parse("L006"), Some(Lint::FloatEquality));
+    assert_eq!(Lint::parse("unknown-directive"), Some(Lint::UnknownDirective));
+    assert_eq!(Lint::parse("shadowed-binding"), Some(Lint::ShadowedBinding));
+    assert_eq!(Lint::parse("unknown"), None);
+}
+
+#[test]
+fn test_unused_bindings() {
+    assert_eq!(warnings("let a: int = 1;
+let b: int = a;
+let _c: int = 3;
+function f(x: int, y: int): int { x }
+pub let d: int = 4;
+f(b, 2);"), [
+        (Lint::UnusedParameter, Span::new(4, 20)),
+    ]);
+    assert_eq!(warnings("let a: int = 1;"), [(Lint::UnusedVariable, Span::new(1, 5))]);
+}
+
+#[test]
+fn test_shadowed_bindings() {
+    assert_eq!(warnings("let a: int = 1;
+let a: int = a + 1;
+function f(a: int): int { a }
+print(f(a));"), [
+        (Lint::ShadowedBinding, Span::new(2, 5)),
+        (Lint::ShadowedBinding, Span::new(3, 12)),
+    ]);
+}
+
+#[test]
+fn test_unreachable_code() {
+    assert_eq!(warnings("function f(x: int): int {
+    if (x > 0) { ret 1; } else { ret 2; }
+    print(x);
+    x
+}
+print(f(1));"), [(Lint::UnreachableCode, Span::new(3, 5))]);
+}
+
+#[test]
+fn test_infinite_loop() {
+    assert_eq!(warnings("while (true) { print(1); }"), [(Lint::InfiniteLoop, Span::new(1, 1))]);
+    assert_eq!(warnings("function f(): int {
+    while (true) { if (true) { ret 1; } }
+}
+print(f());"), []);
+}
+
+#[test]
+fn test_float_equality() {
+    assert_eq!(warnings("let a: float = 1.5;
+print(a == 1.5);
+print(0.1 + 0.2 != 0.3);
+print(to_float(1) == 1.0);
+print(1 == 1);"), [
+        (Lint::FloatEquality, Span::new(2, 7)),
+        (Lint::FloatEquality, Span::new(3, 1)),
+        (Lint::FloatEquality, Span::new(4, 7)),
+    ]);
+}
+
+#[test]
+fn test_missing_return() {
+    assert_eq!(warnings("function a(x: int): int { if (x > 0) { ret 1; } }
+function b(x: int): int { if (x > 0) { 1 } else { 2 } }
+function c(x: int): int { while (x > 0) { x = x - 1; } }
+function d(x: int): null { print(x); }
+print(a(1) + b(1) + c(1));
+d(1);"), [
+        (Lint::MissingReturn, Span::new(1, 10)),
+        (Lint::MissingReturn, Span::new(3, 10)),
+    ]);
+}
+
+#[test]
+fn test_lint_directives() {
+    let program = "// lint-file: allow(L001)
+let a: int = 1;
+// lint: deny(unused-variable)
+let b: int = 2;
+let c: int = 3; // lint: warn(L001)
+while (true) {} // lint: allow(infinite-loop)";
+
+    let levels = lint(program, &Config::default()).unwrap().iter()
+        .map(|warning| (warning.span.line, warning.level))
+        .collect::<Vec<(usize, Level)>>();
+    assert_eq!(levels, [(4, Level::Deny), (5, Level::Warn)]);
+
+    let mut config = Config::default();
+    config.set(Lint::InfiniteLoop, Level::Deny);
+    config.set(Lint::UnusedVariable, Level::Deny);
+    let levels = lint(program, &config).unwrap().iter()
+        .map(|warning| (warning.span.line, warning.level))
+        .collect::<Vec<(usize, Level)>>();
+    assert_eq!(levels, [(4, Level::Deny), (5, Level::Warn)], "Comments have to win over the configuration!");
+}
+
+#[test]
+fn test_unknown_directives() {
+    let program = "// lint-file: forbid(L001)
+let _a: int = 1; // lint: allow(W001, unused-variable)";
+
+    let warnings = lint(program, &Config::default()).unwrap().iter()
+        .map(|warning| (warning.lint, warning.span, warning.message.clone()))
+        .collect::<Vec<(Lint, Span, String)>>();
+    assert_eq!(warnings, [
+        (Lint::UnknownDirective, Span::new(1, 1), "Found unknown lint level forbid in a lint comment!".to_string()),
+        (Lint::UnknownDirective, Span::new(2, 18), "Found unknown lint W001 in a lint comment!".to_string()),
+    ]);
+}
+
+#[test]
+fn test_lint_command() {
+    let path = std::env::temp_dir().join(format!("lint_command_{}.p", std::process::id()));
+    fs::write(&path, "let a: int = 1;\n").unwrap();
+    let run = |arguments: &[&str]| Command::new(env!("CARGO_BIN_EXE_interpreter")).arg("lint").args(arguments).arg(&path).output().unwrap();
+
+    let output = run(&[]);
+    assert!(output.status.success(), "Warnings failed the command!");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(":1:5: warning[L001 unused-variable]: Variable a is never used!"), "Found {}!", stdout);
+
+    assert!(!run(&["--deny", "L001"]).status.success());
+    assert!(run(&["--allow", "unused-variable", "--deny", "L006"]).status.success());
+    assert!(!run(&["--deny", "unknown"]).status.success());
+
+    fs::remove_file(&path).unwrap();
+}
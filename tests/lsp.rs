@@ -0,0 +1,271 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use lsp_types::Url;
+use serde_json::{json, Value};
+
+const MODULES_PATH: &str = "res/tests/modules";
+
+const PROGRAM: &str = "import { square } from \"math\";
+let total: int = square(3);
+function describe(value: int): string {
+    let text: string = to_string(value);
+    text
+}
+let name: string = describe(total);
+";
+
+/// Editor which talks to the language server binary over its standard input and output
+struct Client {
+    process: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    id: i64,
+    // Notifications which arrived while waiting for a response
+    notifications: Vec<Value>,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = process.stdin.take().unwrap();
+        let output = BufReader::new(process.stdout.take().unwrap());
+        let mut client = Self { process, input, output, id: 0, notifications: vec![] };
+
+        let result = client.request("initialize", json!({ "processId": null, "rootUri": null, "capabilities": {} }));
+        assert_eq!(result["capabilities"]["hoverProvider"], json!(true));
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.input.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.output.read_line(&mut header).unwrap();
+            let header = header.trim();
+            if header.is_empty() { break }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": self.id, "method": method, "params": params }));
+        loop {
+            let message = self.receive();
+            if message["id"] == json!(self.id) {
+                assert!(message.get("error").is_none(), "Request {} failed with {}!", method, message);
+                return message["result"].clone()
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Waits for the next diagnostics the server publishes
+    fn diagnostics(&mut self) -> Vec<Value> {
+        let position = self.notifications.iter().position(|message| message["method"] == "textDocument/publishDiagnostics");
+        let message = match position {
+            Some(position) => self.notifications.remove(position),
+            None => loop {
+                let message = self.receive();
+                if message["method"] == "textDocument/publishDiagnostics" { break message }
+            },
+        };
+        message["params"]["diagnostics"].as_array().unwrap().clone()
+    }
+
+    fn open(&mut self, uri: &Url, text: &str) -> Vec<Value> {
+        self.notify("textDocument/didOpen", json!({
+            "textDocument": { "uri": uri, "languageId": "p", "version": 1, "text": text },
+        }));
+        self.diagnostics()
+    }
+
+    fn change(&mut self, uri: &Url, version: i32, text: &str) -> Vec<Value> {
+        self.notify("textDocument/didChange", json!({
+            "textDocument": { "uri": uri, "version": version },
+            "contentChanges": [{ "text": text }],
+        }));
+        self.diagnostics()
+    }
+
+    /// Requests a method which takes a position inside the document
+    fn at(&mut self, method: &str, uri: &Url, line: u32, character: u32) -> Value {
+        self.request(method, json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        }))
+    }
+
+    fn shutdown(mut self) -> ExitStatus {
+        assert_eq!(self.request("shutdown", Value::Null), Value::Null);
+        self.notify("exit", Value::Null);
+        self.process.wait().unwrap()
+    }
+}
+
+fn document() -> Url {
+    Url::from_file_path(Path::new(MODULES_PATH).canonicalize().unwrap().join("main.p")).unwrap()
+}
+
+fn range(line: u32, start: u32, end: u32) -> Value {
+    json!({ "start": { "line": line, "character": start }, "end": { "line": line, "character": end } })
+}
+
+#[test]
+fn test_diagnostics() {
+    let mut client = Client::start();
+    let uri = document();
+    // Lints are published as warnings with their code
+    let diagnostics = client.open(&uri, PROGRAM);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["range"], range(6, 4, 8));
+    assert_eq!(diagnostics[0]["severity"], json!(2));
+    assert_eq!(diagnostics[0]["code"], json!("L001"));
+
+    let diagnostics = client.change(&uri, 2, "let a: int = 1;\nlet b: int = a +;");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 1, "character": 16 }));
+    assert_eq!(diagnostics[0]["severity"], json!(1));
+
+    let messages = |diagnostics: Vec<Value>| diagnostics.iter()
+        .map(|diagnostic| (diagnostic["range"].clone(), diagnostic["message"].as_str().unwrap().to_string()))
+        .collect::<Vec<(Value, String)>>();
+    let diagnostics = client.change(&uri, 3, "let a: int = \"text\";\nlet b: int = a + c;");
+    assert_eq!(messages(diagnostics), [
+        (range(0, 4, 5), "Expected a value of type int but found string!".to_string()),
+        (range(1, 4, 5), "Variable b is never used!".to_string()),
+        (range(1, 17, 18), "Found unknown identifier c at line 2!".to_string()),
+    ]);
+    let diagnostics = client.change(&uri, 4, "let _a: int = 1;\nimport \"missing\";");
+    assert_eq!(messages(diagnostics), [(range(1, 0, 6), "Cannot find the module missing imported at line 2!".to_string())]);
+
+    client.notify("textDocument/didClose", json!({ "textDocument": { "uri": uri } }));
+    assert_eq!(client.diagnostics(), Vec::<Value>::new());
+    assert!(client.shutdown().success());
+}
+
+#[test]
+fn test_hover_and_definition() {
+    let mut client = Client::start();
+    let uri = document();
+    client.open(&uri, PROGRAM);
+
+    let hover = client.at("textDocument/hover", &uri, 6, 20);
+    assert_eq!(hover["contents"]["value"], json!("```p\nfunction describe(value: int): string\n```"));
+    let hover = client.at("textDocument/hover", &uri, 6, 30);
+    assert_eq!(hover["contents"]["value"], json!("```p\nlet total: int\n```"));
+    let hover = client.at("textDocument/hover", &uri, 3, 34);
+    assert_eq!(hover["contents"]["value"], json!("```p\nvalue: int\n```"));
+    assert_eq!(client.at("textDocument/hover", &uri, 1, 13), Value::Null);
+
+    let definition = client.at("textDocument/definition", &uri, 6, 30);
+    assert_eq!(definition, json!({ "uri": uri, "range": range(1, 4, 9) }));
+    let definition = client.at("textDocument/definition", &uri, 4, 5);
+    assert_eq!(definition, json!({ "uri": uri, "range": range(3, 8, 12) }));
+    let definition = client.at("textDocument/definition", &uri, 1, 19);
+    assert_eq!(definition, json!({ "uri": uri, "range": range(0, 9, 15) }));
+
+    assert!(client.shutdown().success());
+}
+
+#[test]
+fn test_document_symbols() {
+    let mut client = Client::start();
+    let uri = document();
+    client.open(&uri, PROGRAM);
+
+    let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": uri } }));
+    let outline = symbols.as_array().unwrap().iter()
+        .map(|symbol| {
+            let children = symbol["children"].as_array().into_iter().flatten()
+                .map(|child| child["name"].as_str().unwrap())
+                .collect::<Vec<&str>>();
+            (symbol["name"].as_str().unwrap(), symbol["kind"].as_u64().unwrap(), children)
+        })
+        .collect::<Vec<_>>();
+    // Kinds of the protocol, 12 is a function and 13 a variable
+    assert_eq!(outline, [("total", 13, vec![]), ("describe", 12, vec!["text"]), ("name", 13, vec![])]);
+
+    assert_eq!(symbols[1]["range"], json!({ "start": { "line": 2, "character": 0 }, "end": { "line": 5, "character": 1 } }));
+    assert_eq!(symbols[1]["selectionRange"], range(2, 9, 17));
+    assert!(client.shutdown().success());
+}
+
+#[test]
+fn test_completion() {
+    let mut client = Client::start();
+    let uri = document();
+    client.open(&uri, PROGRAM);
+
+    let labels = |items: Value| items.as_array().unwrap().iter()
+        .map(|item| item["label"].as_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+
+    let inside = labels(client.at("textDocument/completion", &uri, 4, 4));
+    for label in ["let", "while", "to_string", "square", "total", "describe", "value", "text"] {
+        assert!(inside.contains(&label.to_string()), "Missing {} in {:?}!", label, inside);
+    }
+    assert!(!inside.contains(&"name".to_string()));
+
+    let outside = labels(client.at("textDocument/completion", &uri, 7, 0));
+    assert!(outside.contains(&"name".to_string()));
+    assert!(!outside.contains(&"value".to_string()) && !outside.contains(&"text".to_string()));
+
+    // Completion keeps working with the last analysis while the document cannot be parsed
+    client.change(&uri, 2, &format!("{}let broken: int = ", PROGRAM));
+    assert!(labels(client.at("textDocument/completion", &uri, 7, 0)).contains(&"describe".to_string()));
+    assert!(client.shutdown().success());
+}
+
+#[test]
+fn test_utf16_positions() {
+    let mut client = Client::start();
+    let uri = document();
+    // Every emoji is a single character but two UTF-16 code units
+    let diagnostics = client.open(&uri, "let s: string = \"😀😀\"; let total: int = c;\ntotal;");
+    let unknown = diagnostics.iter().find(|diagnostic| diagnostic["message"] == "Found unknown identifier c at line 1!").unwrap();
+    assert_eq!(unknown["range"], range(0, 41, 42));
+
+    client.change(&uri, 2, "let s: string = \"😀😀\"; let total: int = 1;\ntotal;");
+    let hover = client.at("textDocument/hover", &uri, 0, 29);
+    assert_eq!(hover["contents"]["value"], json!("```p\nlet total: int\n```"));
+    let definition = client.at("textDocument/definition", &uri, 1, 2);
+    assert_eq!(definition, json!({ "uri": uri, "range": range(0, 28, 33) }));
+    assert!(client.shutdown().success());
+}
+
+#[test]
+fn test_broken_notifications_and_texts() {
+    let mut client = Client::start();
+    let uri = document();
+    client.notify("textDocument/didOpen", json!({ "textDocument": { "uri": uri } }));
+    client.open(&uri, "let total: int = 1;\ntotal;");
+
+    // Positions are resolved in the last text which could be parsed, not in the broken one
+    client.change(&uri, 2, "😀😀😀 let total: int = 1;\ntotal;");
+    let definition = client.at("textDocument/definition", &uri, 1, 2);
+    assert_eq!(definition, json!({ "uri": uri, "range": range(0, 4, 9) }));
+    assert!(client.shutdown().success());
+}
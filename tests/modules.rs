@@ -0,0 +1,86 @@
+use interpreter::error::ResolveError;
+use interpreter::evaluator::environment::Environment;
+use interpreter::evaluator::object::Object;
+use interpreter::evaluator::{execute, Backend};
+use interpreter::lexer::Lexer;
+use interpreter::parser::Parser;
+use interpreter::resolver::Resolver;
+
+const MODULES_PATH: &str = "res/tests/modules";
+
+fn evaluate(program: &str, backend: Backend, environment: &mut Environment) -> anyhow::Result<()> {
+    let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+    let mut ast = Parser::from_spanned_tokens(tokens).parse()?;
+    Resolver::from_environment(environment).in_directory(MODULES_PATH).resolve(&mut ast)?;
+    execute(ast, backend, environment)
+}
+
+fn integer(name: &str, environment: &Environment) -> i128 {
+    match environment.get(name).unwrap().value(environment).unwrap() {
+        Object::Integer(value) => value,
+        obj => panic!("Expected an integer but found {:?}!", obj),
+    }
+}
+
+#[test]
+fn test_imports() {
+    let program = "import \"math\";
+import { area, unit } from \"geometry/area\";
+let a: int = math.square(3);
+let b: int = math.quadruple(5);
+let c: int = area(4, 4) + area(2, 3) + unit;
+let runs: int = math.runs;";
+
+    for backend in [Backend::Tree, Backend::Bytecode] {
+        let mut environment = Environment::default();
+        evaluate(program, backend, &mut environment).unwrap();
+        environment.collect_garbage();
+
+        assert_eq!(integer("a", &environment), 9);
+        assert_eq!(integer("b", &environment), 20);
+        assert_eq!(integer("c", &environment), 23);
+        // Both imports of the math module share the one which ran first
+        assert_eq!(integer("runs", &environment), 1);
+    }
+}
+
+#[test]
+fn test_imports_across_programs() {
+    let mut environment = Environment::default();
+    evaluate("import { square } from \"math\";", Backend::Tree, &mut environment).unwrap();
+    evaluate("import \"math\"; let a: int = square(2) + math.square(3) + math.runs;", Backend::Tree, &mut environment).unwrap();
+
+    assert_eq!(integer("a", &environment), 14);
+}
+
+#[test]
+fn test_private_globals() {
+    let mut environment = Environment::default();
+    let err = evaluate("import \"math\"; let a: int = math.twice(2);", Backend::Tree, &mut environment).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ResolveError>(), Some(ResolveError::UnknownIdentifier(name, _)) if name == "math.twice"));
+
+    let err = evaluate("import { factor } from \"math\";", Backend::Tree, &mut environment).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ResolveError>(), Some(ResolveError::NotExported(name, _, _)) if name == "factor"));
+
+    let err = evaluate("import \"math\"; let a: int = twice(2);", Backend::Tree, &mut environment).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ResolveError>(), Some(ResolveError::UnknownIdentifier(name, _)) if name == "twice"));
+}
+
+#[test]
+fn test_import_errors() {
+    let mut environment = Environment::default();
+    let err = evaluate("import \"cycle_a\";", Backend::Tree, &mut environment).unwrap_err();
+    match err.downcast_ref::<ResolveError>() {
+        Some(ResolveError::ImportCycle(cycle)) => {
+            let files = cycle.split(" -> ").map(|path| path.rsplit('/').next().unwrap()).collect::<Vec<&str>>();
+            assert_eq!(files, ["cycle_a.p", "cycle_b.p", "cycle_a.p"]);
+        }
+        _ => panic!("Expected import cycle error but found {:?}!", err),
+    }
+
+    let err = evaluate("import \"missing\";", Backend::Tree, &mut environment).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ResolveError>(), Some(ResolveError::ModuleNotFound(path, _)) if path == "missing"));
+
+    let err = evaluate("if (true) { import \"math\"; }", Backend::Tree, &mut environment).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ResolveError>(), Some(ResolveError::NestedImport(_))));
+}
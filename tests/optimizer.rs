@@ -0,0 +1,101 @@
+use std::fs;
+use interpreter::evaluator::environment::Environment;
+use interpreter::evaluator::printer::print;
+use interpreter::evaluator::{execute, Backend};
+use interpreter::lexer::Lexer;
+use interpreter::optimizer::Optimizer;
+use interpreter::parser::ast::expression::Expression;
+use interpreter::parser::ast::statement::Statement;
+use interpreter::parser::Parser;
+use interpreter::resolver::Resolver;
+
+const CORPUS_PATH: &str = "res/tests/corpus";
+
+fn parse(program: &str) -> Vec<Statement> {
+    Parser::from_tokens(Lexer::from_string(program.to_string()).lex()).parse().unwrap()
+}
+
+fn optimize(program: &str) -> Vec<Statement> {
+    Optimizer::default().optimize(parse(program))
+}
+
+fn run(mut ast: Vec<Statement>) -> String {
+    let mut environment = Environment::default();
+    let result = Resolver::from_environment(&environment).resolve(&mut ast)
+        .and_then(|_| execute(ast, Backend::Tree, &mut environment));
+    let mut outcome = match result {
+        Ok(()) => "Ok".to_string(),
+        Err(err) => err.root_cause().to_string(),
+    };
+
+    for name in environment.stack().globals() {
+        outcome.push_str(&format!("\n{} = {}", name, print(environment.get(name).unwrap(), &environment).unwrap()));
+    }
+    outcome
+}
+
+#[test]
+fn test_constant_folding() {
+    assert_eq!(optimize("60 * 60 * 24;"), parse("86400;"));
+    assert_eq!(optimize("\"a\" + \"b\";"), parse("\"ab\";"));
+    assert_eq!(optimize("-(1.5 * 2) > 2;"), parse("false;"));
+    assert_eq!(optimize("!(1 == 2);"), parse("true;"));
+    assert_eq!(optimize("a + 2 * 3;"), parse("a + 6;"));
+    assert_eq!(optimize("1 + true;"), parse("1 + true;"), "Failing operations have to fail at runtime!");
+}
+
+#[test]
+fn test_boolean_conditions() {
+    assert_eq!(optimize("if (a == true) { 1 }"), parse("if (a) { 1 }"));
+    assert_eq!(optimize("while (false != (a && true)) { 1 }"), parse("while (a) { 1 }"));
+    assert_eq!(optimize("if (a == false) { 1 }"), parse("if (!a) { 1 }"));
+    assert_eq!(optimize("if (!!a) { 1 }"), parse("if (a) { 1 }"));
+    assert_eq!(optimize("let b: bool = a == true;"), parse("let b: bool = a == true;"), "Only conditions get simplified!");
+}
+
+#[test]
+fn test_dead_branches() {
+    assert_eq!(optimize("if (2 > 1) { a } else { b }"), vec![Statement::Expression {
+        value: Box::new(Expression::Block { statements: vec![Box::new(parse("a;").remove(0))] })
+    }]);
+    assert_eq!(optimize("if (false) { a }"), vec![Statement::Expression {
+        value: Box::new(Expression::Block { statements: vec![] })
+    }]);
+    assert_eq!(optimize("while (1 > 2) { a }"), optimize("if (false) { a }"));
+    assert_eq!(optimize("if (false) { a } else { let b: int = 1; b }"), parse("if (true) { let b: int = 1; b }"), "Branches with declarations need their scope!");
+}
+
+#[test]
+fn test_unreachable_statements() {
+    assert_eq!(optimize("function f(): int { ret 1; let a: int = 2; a }"), parse("function f(): int { ret 1; }"));
+    assert_eq!(optimize("ret 1; 2;"), parse("ret 1; 2;"), "Top level statements keep running after a ret!");
+}
+
+#[test]
+fn test_optimized_modules() {
+    for optimize in [false, true] {
+        let mut environment = Environment::default();
+        let mut ast = parse("import { day } from \"constants\";");
+        Resolver::from_environment(&environment).in_directory("res/tests/modules").optimized(optimize).resolve(&mut ast).unwrap();
+        execute(ast, Backend::Tree, &mut environment).unwrap();
+
+        let module = environment.modules().next().unwrap();
+        let folded = match &module.statements[..] {
+            [Statement::Export { statement }] => matches!(&**statement, Statement::Let { value, .. } if **value == Expression::Integer { value: "86400".to_string() }),
+            statements => panic!("Expected a single export but found {:?}!", statements),
+        };
+        assert_eq!(folded, optimize);
+        assert_eq!(print(environment.get("day").unwrap(), &environment).unwrap(), "86400");
+    }
+}
+
+#[test]
+fn test_optimized_corpus() {
+    let mut paths = fs::read_dir(CORPUS_PATH).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let program = fs::read_to_string(&path).unwrap();
+        assert_eq!(run(parse(&program)), run(optimize(&program)), "Optimizer changed the result of {}!", path.display());
+    }
+}
@@ -1,5 +1,8 @@
-use interpreter::lexer::token::Token;
+use std::sync::Arc;
+use interpreter::lexer::Lexer;
+use interpreter::lexer::token::{Span, Token};
 use interpreter::parser::ast::expression::Expression;
+use interpreter::parser::ast::pattern::Pattern;
 use interpreter::parser::ast::statement::Statement;
 use interpreter::parser::Parser;
 use interpreter::parser::precedences::Precedences;
@@ -169,6 +172,8 @@ fn test_call_expression() {
             arguments: vec![Box::new(Expression::Integer {
                 value: "1".to_string()
             })],
+            span: Span::default(),
+            slot: None,
         }
     )
 }
@@ -205,7 +210,11 @@ fn test_assign_expression() {
     assert_eq!(
         parser.parse_expression(Precedences::Lowest).unwrap(),
         Expression::Assign {
-            name: "a".to_string(),
+            assign_to: Box::new(Expression::Identifier {
+                name: "a".to_string(),
+                span: Span::default(),
+                slot: None,
+            }),
             value: Box::new(Expression::Integer {
                 value: "1".to_string()
             }),
@@ -377,7 +386,7 @@ pub fn test_function_statement() {
             name: "test".to_string(),
             parameter: vec![("a".to_string(), Token::IntegerType)],
             typee: Token::IntegerType,
-            body: Box::new(Expression::Block {
+            body: Arc::new(Expression::Block {
                 statements: vec![Box::new(Statement::Expression {
                     value: Box::new(Expression::Integer {
                         value: "1".to_string(),
@@ -386,4 +395,190 @@ pub fn test_function_statement() {
             }),
         }
     )
-}
\ No newline at end of file
+}
+#[test]
+fn test_bitwise_and_power_precedence() {
+    let integer = |value: &str| Box::new(Expression::Integer { value: value.to_string() });
+    let identifier = |name: &str| Box::new(Expression::Identifier { name: name.to_string(), span: Span::default(), slot: None });
+
+    // a & &b | -2 ** 3 ** 2
+    let tokens = vec![
+        Token::Identifier("a".to_string()),
+        Token::SingleAnd,
+        Token::SingleAnd,
+        Token::Identifier("b".to_string()),
+        Token::BitOr,
+        Token::Subtract,
+        Token::Integer("2".to_string()),
+        Token::Power,
+        Token::Integer("3".to_string()),
+        Token::Power,
+        Token::Integer("2".to_string()),
+    ];
+
+    let mut parser = Parser::from_tokens(tokens);
+    assert_eq!(
+        parser.parse_expression(Precedences::Lowest).unwrap(),
+        Expression::Infix {
+            left: Box::new(Expression::Infix {
+                left: identifier("a"),
+                operation: Token::SingleAnd,
+                right: Box::new(Expression::Prefix {
+                    prefix: Token::SingleAnd,
+                    value: identifier("b"),
+                }),
+            }),
+            operation: Token::BitOr,
+            right: Box::new(Expression::Prefix {
+                prefix: Token::Subtract,
+                value: Box::new(Expression::Infix {
+                    left: integer("2"),
+                    operation: Token::Power,
+                    right: Box::new(Expression::Infix {
+                        left: integer("3"),
+                        operation: Token::Power,
+                        right: integer("2"),
+                    }),
+                }),
+            }),
+        }
+    )
+}
+
+#[test]
+fn test_map_expression() {
+    let mut lexer = Lexer::from_string("let m: map<string, map<int, bool>> = { \"a\": { 1: true }, \"b\": {} };".to_string());
+    let mut parser = Parser::from_tokens(lexer.lex());
+
+    let map = |entries: Vec<(Expression, Expression)>| Expression::Map {
+        entries: entries.into_iter().map(|(key, value)| (Box::new(key), Box::new(value))).collect(),
+    };
+
+    assert_eq!(
+        parser.parse_statement().unwrap(),
+        Statement::Let {
+            name: "m".to_string(),
+            typee: Token::MapType(
+                Box::new(Token::StringType),
+                Box::new(Token::MapType(Box::new(Token::IntegerType), Box::new(Token::BooleanType))),
+            ),
+            value: Box::new(map(vec![
+                (Expression::String { value: "a".to_string() }, map(vec![
+                    (Expression::Integer { value: "1".to_string() }, Expression::Boolean { value: "true".to_string() }),
+                ])),
+                (Expression::String { value: "b".to_string() }, map(vec![])),
+            ])),
+        }
+    );
+}
+
+#[test]
+fn test_destructure_statement() {
+    let mut lexer = Lexer::from_string("let (a, [b, ..c]): (int, array) = (1, [2, 3]);".to_string());
+    let mut parser = Parser::from_tokens(lexer.lex());
+
+    let integer = |value: &str| Box::new(Expression::Integer { value: value.to_string() });
+
+    assert_eq!(
+        parser.parse_statement().unwrap(),
+        Statement::Destructure {
+            pattern: Pattern::Tuple(vec![
+                Pattern::Identifier("a".to_string()),
+                Pattern::Array(vec![Pattern::Identifier("b".to_string()), Pattern::Rest("c".to_string())]),
+            ]),
+            typee: Token::TupleType(vec![Token::IntegerType, Token::ArrayType]),
+            value: Box::new(Expression::Tuple {
+                values: vec![integer("1"), Box::new(Expression::Array { values: vec![integer("2"), integer("3")] })],
+            }),
+        }
+    );
+
+    let mut lexer = Lexer::from_string("let (a, b): (int, int, int) = (1, 2);".to_string());
+    assert!(Parser::from_tokens(lexer.lex()).parse_statement().is_err(), "Pattern and type lengths have to match!");
+}
+
+#[test]
+fn test_import_and_export_statements() {
+    let mut lexer = Lexer::from_string("import \"lib/math\"; import { a, b } from \"util\"; pub let c: int = math.pi;".to_string());
+    let mut parser = Parser::from_tokens(lexer.lex());
+
+    assert_eq!(
+        parser.parse().unwrap(),
+        vec![
+            Statement::Import { path: "lib/math".to_string(), names: None, span: Span::default(), module: None },
+            Statement::Import {
+                path: "util".to_string(),
+                names: Some(vec![("a".to_string(), "a".to_string()), ("b".to_string(), "b".to_string())]),
+                span: Span::default(),
+                module: None,
+            },
+            Statement::Export {
+                statement: Box::new(Statement::Let {
+                    name: "c".to_string(),
+                    typee: Token::IntegerType,
+                    value: Box::new(Expression::Identifier { name: "math.pi".to_string(), span: Span::default(), slot: None }),
+                }),
+            },
+        ]
+    );
+
+    let mut lexer = Lexer::from_string("pub 1;".to_string());
+    assert!(Parser::from_tokens(lexer.lex()).parse().is_err(), "Only declarations can be exported!");
+    let mut lexer = Lexer::from_string("import { a } \"util\";".to_string());
+    assert!(Parser::from_tokens(lexer.lex()).parse().is_err(), "Named imports need a from!");
+}
+
+#[test]
+fn test_slice_expression() {
+    let identifier = |name: &str, column: usize| Box::new(Expression::Identifier { name: name.to_string(), span: Span::new(1, column), slot: None });
+    let integer = |value: &str| Box::new(Expression::Integer { value: value.to_string() });
+
+    let cases = [
+        ("a[1..3]", Some(integer("1")), Some(integer("3"))),
+        ("a[..n]", None, Some(identifier("n", 5))),
+        ("a[1..]", Some(integer("1")), None),
+        ("a[..]", None, None),
+    ];
+
+    for (program, start, end) in cases {
+        let mut lexer = Lexer::from_string(program.to_string());
+        let mut parser = Parser::from_spanned_tokens(lexer.lex_spanned());
+
+        assert_eq!(
+            parser.parse_expression(Precedences::Lowest).unwrap(),
+            Expression::Slice { source: identifier("a", 1), start, end },
+            "Wrong slice for {}!", program
+        );
+    }
+}
+
+#[test]
+fn test_format_string_expression() {
+    use interpreter::evaluator::format::{Alignment, FormatSpec};
+    use interpreter::parser::ast::expression::FormatPart;
+
+    let mut lexer = Lexer::from_string("f\"{a + 1:*^6.2}!\"".to_string());
+    let mut parser = Parser::from_tokens(lexer.lex());
+
+    assert_eq!(
+        parser.parse_expression(Precedences::Lowest).unwrap(),
+        Expression::Format {
+            parts: vec![
+                FormatPart::Value {
+                    value: Box::new(Expression::Infix {
+                        left: Box::new(Expression::Identifier { name: "a".to_string(), span: Span::default(), slot: None }),
+                        operation: Token::Add,
+                        right: Box::new(Expression::Integer { value: "1".to_string() }),
+                    }),
+                    spec: FormatSpec { fill: '*', alignment: Some(Alignment::Center), width: 6, precision: Some(2) },
+                },
+                FormatPart::Literal("!".to_string()),
+            ],
+        }
+    );
+
+    for program in ["f\"{a:.}\"", "f\"{a:<<<}\"", "f\"{}\"", "f\"{a b}\""] {
+        let mut lexer = Lexer::from_string(program.to_string());
+        assert!(Parser::from_tokens(lexer.lex()).parse_expression(Precedences::Lowest).is_err(), "{} should not parse!", program);
+    }
+}
@@ -0,0 +1,107 @@
+use interpreter::evaluator::object::Object;
+use interpreter::repl::command::Command;
+use interpreter::repl::editor::is_complete;
+use interpreter::repl::helper::{complete, highlight};
+use interpreter::repl::Session;
+
+#[test]
+fn test_complete_input() {
+    let complete = [
+        "1 + 2",
+        "let a: int = 1;",
+        "function f(a: int): int {\n    ret a;\n}",
+        "if (true) { 1 } else { 2 }",
+        "let a: array = [1,\n2];",
+        // Errors are reported instead of waiting for more lines
+        "let 1",
+        "}",
+        ":ast function f(): int {",
+    ];
+    for input in complete {
+        assert!(is_complete(input), "{:?} is complete!", input);
+    }
+
+    let incomplete = [
+        "function f(a: int): int {",
+        "function f(a: int): int {\n    ret a;",
+        "let a: array = [1,",
+        "call(1,\n2",
+        "let a: int = 1",
+        "1 +",
+    ];
+    for input in incomplete {
+        assert!(!is_complete(input), "{:?} is incomplete!", input);
+    }
+}
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(Command::parse(":tokens 1 + 2").unwrap(), Command::Tokens("1 + 2".to_string()));
+    assert_eq!(Command::parse(":ast  let a: int = 1;").unwrap(), Command::Ast("let a: int = 1;".to_string()));
+    assert_eq!(Command::parse(":type a").unwrap(), Command::Type("a".to_string()));
+    assert_eq!(Command::parse(":load res/main.p").unwrap(), Command::Load("res/main.p".to_string()));
+    assert_eq!(Command::parse(":env").unwrap(), Command::Env);
+    assert_eq!(Command::parse(":reset").unwrap(), Command::Reset);
+    assert_eq!(Command::parse(":time").unwrap(), Command::Time);
+    assert_eq!(Command::parse(":help").unwrap(), Command::Help);
+
+    assert!(Command::parse(":tokens").is_err(), "Tokens needs a program!");
+    assert!(Command::parse(":load ").is_err(), "Load needs a file!");
+    assert!(Command::parse(":unknown").is_err(), "Unknown commands are errors!");
+}
+
+#[test]
+fn test_session_commands() {
+    let mut session = Session::default();
+    session.run("let a: int = 41;".to_string());
+    session.run("a = a + 1;".to_string());
+    assert!(matches!(session.environment.get("a").unwrap().value(&session.environment).unwrap(), Object::Integer(42)));
+
+    session.run(":time".to_string());
+    assert!(session.timing);
+
+    session.run(":load res/tests/corpus/fibonacci.p".to_string());
+    assert!(session.environment.stack().globals().count() > 1);
+
+    session.run(":reset".to_string());
+    assert_eq!(session.environment.stack().globals().count(), 0);
+    assert!(session.timing, "Reset keeps the settings of the session!");
+}
+
+#[test]
+fn test_type_has_no_side_effects() {
+    let mut session = Session::default();
+    session.run("let x: int = 1;".to_string());
+    session.run(":type x = 5".to_string());
+    session.run(":type let y: int = 3;".to_string());
+
+    assert!(matches!(session.environment.get("x").unwrap().value(&session.environment).unwrap(), Object::Integer(1)));
+    assert!(session.environment.get("y").is_none(), "Type declared a variable!");
+    assert_eq!(session.environment.stack().globals().count(), 1);
+}
+
+#[test]
+fn test_highlight() {
+    colored::control::set_override(true);
+    let highlighted = highlight("let a: int = 12;  \"x\"\n  pub");
+    let commented = highlight("1 // one");
+    colored::control::unset_override();
+
+    assert_eq!(
+        highlighted,
+        "\u{1b}[35mlet\u{1b}[0m a: \u{1b}[36mint\u{1b}[0m = \u{1b}[33m12\u{1b}[0m;  \u{1b}[32m\"x\"\u{1b}[0m\n  \u{1b}[35mpub\u{1b}[0m"
+    );
+    assert_eq!(commented, "\u{1b}[33m1\u{1b}[0m \u{1b}[90m// one\u{1b}[0m");
+}
+
+#[test]
+fn test_complete() {
+    let names = vec!["value".to_string(), "variance".to_string(), "math.square".to_string()];
+
+    assert_eq!(complete("let x: int = va", 15, &names), (13, vec!["value".to_string(), "variance".to_string()]));
+    assert_eq!(complete("wh", 2, &names), (0, vec!["while".to_string()]));
+    assert_eq!(complete("math.sq", 7, &names), (0, vec!["math.square".to_string()]));
+    assert_eq!(complete("  :lo", 5, &names), (2, vec![":load".to_string()]));
+    assert_eq!(complete("(i", 2, &names), (1, vec!["if".to_string(), "import".to_string(), "int".to_string()]));
+    assert_eq!(complete("a ", 2, &names), (2, vec![]));
+}
@@ -0,0 +1,145 @@
+use interpreter::error::ResolveError;
+use interpreter::evaluator::environment::Environment;
+use interpreter::evaluator::{execute, Backend, Evaluator};
+use interpreter::lexer::Lexer;
+use interpreter::parser::ast::expression::Expression;
+use interpreter::parser::ast::statement::Statement;
+use interpreter::parser::Parser;
+use interpreter::resolver::{Resolver, Slot};
+
+fn parse(program: &str) -> Vec<Statement> {
+    let tokens = Lexer::from_string(program.to_string()).lex_spanned();
+    Parser::from_spanned_tokens(tokens).parse().unwrap()
+}
+
+#[test]
+fn test_resolve_slots() {
+    let mut ast = parse("let a: int = 1; let b: int = 2; if (true) { let c: int = 3; b + c; }");
+    Resolver::default().resolve(&mut ast).unwrap();
+
+    let Statement::Expression { value } = &ast[2] else { panic!("Expected expression statement!") };
+    let Expression::If { consequence, .. } = value.as_ref() else { panic!("Expected if expression!") };
+    let Expression::Block { statements } = consequence.as_ref() else { panic!("Expected block expression!") };
+    let Statement::Expression { value } = statements[1].as_ref() else { panic!("Expected expression statement!") };
+    let Expression::Infix { left, right, .. } = value.as_ref() else { panic!("Expected infix expression!") };
+
+    assert!(matches!(left.as_ref(), Expression::Identifier { slot: Some(Slot { hops: 0, depth: 1, index: 1 }), .. }));
+    assert!(matches!(right.as_ref(), Expression::Identifier { slot: Some(Slot { hops: 0, depth: 0, index: 0 }), .. }));
+}
+
+#[test]
+fn test_resolve_unknown_identifier() {
+    let mut ast = parse("let a: int = 1;\nlet b: int = a + c;");
+    let err = Resolver::default().resolve(&mut ast).unwrap_err();
+
+    match err.downcast_ref::<ResolveError>() {
+        Some(ResolveError::UnknownIdentifier(name, span)) => {
+            assert_eq!(name, "c");
+            assert_eq!(span.line, 2);
+        }
+        _ => panic!("Expected unknown identifier error!"),
+    }
+}
+
+#[test]
+fn test_resolve_caller_scope_invisible() {
+    let mut ast = parse("function f(): int { local } function g(): int { let local: int = 1; f() }");
+
+    assert!(Resolver::default().resolve(&mut ast).is_err(), "Function can see variables of its caller!");
+}
+
+#[test]
+fn test_resolve_with_environment() {
+    let mut environment = Environment::default();
+
+    let mut ast = parse("let a: int = 1; function double(n: int): int { n * 2 }");
+    Resolver::from_environment(&environment).resolve(&mut ast).unwrap();
+    Evaluator::new(ast.into_iter()).evaluate(&mut environment).unwrap();
+
+    let mut ast = parse("let b: int = double(a);");
+    Resolver::from_environment(&environment).resolve(&mut ast).unwrap();
+    Evaluator::new(ast.into_iter()).evaluate(&mut environment).unwrap();
+
+    let b = environment.get("b").unwrap().value(&environment).unwrap();
+    assert_eq!(format!("{:?}", b), "Integer(2)");
+}
+
+#[test]
+fn test_resolve_mutual_recursion() {
+    let program = "function even(n: int): bool { if (n == 0) { true } else { odd(n - 1) } }
+function odd(n: int): bool { if (n == 0) { false } else { even(n - 1) } }
+let result: bool = even(10);";
+
+    let mut environment = Environment::default();
+    let mut ast = parse(program);
+    Resolver::from_environment(&environment).resolve(&mut ast).unwrap();
+    Evaluator::new(ast.into_iter()).evaluate(&mut environment).unwrap();
+
+    let result = environment.get("result").unwrap().value(&environment).unwrap();
+    assert_eq!(format!("{:?}", result), "Boolean(true)");
+}
+
+#[test]
+fn test_resolve_nested_recursion() {
+    let program = "function outer(): int {
+    function inner(n: int): int { if (n == 0) { ret 0; } ret inner(n - 1) + 1; }
+    function twice(n: int): int { inner(n) * 2 }
+    twice(3)
+}
+let result: int = outer();";
+
+    let mut environment = Environment::default();
+    let mut ast = parse(program);
+    Resolver::from_environment(&environment).resolve(&mut ast).unwrap();
+    Evaluator::new(ast.into_iter()).evaluate(&mut environment).unwrap();
+
+    let result = environment.get("result").unwrap().value(&environment).unwrap();
+    assert_eq!(format!("{:?}", result), "Integer(6)");
+}
+
+#[test]
+fn test_resolve_captured_variables() {
+    let cases = [
+        // The caller declares a variable with the same name, the function still sees the one around its declaration
+        ("function outer(): int {
+    let x: int = 1;
+    function inner(): int { ret x; }
+    function other(): int { let x: int = 99; ret inner(); }
+    ret other();
+}
+let result: int = outer();", "Integer(1)"),
+        // Variables two functions further out and inside of blocks around the declaration
+        ("function outer(n: int): int {
+    let total: int = 0;
+    if (true) {
+        let step: int = n;
+        function middle(): int {
+            function inner(): int { total = total + step; ret total; }
+            let total: int = 100;
+            inner();
+            inner() + total
+        }
+        ret middle() * 1000 + total;
+    }
+}
+let result: int = outer(3);", "Integer(106006)"),
+        // Every recursive call finds the variable of the call of outer it was declared in
+        ("function outer(n: int): int {
+    function inner(): int { if (n == 0) { ret 0; } ret n + outer(n - 1); }
+    inner()
+}
+let result: int = outer(4);", "Integer(10)"),
+    ];
+
+    for (program, expected) in cases {
+        for backend in [Backend::Tree, Backend::Bytecode] {
+            let mut environment = Environment::default();
+            let mut ast = parse(program);
+            Resolver::from_environment(&environment).resolve(&mut ast).unwrap();
+            execute(ast, backend, &mut environment).unwrap();
+
+            let result = environment.get("result").unwrap().value(&environment).unwrap();
+            assert_eq!(format!("{:?}", result), expected, "{:?} failed on {}", backend, program);
+        }
+    }
+}